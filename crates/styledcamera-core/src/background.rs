@@ -0,0 +1,362 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BackgroundFit {
+    /// Fill the frame, cropping the image's overflow (aspect preserved).
+    Cover,
+    /// Show the whole image, letterboxed (aspect preserved).
+    Contain,
+    /// Fill the frame, ignoring aspect.
+    Stretch,
+    /// Repeat the image at its native pixel size from the top-left corner.
+    Tile,
+}
+
+impl BackgroundFit {
+    pub fn from_i64(v: i64) -> Self {
+        match v {
+            1 => BackgroundFit::Contain,
+            2 => BackgroundFit::Stretch,
+            3 => BackgroundFit::Tile,
+            _ => BackgroundFit::Cover,
+        }
+    }
+}
+
+/// Maps frame UVs to image UVs as `uv_image = uv_frame * scale + offset`.
+///
+/// Image UVs outside 0..1 are letterbox (Contain) or wrap around (Tile).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FitTransform {
+    pub scale: [f32; 2],
+    pub offset: [f32; 2],
+}
+
+impl FitTransform {
+    pub const IDENTITY: FitTransform = FitTransform { scale: [1.0, 1.0], offset: [0.0, 0.0] };
+
+    pub fn apply(&self, uv: [f32; 2]) -> [f32; 2] {
+        [
+            uv[0] * self.scale[0] + self.offset[0],
+            uv[1] * self.scale[1] + self.offset[1],
+        ]
+    }
+}
+
+pub fn background_fit_transform(
+    fit: BackgroundFit,
+    image_w: u32,
+    image_h: u32,
+    frame_w: u32,
+    frame_h: u32,
+) -> FitTransform {
+    if image_w == 0 || image_h == 0 || frame_w == 0 || frame_h == 0 {
+        return FitTransform::IDENTITY;
+    }
+
+    let (iw, ih) = (image_w as f32, image_h as f32);
+    let (fw, fh) = (frame_w as f32, frame_h as f32);
+
+    let zoom = match fit {
+        BackgroundFit::Stretch => return FitTransform::IDENTITY,
+        BackgroundFit::Tile => {
+            return FitTransform { scale: [fw / iw, fh / ih], offset: [0.0, 0.0] };
+        }
+        BackgroundFit::Cover => (fw / iw).max(fh / ih),
+        BackgroundFit::Contain => (fw / iw).min(fh / ih),
+    };
+
+    // Size of the zoomed image in frame pixels, centered on the frame.
+    let (zw, zh) = (iw * zoom, ih * zoom);
+    FitTransform {
+        scale: [fw / zw, fh / zh],
+        offset: [(zw - fw) / (2.0 * zw), (zh - fh) / (2.0 * zh)],
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GradientKind {
+    /// Along a direction given by the angle (0 = left to right, clockwise in degrees).
+    Linear,
+    /// From the frame center to its corners.
+    Radial,
+    /// Around the frame center, starting at the angle.
+    Conic,
+}
+
+pub const MAX_GRADIENT_STOPS: usize = 4;
+
+/// Gradient position (0..1) of a frame UV; mirrors `GradientT` in background_generate.effect.
+pub fn gradient_t(kind: GradientKind, uv: [f32; 2], size: [f32; 2], angle_deg: f32) -> f32 {
+    let size = [size[0].max(1.0), size[1].max(1.0)];
+    // Pixel position relative to the center, y down.
+    let p = [(uv[0] - 0.5) * size[0], (uv[1] - 0.5) * size[1]];
+    let a = angle_deg.to_radians();
+
+    let t = match kind {
+        GradientKind::Linear => {
+            let dir = [a.cos(), a.sin()];
+            // Half the frame's extent along the direction, so the corners land on 0 and 1.
+            let extent = (dir[0].abs() * size[0] + dir[1].abs() * size[1]) * 0.5;
+            (p[0] * dir[0] + p[1] * dir[1]) / (2.0 * extent.max(1e-6)) + 0.5
+        }
+        GradientKind::Radial => {
+            let r = (p[0] * p[0] + p[1] * p[1]).sqrt();
+            let corner = (size[0] * size[0] + size[1] * size[1]).sqrt() * 0.5;
+            r / corner
+        }
+        GradientKind::Conic => {
+            let turn = (p[1].atan2(p[0]) - a) / std::f32::consts::TAU;
+            turn - turn.floor()
+        }
+    };
+    t.clamp(0.0, 1.0)
+}
+
+/// Color at `t` for evenly spaced stops; mirrors `SampleStops` in background_generate.effect.
+pub fn sample_gradient(stops: &[[f32; 4]], t: f32) -> [f32; 4] {
+    let n = stops.len().min(MAX_GRADIENT_STOPS);
+    match n {
+        0 => return [0.0, 0.0, 0.0, 1.0],
+        1 => return stops[0],
+        _ => {}
+    }
+
+    let x = t.clamp(0.0, 1.0) * (n - 1) as f32;
+    let i = (x.floor() as usize).min(n - 2);
+    let f = x - i as f32;
+    let (a, b) = (stops[i], stops[i + 1]);
+    [
+        a[0] + (b[0] - a[0]) * f,
+        a[1] + (b[1] - a[1]) * f,
+        a[2] + (b[2] - a[2]) * f,
+        a[3] + (b[3] - a[3]) * f,
+    ]
+}
+
+/// Loop length of the animated backgrounds in seconds of animation time; mirrors `ANIM_PERIOD`
+/// in background_generate.effect, where every motion completes whole cycles per loop.
+pub const GENERATED_ANIM_PERIOD_S: f64 = 6400.0;
+
+/// Advances the animation clock by `seconds` (already scaled by speed), wrapping at the loop
+/// length so the shader's f32 time stays precise without a visible jump.
+pub fn advance_anim_time(time: f64, seconds: f64) -> f64 {
+    (time + seconds).rem_euclid(GENERATED_ANIM_PERIOD_S)
+}
+
+/// Running-average rate of the clean background plate for an averaging window in frames.
+pub fn plate_blend(frames: u32) -> f32 {
+    1.0 / frames.max(1) as f32
+}
+
+/// One clean-plate update of a pixel; mirrors `PSAccumulate` in background_plate.effect.
+///
+/// `prev` is the plate (rgb, seen) where `seen` tells how much background this pixel has shown
+/// so far; `background` is the frame's background confidence (0 = person). Unseen pixels take
+/// their first background sample outright, then settle into the running average.
+pub fn accumulate_plate(prev: [f32; 4], frame: [f32; 3], background: f32, blend: f32) -> [f32; 4] {
+    let background = background.clamp(0.0, 1.0);
+    let seen = prev[3].clamp(0.0, 1.0);
+    let rate = background * blend.clamp(0.0, 1.0).max(1.0 - seen);
+    [
+        prev[0] + (frame[0] - prev[0]) * rate,
+        prev[1] + (frame[1] - prev[1]) * rate,
+        prev[2] + (frame[2] - prev[2]) * rate,
+        seen + (1.0 - seen) * background,
+    ]
+}
+
+/// Color levels per channel left by the privacy background's scramble style.
+pub const PRIVACY_LEVELS: f32 = 4.0;
+
+/// Mosaic block size in UV for a block given in pixels of a 1080p frame. Scaling with the frame
+/// height keeps the mosaic equally coarse (and the background equally unreadable) at any
+/// resolution; blocks stay square in pixels and at least 2 pixels wide.
+pub fn privacy_block_uv(block_px_1080p: f32, width: u32, height: u32) -> [f32; 2] {
+    let (w, h) = (width.max(1) as f32, height.max(1) as f32);
+    let px = (block_px_1080p * h / 1080.0).max(2.0);
+    [(px / w).min(1.0), (px / h).min(1.0)]
+}
+
+/// Scramble style: a block's average quantized to `PRIVACY_LEVELS` after a per-block offset
+/// (`noise` in 0..1); mirrors `Scramble` in background_privacy.effect.
+pub fn scramble_channel(v: f32, noise: f32) -> f32 {
+    let steps = PRIVACY_LEVELS - 1.0;
+    let dithered = v + (noise - 0.5) / steps;
+    ((dithered * steps + 0.5).floor() / steps).clamp(0.0, 1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx(a: [f32; 2], b: [f32; 2]) -> bool {
+        (a[0] - b[0]).abs() < 1e-5 && (a[1] - b[1]).abs() < 1e-5
+    }
+
+    #[test]
+    fn stretch_is_identity() {
+        let t = background_fit_transform(BackgroundFit::Stretch, 640, 480, 1920, 1080);
+        assert_eq!(t, FitTransform::IDENTITY);
+    }
+
+    #[test]
+    fn cover_crops_the_overflowing_axis() {
+        // 4:3 image in a 16:9 frame: full width, top/bottom cropped.
+        let t = background_fit_transform(BackgroundFit::Cover, 800, 600, 1600, 900);
+        assert!(approx(t.apply([0.0, 0.0]), [0.0, 0.125]));
+        assert!(approx(t.apply([1.0, 1.0]), [1.0, 0.875]));
+        assert!(approx(t.apply([0.5, 0.5]), [0.5, 0.5]));
+    }
+
+    #[test]
+    fn contain_letterboxes_the_short_axis() {
+        // 4:3 image in a 16:9 frame: full height, pillarboxed.
+        let t = background_fit_transform(BackgroundFit::Contain, 800, 600, 1600, 900);
+        let left = t.apply([0.0, 0.0]);
+        let right = t.apply([1.0, 1.0]);
+        assert!(left[0] < 0.0 && right[0] > 1.0);
+        assert!(approx([left[1], right[1]], [0.0, 1.0]));
+        // Image spans 1200 of 1600 frame pixels: starts at 200 px.
+        let start = t.apply([200.0 / 1600.0, 0.5]);
+        assert!(approx(start, [0.0, 0.5]));
+    }
+
+    #[test]
+    fn tile_repeats_at_native_size() {
+        let t = background_fit_transform(BackgroundFit::Tile, 100, 50, 1000, 500);
+        assert!(approx(t.apply([1.0, 1.0]), [10.0, 10.0]));
+        assert!(approx(t.apply([0.0, 0.0]), [0.0, 0.0]));
+    }
+
+    #[test]
+    fn degenerate_sizes_fall_back_to_identity() {
+        assert_eq!(
+            background_fit_transform(BackgroundFit::Cover, 0, 480, 1920, 1080),
+            FitTransform::IDENTITY
+        );
+        assert_eq!(
+            background_fit_transform(BackgroundFit::Contain, 640, 480, 0, 0),
+            FitTransform::IDENTITY
+        );
+    }
+
+    #[test]
+    fn fit_from_setting_value() {
+        assert_eq!(BackgroundFit::from_i64(0), BackgroundFit::Cover);
+        assert_eq!(BackgroundFit::from_i64(3), BackgroundFit::Tile);
+        assert_eq!(BackgroundFit::from_i64(42), BackgroundFit::Cover);
+    }
+
+    const RED: [f32; 4] = [1.0, 0.0, 0.0, 1.0];
+    const GREEN: [f32; 4] = [0.0, 1.0, 0.0, 1.0];
+    const BLUE: [f32; 4] = [0.0, 0.0, 1.0, 1.0];
+
+    #[test]
+    fn gradient_interpolates_evenly_spaced_stops() {
+        let stops = [RED, GREEN, BLUE];
+        assert_eq!(sample_gradient(&stops, 0.0), RED);
+        assert_eq!(sample_gradient(&stops, 0.5), GREEN);
+        assert_eq!(sample_gradient(&stops, 1.0), BLUE);
+        let c = sample_gradient(&stops, 0.25);
+        assert!((c[0] - 0.5).abs() < 1e-6 && (c[1] - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn gradient_handles_few_or_many_stops() {
+        assert_eq!(sample_gradient(&[], 0.3), [0.0, 0.0, 0.0, 1.0]);
+        assert_eq!(sample_gradient(&[RED], 0.7), RED);
+        // Stops beyond the supported maximum are ignored.
+        let stops = [RED, GREEN, BLUE, RED, BLUE];
+        assert_eq!(sample_gradient(&stops, 1.0), RED);
+    }
+
+    #[test]
+    fn anim_time_wraps_at_the_loop_length() {
+        assert_eq!(advance_anim_time(10.0, 0.5), 10.5);
+        let t = advance_anim_time(GENERATED_ANIM_PERIOD_S - 0.25, 0.5);
+        assert!((t - 0.25).abs() < 1e-9);
+        assert!(advance_anim_time(0.0, GENERATED_ANIM_PERIOD_S * 3.0 + 1.0) < 1.0 + 1e-9);
+    }
+
+    #[test]
+    fn linear_gradient_spans_the_frame() {
+        let size = [1920.0, 1080.0];
+        assert!((gradient_t(GradientKind::Linear, [0.0, 0.5], size, 0.0)).abs() < 1e-5);
+        assert!((gradient_t(GradientKind::Linear, [1.0, 0.5], size, 0.0) - 1.0).abs() < 1e-5);
+        // 90 degrees runs top to bottom.
+        assert!((gradient_t(GradientKind::Linear, [0.3, 1.0], size, 90.0) - 1.0).abs() < 1e-5);
+        // Diagonal: opposite corners reach the ends.
+        assert!((gradient_t(GradientKind::Linear, [1.0, 1.0], size, 30.0) - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn radial_gradient_reaches_one_at_corners() {
+        let size = [1280.0, 720.0];
+        assert_eq!(gradient_t(GradientKind::Radial, [0.5, 0.5], size, 0.0), 0.0);
+        assert!((gradient_t(GradientKind::Radial, [0.0, 1.0], size, 0.0) - 1.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn conic_gradient_turns_clockwise_from_angle() {
+        let size = [1000.0, 1000.0];
+        assert!(gradient_t(GradientKind::Conic, [1.0, 0.5], size, 0.0).abs() < 1e-5);
+        assert!((gradient_t(GradientKind::Conic, [0.5, 1.0], size, 0.0) - 0.25).abs() < 1e-5);
+        assert!((gradient_t(GradientKind::Conic, [0.0, 0.5], size, 0.0) - 0.5).abs() < 1e-5);
+        assert!((gradient_t(GradientKind::Conic, [0.5, 1.0], size, 90.0)).abs() < 1e-5);
+    }
+
+    #[test]
+    fn unseen_plate_pixels_take_the_first_background_sample() {
+        let p = accumulate_plate([0.0; 4], [0.2, 0.4, 0.6], 1.0, plate_blend(30));
+        assert_eq!(p, [0.2, 0.4, 0.6, 1.0]);
+    }
+
+    #[test]
+    fn person_pixels_leave_the_plate_alone() {
+        let prev = [0.1, 0.2, 0.3, 1.0];
+        assert_eq!(accumulate_plate(prev, [1.0, 1.0, 1.0], 0.0, 0.5), prev);
+        // Nothing seen yet stays unseen.
+        assert_eq!(accumulate_plate([0.0; 4], [1.0, 1.0, 1.0], 0.0, 0.5)[3], 0.0);
+    }
+
+    #[test]
+    fn seen_plate_is_a_running_average() {
+        let blend = plate_blend(4);
+        let mut p = [0.0, 0.0, 0.0, 1.0];
+        p = accumulate_plate(p, [1.0, 1.0, 1.0], 1.0, blend);
+        assert!((p[0] - 0.25).abs() < 1e-6);
+        for _ in 0..100 {
+            p = accumulate_plate(p, [1.0, 1.0, 1.0], 1.0, blend);
+        }
+        assert!((p[0] - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn plate_blend_guards_zero_frames() {
+        assert_eq!(plate_blend(0), 1.0);
+        assert_eq!(plate_blend(10), 0.1);
+    }
+
+    #[test]
+    fn privacy_blocks_scale_with_resolution() {
+        let hd = privacy_block_uv(32.0, 1920, 1080);
+        let uhd = privacy_block_uv(32.0, 3840, 2160);
+        assert!((hd[0] - uhd[0]).abs() < 1e-6 && (hd[1] - uhd[1]).abs() < 1e-6);
+        // Square in pixels.
+        assert!((hd[0] * 1920.0 - hd[1] * 1080.0).abs() < 1e-3);
+        // Never below two pixels.
+        assert_eq!(privacy_block_uv(1.0, 640, 360), [2.0 / 640.0, 2.0 / 360.0]);
+    }
+
+    #[test]
+    fn scramble_leaves_only_a_few_levels() {
+        for i in 0..=20 {
+            for n in [0.0, 0.3, 0.99] {
+                let v = scramble_channel(i as f32 / 20.0, n);
+                let steps = v * (PRIVACY_LEVELS - 1.0);
+                assert!((steps - steps.round()).abs() < 1e-5, "{v}");
+            }
+        }
+        assert_eq!(scramble_channel(0.0, 0.5), 0.0);
+        assert_eq!(scramble_channel(1.0, 0.5), 1.0);
+    }
+}
@@ -0,0 +1,490 @@
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlurQuality {
+    /// Fewest pyramid levels; wide tap offsets.
+    Fast,
+    Balanced,
+    /// Most pyramid levels; tight tap offsets, smoothest result.
+    High,
+}
+
+impl BlurQuality {
+    pub fn from_i64(v: i64) -> Self {
+        match v {
+            0 => BlurQuality::Fast,
+            2 => BlurQuality::High,
+            _ => BlurQuality::Balanced,
+        }
+    }
+
+    pub fn max_levels(self) -> u32 {
+        match self {
+            BlurQuality::Fast => 5,
+            BlurQuality::Balanced => 6,
+            BlurQuality::High => 7,
+        }
+    }
+
+    /// Largest tap offset (in source texels) before another level is added.
+    pub fn max_offset(self) -> f32 {
+        match self {
+            BlurQuality::Fast => 3.0,
+            BlurQuality::Balanced => 2.0,
+            BlurQuality::High => 1.25,
+        }
+    }
+}
+
+/// Blur radius at full intensity, in texels of the pyramid's base level.
+pub const MAX_BLUR_RADIUS: f32 = 96.0;
+
+// Smallest edge a pyramid level may have.
+const MIN_LEVEL_SIZE: u32 = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BlurPyramidPlan {
+    /// Down/up level pairs; 0 means no blur.
+    pub levels: u32,
+    /// Tap offset passed to the DualDown/DualUp passes.
+    pub offset: f32,
+}
+
+/// Picks the dual-filter pyramid for a blur intensity (0..1) on a `base_w` x `base_h` base level.
+///
+/// The radius is roughly `offset * 2^levels` base texels. Levels are added until the offset
+/// needed for the radius fits the quality's limit, capped by the preset and the frame size.
+pub fn blur_pyramid_plan(
+    intensity: f32,
+    quality: BlurQuality,
+    base_w: u32,
+    base_h: u32,
+) -> BlurPyramidPlan {
+    let none = BlurPyramidPlan {
+        levels: 0,
+        offset: 0.0,
+    };
+    if intensity.is_nan() || intensity <= 0.0001 {
+        return none;
+    }
+
+    let mut size_levels = 0;
+    let mut edge = base_w.min(base_h);
+    while edge / 2 >= MIN_LEVEL_SIZE {
+        edge /= 2;
+        size_levels += 1;
+    }
+    let max_levels = quality.max_levels().min(size_levels);
+    if max_levels == 0 {
+        return none;
+    }
+
+    let radius = intensity.min(1.0) * MAX_BLUR_RADIUS;
+    let max_offset = quality.max_offset();
+    let mut levels = 1;
+    while levels < max_levels && radius > max_offset * (1u32 << levels) as f32 {
+        levels += 1;
+    }
+
+    BlurPyramidPlan {
+        levels,
+        offset: (radius / (1u32 << levels) as f32).min(max_offset),
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BokehShape {
+    Disk,
+    /// Six-bladed aperture, flat edge at the top and bottom.
+    Hexagon,
+}
+
+impl BokehShape {
+    pub fn from_i64(v: i64) -> Self {
+        match v {
+            1 => BokehShape::Hexagon,
+            _ => BokehShape::Disk,
+        }
+    }
+}
+
+/// Taps of the lens blur kernel; matches `BOKEH_TAPS` in bokeh_blur.effect.
+pub const BOKEH_TAPS: usize = 48;
+
+const GOLDEN_ANGLE: f32 = 2.399_963;
+
+/// Distance from the center to a flat-topped regular hexagon's edge (unit circumradius)
+/// along direction `theta`.
+pub fn hexagon_radius(theta: f32) -> f32 {
+    let sector = std::f32::consts::FRAC_PI_3;
+    let a = theta.rem_euclid(sector) - sector * 0.5;
+    (sector * 0.5).cos() / a.cos()
+}
+
+/// Offset of lens blur tap `i` in units of the blur radius; mirrors `BokehTap` in
+/// bokeh_blur.effect. Taps follow a golden-angle spiral, which covers the aperture evenly
+/// without a regular grid's ringing.
+pub fn bokeh_tap(i: usize, shape: BokehShape) -> [f32; 2] {
+    let r = ((i as f32 + 0.5) / BOKEH_TAPS as f32).sqrt();
+    let theta = i as f32 * GOLDEN_ANGLE;
+    let r = match shape {
+        BokehShape::Disk => r,
+        BokehShape::Hexagon => r * hexagon_radius(theta),
+    };
+    [r * theta.cos(), r * theta.sin()]
+}
+
+// Luma where highlights start to bloom, and the weight of a full-white tap at boost 1.
+const BOKEH_HIGHLIGHT_START: f32 = 0.7;
+const BOKEH_HIGHLIGHT_GAIN: f32 = 8.0;
+
+/// Kernel weight of a tap with the given luma; mirrors `HighlightWeight` in bokeh_blur.effect.
+/// Bright taps outweigh their neighbors so small lights spread into aperture-shaped balls.
+pub fn bokeh_highlight_weight(luma: f32, boost: f32) -> f32 {
+    let t = ((luma - BOKEH_HIGHLIGHT_START) / (1.0 - BOKEH_HIGHLIGHT_START)).clamp(0.0, 1.0);
+    1.0 + boost.max(0.0) * BOKEH_HIGHLIGHT_GAIN * t * t
+}
+
+// Below this accumulated background weight a pixel has no usable background around it.
+const MIN_BACKGROUND_WEIGHT: f32 = 1e-4;
+
+/// Background weight of a refined mask sample: 1 for background, 0 for the person.
+pub fn background_weight(coverage: f32) -> f32 {
+    1.0 - coverage.clamp(0.0, 1.0)
+}
+
+/// CPU reference for the mask-aware pyramid blur (normalized convolution).
+///
+/// `rgb` and `coverage` (the refined person mask, as `RefineMask`) are the pyramid's base level.
+/// Mirrors the GPU passes: colors are premultiplied by their background weight
+/// (`DownsampleMasked`), color and weight run through `plan.levels` DualDown and DualUp passes
+/// together (blur_pass.effect), and `PSComposite` divides the blurred weight back out. Pixels
+/// with no background nearby keep their own color.
+pub fn mask_weighted_blur(
+    rgb: &[[f32; 3]],
+    coverage: &[f32],
+    width: usize,
+    height: usize,
+    plan: BlurPyramidPlan,
+) -> Vec<[f32; 3]> {
+    let n = width * height;
+    if n == 0 || rgb.len() != n || coverage.len() != n {
+        return rgb.to_vec();
+    }
+
+    let weighted: Vec<[f32; 4]> = rgb
+        .iter()
+        .zip(coverage)
+        .map(|(c, &m)| {
+            let w = background_weight(m);
+            [c[0] * w, c[1] * w, c[2] * w, w]
+        })
+        .collect();
+
+    let mut sizes = vec![(width, height)];
+    for i in 0..plan.levels as usize {
+        let (w, h) = sizes[i];
+        sizes.push(((w / 2).max(1), (h / 2).max(1)));
+    }
+    let mut level = Level { data: weighted, width, height };
+    for &size in &sizes[1..] {
+        level = dual_pass(&level, size, plan.offset, &DUAL_DOWN_TAPS);
+    }
+    for &size in sizes[..sizes.len() - 1].iter().rev() {
+        level = dual_pass(&level, size, plan.offset, &DUAL_UP_TAPS);
+    }
+
+    level
+        .data
+        .iter()
+        .zip(rgb)
+        .map(|(p, c)| {
+            if p[3] > MIN_BACKGROUND_WEIGHT {
+                [p[0] / p[3], p[1] / p[3], p[2] / p[3]]
+            } else {
+                *c
+            }
+        })
+        .collect()
+}
+
+struct Level {
+    data: Vec<[f32; 4]>,
+    width: usize,
+    height: usize,
+}
+
+// (x, y, weight) per tap, in units of the pass's tap offset; mirrors PSDualDown / PSDualUp.
+const DUAL_DOWN_TAPS: [(f32, f32, f32); 5] = [
+    (0.0, 0.0, 4.0 / 8.0),
+    (-1.0, -1.0, 1.0 / 8.0),
+    (1.0, -1.0, 1.0 / 8.0),
+    (-1.0, 1.0, 1.0 / 8.0),
+    (1.0, 1.0, 1.0 / 8.0),
+];
+const DUAL_UP_TAPS: [(f32, f32, f32); 8] = [
+    (-2.0, 0.0, 1.0 / 12.0),
+    (2.0, 0.0, 1.0 / 12.0),
+    (0.0, -2.0, 1.0 / 12.0),
+    (0.0, 2.0, 1.0 / 12.0),
+    (-1.0, -1.0, 2.0 / 12.0),
+    (1.0, -1.0, 2.0 / 12.0),
+    (-1.0, 1.0, 2.0 / 12.0),
+    (1.0, 1.0, 2.0 / 12.0),
+];
+
+// Renders `src` into a `size` level with the given taps; the offset is in source texels.
+fn dual_pass(
+    src: &Level,
+    size: (usize, usize),
+    offset: f32,
+    taps: &[(f32, f32, f32)],
+) -> Level {
+    let (width, height) = size;
+    let ox = (0.5 + offset) / src.width as f32;
+    let oy = (0.5 + offset) / src.height as f32;
+    let mut data = Vec::with_capacity(width * height);
+    for y in 0..height {
+        for x in 0..width {
+            let u = (x as f32 + 0.5) / width as f32;
+            let v = (y as f32 + 0.5) / height as f32;
+            let mut sum = [0.0; 4];
+            for &(tx, ty, w) in taps {
+                let p = sample_bilinear(src, u + tx * ox, v + ty * oy);
+                for c in 0..4 {
+                    sum[c] += p[c] * w;
+                }
+            }
+            data.push(sum);
+        }
+    }
+    Level { data, width, height }
+}
+
+// Linear filtering with clamp addressing, like the effects' linear_clamp_sampler.
+fn sample_bilinear(src: &Level, u: f32, v: f32) -> [f32; 4] {
+    let x = u * src.width as f32 - 0.5;
+    let y = v * src.height as f32 - 0.5;
+    let (fx, fy) = (x - x.floor(), y - y.floor());
+    let texel = |x: f32, y: f32| {
+        let x = (x as isize).clamp(0, src.width as isize - 1) as usize;
+        let y = (y as isize).clamp(0, src.height as isize - 1) as usize;
+        src.data[y * src.width + x]
+    };
+    let (x0, y0) = (x.floor(), y.floor());
+    let (a, b) = (texel(x0, y0), texel(x0 + 1.0, y0));
+    let (c, d) = (texel(x0, y0 + 1.0), texel(x0 + 1.0, y0 + 1.0));
+    let mut out = [0.0; 4];
+    for i in 0..4 {
+        let top = a[i] + (b[i] - a[i]) * fx;
+        let bottom = c[i] + (d[i] - c[i]) * fx;
+        out[i] = top + (bottom - top) * fy;
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RED: [f32; 3] = [1.0, 0.0, 0.0];
+    const BLUE: [f32; 3] = [0.0, 0.0, 1.0];
+
+    // Person (red, mask 1) on the left `split` columns, background (blue, mask 0) elsewhere.
+    fn split_frame(width: usize, height: usize, split: usize) -> (Vec<[f32; 3]>, Vec<f32>) {
+        let mut rgb = Vec::new();
+        let mut mask = Vec::new();
+        for _ in 0..height {
+            for x in 0..width {
+                let person = x < split;
+                rgb.push(if person { RED } else { BLUE });
+                mask.push(if person { 1.0 } else { 0.0 });
+            }
+        }
+        (rgb, mask)
+    }
+
+    #[test]
+    fn zero_intensity_needs_no_levels() {
+        let plan = blur_pyramid_plan(0.0, BlurQuality::Balanced, 960, 540);
+        assert_eq!(plan.levels, 0);
+    }
+
+    #[test]
+    fn levels_grow_with_intensity() {
+        let mut last = 0;
+        for i in 1..=20 {
+            let plan = blur_pyramid_plan(i as f32 / 20.0, BlurQuality::Balanced, 960, 540);
+            assert!(plan.levels >= last.max(1));
+            assert!(plan.offset <= BlurQuality::Balanced.max_offset());
+            last = plan.levels;
+        }
+        assert_eq!(last, BlurQuality::Balanced.max_levels());
+    }
+
+    #[test]
+    fn full_intensity_reaches_max_radius_on_every_preset() {
+        for quality in [BlurQuality::Fast, BlurQuality::Balanced, BlurQuality::High] {
+            let plan = blur_pyramid_plan(1.0, quality, 960, 540);
+            let radius = plan.offset * (1u32 << plan.levels) as f32;
+            assert!(
+                (radius - MAX_BLUR_RADIUS).abs() < 1e-3,
+                "{quality:?}: {plan:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn higher_quality_uses_more_levels_and_smaller_offsets() {
+        let fast = blur_pyramid_plan(0.8, BlurQuality::Fast, 960, 540);
+        let high = blur_pyramid_plan(0.8, BlurQuality::High, 960, 540);
+        assert!(high.levels > fast.levels);
+        assert!(high.offset < fast.offset);
+    }
+
+    #[test]
+    fn small_frames_cap_levels() {
+        let plan = blur_pyramid_plan(1.0, BlurQuality::High, 64, 16);
+        // 16 -> 8 -> 4 -> 2
+        assert_eq!(plan.levels, 3);
+        assert_eq!(blur_pyramid_plan(1.0, BlurQuality::High, 3, 3).levels, 0);
+    }
+
+    #[test]
+    fn quality_from_setting_value() {
+        assert_eq!(BlurQuality::from_i64(0), BlurQuality::Fast);
+        assert_eq!(BlurQuality::from_i64(2), BlurQuality::High);
+        assert_eq!(BlurQuality::from_i64(7), BlurQuality::Balanced);
+    }
+
+    #[test]
+    fn disk_taps_cover_the_unit_disk() {
+        let taps: Vec<_> = (0..BOKEH_TAPS)
+            .map(|i| bokeh_tap(i, BokehShape::Disk))
+            .collect();
+        let radii: Vec<f32> = taps
+            .iter()
+            .map(|t| (t[0] * t[0] + t[1] * t[1]).sqrt())
+            .collect();
+        assert!(radii.iter().all(|&r| r <= 1.0));
+        assert!(radii[0] < 0.2);
+        assert!(radii[BOKEH_TAPS - 1] > 0.95);
+        // Roughly centered: the spiral doesn't favor a side.
+        let mean_x = taps.iter().map(|t| t[0]).sum::<f32>() / BOKEH_TAPS as f32;
+        let mean_y = taps.iter().map(|t| t[1]).sum::<f32>() / BOKEH_TAPS as f32;
+        assert!(mean_x.abs() < 0.1 && mean_y.abs() < 0.1);
+    }
+
+    #[test]
+    fn hexagon_radius_spans_apothem_to_corner() {
+        use std::f32::consts::FRAC_PI_3;
+        // Corners at multiples of 60 degrees, edge midpoints in between.
+        assert!((hexagon_radius(0.0) - 1.0).abs() < 1e-5);
+        assert!((hexagon_radius(2.0 * FRAC_PI_3) - 1.0).abs() < 1e-5);
+        assert!((hexagon_radius(FRAC_PI_3 * 0.5) - 0.866_025).abs() < 1e-5);
+        assert!((hexagon_radius(-FRAC_PI_3 * 0.5) - 0.866_025).abs() < 1e-5);
+    }
+
+    #[test]
+    fn hexagon_taps_stay_inside_the_hexagon() {
+        for i in 0..BOKEH_TAPS {
+            let t = bokeh_tap(i, BokehShape::Hexagon);
+            let r = (t[0] * t[0] + t[1] * t[1]).sqrt();
+            assert!(r <= hexagon_radius(t[1].atan2(t[0])) + 1e-5);
+        }
+    }
+
+    #[test]
+    fn highlight_weight_only_boosts_bright_taps() {
+        assert_eq!(bokeh_highlight_weight(0.5, 1.0), 1.0);
+        assert_eq!(bokeh_highlight_weight(1.0, 0.0), 1.0);
+        assert!(bokeh_highlight_weight(0.9, 1.0) > 1.0);
+        assert!(bokeh_highlight_weight(1.0, 1.0) > bokeh_highlight_weight(0.9, 1.0));
+        assert!((bokeh_highlight_weight(1.0, 1.0) - 9.0).abs() < 1e-5);
+    }
+
+    #[test]
+    fn bokeh_shape_from_setting_value() {
+        assert_eq!(BokehShape::from_i64(1), BokehShape::Hexagon);
+        assert_eq!(BokehShape::from_i64(0), BokehShape::Disk);
+        assert_eq!(BokehShape::from_i64(-3), BokehShape::Disk);
+    }
+
+    fn plan(levels: u32, offset: f32) -> BlurPyramidPlan {
+        BlurPyramidPlan { levels, offset }
+    }
+
+    #[test]
+    fn weighting_carries_no_person_color_into_the_background() {
+        let (rgb, mask) = split_frame(32, 8, 12);
+        for p in [plan(1, 0.0), plan(2, 1.0), plan(3, 2.0)] {
+            let out = mask_weighted_blur(&rgb, &mask, 32, 8, p);
+            for (i, c) in out.iter().enumerate() {
+                // Only background contributes color: any blurred pixel is pure background.
+                if c != &RED {
+                    assert!(c[0].abs() < 1e-5 && (c[2] - 1.0).abs() < 1e-4, "{p:?} {i}: {c:?}");
+                }
+                if mask[i] == 0.0 {
+                    assert!(c[0].abs() < 1e-5, "red leaked at {i}: {c:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unweighted_blur_would_bleed() {
+        // Sanity check of the test setup: with an all-background mask the person's color spreads.
+        let (rgb, _) = split_frame(32, 8, 12);
+        let out = mask_weighted_blur(&rgb, &[0.0; 256], 32, 8, plan(2, 1.0));
+        assert!(out[12][0] > 0.1);
+    }
+
+    #[test]
+    fn shipped_plans_keep_the_background_clean() {
+        let (rgb, mask) = split_frame(64, 16, 24);
+        for quality in [BlurQuality::Fast, BlurQuality::Balanced, BlurQuality::High] {
+            let p = blur_pyramid_plan(1.0, quality, 64, 16);
+            assert!(p.levels > 0);
+            let out = mask_weighted_blur(&rgb, &mask, 64, 16, p);
+            let c = out[24];
+            assert!(c[0].abs() < 1e-5, "{quality:?}: red leaked: {c:?}");
+        }
+    }
+
+    #[test]
+    fn no_levels_keeps_the_frame() {
+        let (rgb, mask) = split_frame(8, 2, 3);
+        assert_eq!(mask_weighted_blur(&rgb, &mask, 8, 2, plan(0, 0.0)), rgb);
+    }
+
+    #[test]
+    fn pyramid_spreads_the_background() {
+        // A single bright background texel spreads over its neighbours.
+        let mut rgb = vec![[0.0; 3]; 32 * 32];
+        rgb[16 * 32 + 16] = [1.0, 1.0, 1.0];
+        let out = mask_weighted_blur(&rgb, &[0.0; 32 * 32], 32, 32, plan(1, 0.0));
+        assert!(out[16 * 32 + 16][0] < 1.0);
+        assert!(out[16 * 32 + 17][0] > 0.0 && out[15 * 32 + 16][0] > 0.0);
+        let total: f32 = out.iter().map(|c| c[0]).sum();
+        assert!((total - 1.0).abs() < 0.05, "energy {total}");
+    }
+
+    #[test]
+    fn constant_background_is_preserved() {
+        let rgb = vec![[0.25, 0.5, 0.75]; 36];
+        let mut mask = vec![0.0; 36];
+        mask[14] = 0.6;
+        let out = mask_weighted_blur(&rgb, &mask, 6, 6, plan(2, 1.0));
+        for c in out {
+            assert!(
+                (c[0] - 0.25).abs() < 1e-5
+                    && (c[1] - 0.5).abs() < 1e-5
+                    && (c[2] - 0.75).abs() < 1e-5
+            );
+        }
+    }
+
+    #[test]
+    fn mismatched_buffers_are_returned_unchanged() {
+        let rgb = vec![RED; 4];
+        assert_eq!(mask_weighted_blur(&rgb, &[0.0; 3], 2, 2, plan(1, 0.0)), rgb);
+    }
+}
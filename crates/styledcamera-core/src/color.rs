@@ -8,6 +8,106 @@ pub fn obs_abgr_to_rgba_vec4(abgr: u32) -> [f32; 4] {
     [r, g, b, a]
 }
 
+/// Luma weights used for saturation (and the shaders' `Desaturate`).
+pub const LUMA_WEIGHTS: [f32; 3] = [0.299, 0.587, 0.114];
+
+/// Per-layer color grade. Every control is neutral at 0.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Grade {
+    /// Stops (-3..3).
+    pub exposure: f32,
+    /// -1..1 around mid grey.
+    pub contrast: f32,
+    /// -1 (grey) .. 1 (double).
+    pub saturation: f32,
+    /// -1 (cool) .. 1 (warm).
+    pub temperature: f32,
+    /// -1 (green) .. 1 (magenta).
+    pub tint: f32,
+    /// 0..1 darkening towards the frame corners.
+    pub vignette: f32,
+}
+
+impl Grade {
+    pub fn is_neutral(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn exposure_gain(&self) -> f32 {
+        self.exposure.clamp(-3.0, 3.0).exp2()
+    }
+
+    /// RGB multipliers for temperature and tint.
+    pub fn white_balance(&self) -> [f32; 3] {
+        let t = self.temperature.clamp(-1.0, 1.0);
+        let g = self.tint.clamp(-1.0, 1.0);
+        [
+            1.0 + 0.2 * t + 0.1 * g,
+            1.0 - 0.2 * g,
+            1.0 - 0.2 * t + 0.1 * g,
+        ]
+    }
+
+    /// Packed as the shaders' grade params: (exposure gain, contrast, saturation, vignette).
+    pub fn shader_params(&self) -> [f32; 4] {
+        [
+            self.exposure_gain(),
+            self.contrast.clamp(-1.0, 1.0),
+            self.saturation.clamp(-1.0, 1.0),
+            self.vignette.clamp(0.0, 1.0),
+        ]
+    }
+}
+
+/// Vignette multiplier at `uv` (0..1, y down) of a frame with `aspect` = width / height.
+pub fn vignette_factor(uv: [f32; 2], aspect: f32, amount: f32) -> f32 {
+    let amount = amount.clamp(0.0, 1.0);
+    if amount <= 0.0 {
+        return 1.0;
+    }
+    let p = [(uv[0] - 0.5) * aspect, uv[1] - 0.5];
+    let corner = (0.25 * aspect * aspect + 0.25).sqrt();
+    let r = (p[0] * p[0] + p[1] * p[1]).sqrt() / corner;
+    let t = ((r - 0.4) / 0.6).clamp(0.0, 1.0);
+    1.0 - amount * t * t * (3.0 - 2.0 * t)
+}
+
+// Heatmap ramp stops, evenly spaced: dark blue, cyan, yellow, red.
+const HEATMAP_STOPS: [[f32; 3]; 4] = [
+    [0.0, 0.0, 0.3],
+    [0.0, 0.8, 1.0],
+    [1.0, 0.9, 0.0],
+    [1.0, 0.1, 0.0],
+];
+
+/// Heatmap color for `t` in 0..1 (cold to hot); mirrors `Heatmap` in mask_debug.effect.
+pub fn heatmap_rgb(t: f32) -> [f32; 3] {
+    let x = t.clamp(0.0, 1.0) * (HEATMAP_STOPS.len() - 1) as f32;
+    let i = (x.floor() as usize).min(HEATMAP_STOPS.len() - 2);
+    let f = x - i as f32;
+    let (a, b) = (HEATMAP_STOPS[i], HEATMAP_STOPS[i + 1]);
+    [
+        a[0] + (b[0] - a[0]) * f,
+        a[1] + (b[1] - a[1]) * f,
+        a[2] + (b[2] - a[2]) * f,
+    ]
+}
+
+/// Applies a grade to a display-referred color; mirrors `GradeColor` in styled_composite.effect.
+/// Order: exposure, white balance, contrast, saturation, vignette, clamp to 0..1.
+pub fn grade_rgb(rgb: [f32; 3], grade: &Grade, uv: [f32; 2], aspect: f32) -> [f32; 3] {
+    let [gain, contrast, saturation, vignette] = grade.shader_params();
+    let wb = grade.white_balance();
+
+    let mut c = [0.0; 3];
+    for k in 0..3 {
+        c[k] = ((rgb[k] * gain * wb[k]) - 0.5) * (1.0 + contrast) + 0.5;
+    }
+    let luma: f32 = c.iter().zip(LUMA_WEIGHTS).map(|(v, w)| v * w).sum();
+    let v = vignette_factor(uv, aspect, vignette);
+    c.map(|ch| ((luma + (ch - luma) * (1.0 + saturation)) * v).clamp(0.0, 1.0))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -26,5 +126,93 @@ mod tests {
         assert_eq!(rgba[2], 0x40 as f32 / 255.0);
         assert_eq!(rgba[3], 0x80 as f32 / 255.0);
     }
-}
 
+    const MID: [f32; 2] = [0.5, 0.5];
+
+    fn close(a: [f32; 3], b: [f32; 3]) -> bool {
+        a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-5)
+    }
+
+    #[test]
+    fn neutral_grade_is_identity() {
+        let g = Grade::default();
+        assert!(g.is_neutral());
+        let c = [0.2, 0.5, 0.9];
+        assert!(close(grade_rgb(c, &g, [0.0, 0.0], 16.0 / 9.0), c));
+    }
+
+    #[test]
+    fn exposure_is_in_stops() {
+        let g = Grade {
+            exposure: 1.0,
+            ..Grade::default()
+        };
+        assert!(close(
+            grade_rgb([0.2, 0.1, 0.3], &g, MID, 1.0),
+            [0.4, 0.2, 0.6]
+        ));
+        let g = Grade {
+            exposure: -1.0,
+            ..Grade::default()
+        };
+        assert!(close(
+            grade_rgb([0.2, 0.1, 0.3], &g, MID, 1.0),
+            [0.1, 0.05, 0.15]
+        ));
+    }
+
+    #[test]
+    fn contrast_pivots_on_mid_grey() {
+        let g = Grade {
+            contrast: 0.5,
+            ..Grade::default()
+        };
+        assert!(close(grade_rgb([0.5; 3], &g, MID, 1.0), [0.5; 3]));
+        assert!(close(grade_rgb([0.3; 3], &g, MID, 1.0), [0.2; 3]));
+    }
+
+    #[test]
+    fn saturation_minus_one_is_grey() {
+        let g = Grade {
+            saturation: -1.0,
+            ..Grade::default()
+        };
+        let out = grade_rgb([0.9, 0.2, 0.1], &g, MID, 1.0);
+        assert!((out[0] - out[1]).abs() < 1e-6 && (out[1] - out[2]).abs() < 1e-6);
+    }
+
+    #[test]
+    fn warm_temperature_shifts_red_over_blue() {
+        let g = Grade {
+            temperature: 1.0,
+            ..Grade::default()
+        };
+        let out = grade_rgb([0.5; 3], &g, MID, 1.0);
+        assert!(out[0] > 0.5 && out[2] < 0.5);
+        let g = Grade {
+            tint: 1.0,
+            ..Grade::default()
+        };
+        let out = grade_rgb([0.5; 3], &g, MID, 1.0);
+        assert!(out[1] < out[0] && out[1] < out[2]);
+    }
+
+    #[test]
+    fn vignette_darkens_corners_only() {
+        assert_eq!(vignette_factor(MID, 16.0 / 9.0, 1.0), 1.0);
+        assert!(vignette_factor([0.0, 0.0], 16.0 / 9.0, 1.0).abs() < 1e-6);
+        assert!((vignette_factor([0.0, 0.0], 16.0 / 9.0, 0.5) - 0.5).abs() < 1e-6);
+        assert_eq!(vignette_factor([0.0, 0.0], 16.0 / 9.0, 0.0), 1.0);
+    }
+
+    #[test]
+    fn heatmap_hits_stops_and_clamps() {
+        let close = |a: [f32; 3], b: [f32; 3]| a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-6);
+        assert!(close(heatmap_rgb(0.0), HEATMAP_STOPS[0]));
+        assert!(close(heatmap_rgb(1.0), HEATMAP_STOPS[3]));
+        assert!(close(heatmap_rgb(-1.0), HEATMAP_STOPS[0]));
+        assert!(close(heatmap_rgb(2.0), HEATMAP_STOPS[3]));
+        let mid = heatmap_rgb(0.5);
+        assert!((mid[0] - 0.5).abs() < 1e-6 && (mid[1] - 0.85).abs() < 1e-6);
+    }
+}
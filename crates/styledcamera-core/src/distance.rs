@@ -0,0 +1,260 @@
+// Squared distance standing in for "no person pixel in this line" (finite, so the envelope
+// intersections below never compute inf - inf).
+const FAR: f32 = 1e20;
+
+/// Person pixels of a u8 mask: raw value (optionally inverted) at or above `threshold` (0..1).
+pub fn person_pixels(mask: &[u8], threshold: f32, invert: bool) -> Vec<bool> {
+    mask.iter()
+        .map(|&m| {
+            let v = m as f32 / 255.0;
+            let v = if invert { 1.0 - v } else { v };
+            v >= threshold
+        })
+        .collect()
+}
+
+/// Exact Euclidean distance from every pixel to the nearest person pixel.
+///
+/// `pixel_size` is the size of one mask pixel in output units, so a square mask stretched over
+/// a wide frame still measures round distances. Pixels are `f32::INFINITY` when the mask has
+/// no person at all. Two separable passes of the Felzenszwalb-Huttenlocher lower envelope.
+pub fn distance_to_person(
+    person: &[bool],
+    width: usize,
+    height: usize,
+    pixel_size: [f32; 2],
+) -> Vec<f32> {
+    let n = width * height;
+    if n == 0 || person.len() != n {
+        return Vec::new();
+    }
+    if !person.iter().any(|&p| p) {
+        return vec![f32::INFINITY; n];
+    }
+
+    let mut sq: Vec<f32> = person.iter().map(|&p| if p { 0.0 } else { FAR }).collect();
+    let mut line = Vec::new();
+    let mut out = Vec::new();
+
+    for x in 0..width {
+        line.clear();
+        line.extend((0..height).map(|y| sq[y * width + x]));
+        squared_distance_1d(&line, pixel_size[1], &mut out);
+        for (y, &d) in out.iter().enumerate() {
+            sq[y * width + x] = d;
+        }
+    }
+    for y in 0..height {
+        let row = &mut sq[y * width..][..width];
+        line.clear();
+        line.extend_from_slice(row);
+        squared_distance_1d(&line, pixel_size[0], &mut out);
+        row.copy_from_slice(&out);
+    }
+
+    sq.into_iter().map(f32::sqrt).collect()
+}
+
+// 1D squared distance transform of `f` with samples `spacing` apart.
+fn squared_distance_1d(f: &[f32], spacing: f32, out: &mut Vec<f32>) {
+    let n = f.len();
+    out.clear();
+    out.resize(n, 0.0);
+
+    // Parabolas of the lower envelope (`v`) and the boundaries between them (`z`).
+    let mut v = vec![0usize; n];
+    let mut z = vec![0f32; n + 1];
+    let pos = |i: usize| i as f32 * spacing;
+    let mut k = 0;
+    z[0] = f32::NEG_INFINITY;
+    z[1] = f32::INFINITY;
+
+    for q in 1..n {
+        loop {
+            let p = v[k];
+            let s =
+                ((f[q] + pos(q) * pos(q)) - (f[p] + pos(p) * pos(p))) / (2.0 * (pos(q) - pos(p)));
+            if s <= z[k] && k > 0 {
+                k -= 1;
+                continue;
+            }
+            if s <= z[k] {
+                // Replaces the first parabola entirely.
+                v[0] = q;
+                z[1] = f32::INFINITY;
+            } else {
+                k += 1;
+                v[k] = q;
+                z[k] = s;
+                z[k + 1] = f32::INFINITY;
+            }
+            break;
+        }
+    }
+
+    k = 0;
+    for (q, d) in out.iter_mut().enumerate() {
+        while z[k + 1] < pos(q) {
+            k += 1;
+        }
+        let dx = pos(q) - pos(v[k]);
+        *d = dx * dx + f[v[k]];
+    }
+}
+
+/// Signed distance to the person's silhouette: positive outside, negative inside.
+///
+/// Each side measures to the nearest pixel of the other, so bilinear filtering between two
+/// neighbouring edge pixels crosses zero halfway between them. Pixels are `f32::INFINITY` when
+/// there is no person and `f32::NEG_INFINITY` when the whole mask is person.
+pub fn signed_distance_to_person(
+    person: &[bool],
+    width: usize,
+    height: usize,
+    pixel_size: [f32; 2],
+) -> Vec<f32> {
+    let outside = distance_to_person(person, width, height, pixel_size);
+    let background: Vec<bool> = person.iter().map(|&p| !p).collect();
+    let inside = distance_to_person(&background, width, height, pixel_size);
+    outside
+        .iter()
+        .zip(&inside)
+        .map(|(&o, &i)| if o > 0.0 { o } else { -i })
+        .collect()
+}
+
+/// Signed distances in -`range`..`range` mapped to 0..255 (the silhouette sits at 127.5).
+pub fn encode_signed_distance_u8(distance: &[f32], range: f32) -> Vec<u8> {
+    let range = range.max(1e-6);
+    distance
+        .iter()
+        .map(|&d| (((d / range) * 0.5 + 0.5).clamp(0.0, 1.0) * 255.0).round() as u8)
+        .collect()
+}
+
+/// Distances scaled by `range` into 0..255 (255 at or beyond `range`, and where there is no person).
+pub fn encode_distance_u8(distance: &[f32], range: f32) -> Vec<u8> {
+    let range = range.max(1e-6);
+    distance
+        .iter()
+        .map(|&d| ((d / range).clamp(0.0, 1.0) * 255.0).round() as u8)
+        .collect()
+}
+
+/// Share of the far (strongest) blur at a distance from the person; mirrors the depth blend in
+/// styled_composite.effect. Both arguments use the same units as the encoded distance (0..1).
+pub fn depth_blur_mix(distance: f32, falloff: f32) -> f32 {
+    let t = (distance / falloff.max(1e-3)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Brute-force reference.
+    fn naive(person: &[bool], width: usize, height: usize, pixel_size: [f32; 2]) -> Vec<f32> {
+        (0..width * height)
+            .map(|i| {
+                let (x, y) = (i % width, i / width);
+                person
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, &p)| p)
+                    .map(|(j, _)| {
+                        let dx = (x as f32 - (j % width) as f32) * pixel_size[0];
+                        let dy = (y as f32 - (j / width) as f32) * pixel_size[1];
+                        (dx * dx + dy * dy).sqrt()
+                    })
+                    .fold(f32::INFINITY, f32::min)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let (w, h) = (13, 9);
+        let mut person = vec![false; w * h];
+        for &(x, y) in &[(2, 2), (3, 2), (10, 7), (6, 0), (0, 8)] {
+            person[y * w + x] = true;
+        }
+        for pixel_size in [[1.0, 1.0], [1.7, 0.6]] {
+            let fast = distance_to_person(&person, w, h, pixel_size);
+            let slow = naive(&person, w, h, pixel_size);
+            for (a, b) in fast.iter().zip(&slow) {
+                assert!(
+                    (a - b).abs() < 1e-3,
+                    "{a} vs {b} at pixel size {pixel_size:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn person_pixels_are_at_distance_zero() {
+        let person = vec![false, true, true, false, false];
+        let d = distance_to_person(&person, 5, 1, [1.0, 1.0]);
+        assert_eq!(d, vec![1.0, 0.0, 0.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn empty_mask_is_infinitely_far() {
+        let d = distance_to_person(&[false; 6], 3, 2, [1.0, 1.0]);
+        assert!(d.iter().all(|v| v.is_infinite()));
+        assert_eq!(encode_distance_u8(&d, 1.0), vec![255; 6]);
+    }
+
+    #[test]
+    fn threshold_and_invert_pick_person_pixels() {
+        let mask = [0u8, 100, 200, 255];
+        assert_eq!(
+            person_pixels(&mask, 0.5, false),
+            vec![false, false, true, true]
+        );
+        assert_eq!(
+            person_pixels(&mask, 0.5, true),
+            vec![true, true, false, false]
+        );
+    }
+
+    #[test]
+    fn encoding_saturates_at_range() {
+        assert_eq!(
+            encode_distance_u8(&[0.0, 0.25, 0.5, 2.0], 0.5),
+            vec![0, 128, 255, 255]
+        );
+    }
+
+    #[test]
+    fn depth_mix_rises_smoothly_to_falloff() {
+        assert_eq!(depth_blur_mix(0.0, 0.3), 0.0);
+        assert_eq!(depth_blur_mix(0.3, 0.3), 1.0);
+        assert_eq!(depth_blur_mix(0.9, 0.3), 1.0);
+        assert!((depth_blur_mix(0.15, 0.3) - 0.5).abs() < 1e-6);
+        assert!(depth_blur_mix(0.1, 0.3) < depth_blur_mix(0.2, 0.3));
+    }
+
+    #[test]
+    fn signed_distance_is_negative_inside() {
+        let person = vec![false, false, true, true, true, false];
+        let d = signed_distance_to_person(&person, 6, 1, [1.0, 1.0]);
+        assert_eq!(d, vec![2.0, 1.0, -1.0, -2.0, -1.0, 1.0]);
+    }
+
+    #[test]
+    fn signed_distance_of_uniform_masks() {
+        let d = signed_distance_to_person(&[false; 4], 2, 2, [1.0, 1.0]);
+        assert!(d.iter().all(|v| *v == f32::INFINITY));
+        let d = signed_distance_to_person(&[true; 4], 2, 2, [1.0, 1.0]);
+        assert!(d.iter().all(|v| *v == f32::NEG_INFINITY));
+        assert_eq!(encode_signed_distance_u8(&d, 1.0), vec![0; 4]);
+    }
+
+    #[test]
+    fn signed_encoding_centers_the_silhouette() {
+        assert_eq!(
+            encode_signed_distance_u8(&[-1.0, -0.5, 0.0, 0.5, 3.0], 1.0),
+            vec![0, 64, 128, 191, 255]
+        );
+    }
+}
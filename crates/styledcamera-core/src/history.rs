@@ -0,0 +1,274 @@
+use std::time::{Duration, Instant};
+
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FrameStamp {
+    pub id: u64,
+    pub time: Instant,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrameTarget {
+    /// The frame with this id (or the closest surviving id if its slot was recycled),
+    /// optionally pushed further back in time by `extra_delay`.
+    Id { id: u64, extra_delay: Duration },
+    /// The frame captured closest to `now - delay`.
+    Delay(Duration),
+}
+
+/// Picks the history slot to composite. `None` entries are empty or unusable slots.
+pub fn select_history_slot(
+    slots: &[Option<FrameStamp>],
+    target: FrameTarget,
+    clock: &impl Clock,
+) -> Option<usize> {
+    match target {
+        FrameTarget::Id { id, extra_delay } => {
+            let base = closest_by(slots, |s| u128::from(s.id.abs_diff(id)))?;
+            if extra_delay.is_zero() {
+                return Some(base);
+            }
+            let base_time = slots[base]?.time;
+            let desired = base_time.checked_sub(extra_delay).unwrap_or(base_time);
+            closest_by(slots, |s| abs_diff_nanos(s.time, desired))
+        }
+        FrameTarget::Delay(delay) => {
+            let now = clock.now();
+            let desired = now.checked_sub(delay).unwrap_or(now);
+            closest_by(slots, |s| abs_diff_nanos(s.time, desired))
+        }
+    }
+}
+
+/// How far the chosen slot lags the newest frame in the ring: the video delay actually applied.
+pub fn slot_delay(slots: &[Option<FrameStamp>], chosen: usize) -> Duration {
+    let Some(Some(chosen)) = slots.get(chosen) else {
+        return Duration::ZERO;
+    };
+    let newest = slots.iter().flatten().map(|s| s.time).max().unwrap_or(chosen.time);
+    newest.saturating_duration_since(chosen.time)
+}
+
+fn closest_by(slots: &[Option<FrameStamp>], dist: impl Fn(&FrameStamp) -> u128) -> Option<usize> {
+    let mut best: Option<(usize, u128, u64)> = None;
+    for (i, slot) in slots.iter().enumerate() {
+        let Some(stamp) = slot else {
+            continue;
+        };
+        let d = dist(stamp);
+        // Ties go to the older frame so video never runs ahead of the mask.
+        let better = match best {
+            None => true,
+            Some((_, best_d, best_id)) => d < best_d || (d == best_d && stamp.id < best_id),
+        };
+        if better {
+            best = Some((i, d, stamp.id));
+        }
+    }
+    best.map(|(i, _, _)| i)
+}
+
+fn abs_diff_nanos(a: Instant, b: Instant) -> u128 {
+    if a <= b {
+        b.duration_since(a).as_nanos()
+    } else {
+        a.duration_since(b).as_nanos()
+    }
+}
+
+// Never fewer than the current frame plus one delayed candidate.
+const MIN_HISTORY_DEPTH: usize = 2;
+// Shrinking reallocates nothing but drops frames; only do it once the requirement fell clearly.
+const SHRINK_HYSTERESIS: usize = 3;
+
+/// Number of history frames needed to look `delay_ms` into the past at the given frame interval.
+pub fn required_history_depth(delay_ms: f32, frame_interval_ms: f32, max_depth: usize) -> usize {
+    let max_depth = max_depth.max(MIN_HISTORY_DEPTH);
+    if !frame_interval_ms.is_finite() || frame_interval_ms <= 0.0 || !delay_ms.is_finite() {
+        return max_depth;
+    }
+
+    // +2: the current frame plus one frame of jitter headroom.
+    let frames = (delay_ms.max(0.0) / frame_interval_ms).ceil() as usize + 2;
+    frames.clamp(MIN_HISTORY_DEPTH, max_depth)
+}
+
+/// Grows to `required` immediately; shrinks only once it dropped by more than the hysteresis.
+pub fn next_history_depth(current: usize, required: usize) -> usize {
+    if current == 0 || required >= current || required + SHRINK_HYSTERESIS <= current {
+        required
+    } else {
+        current
+    }
+}
+
+pub fn history_vram_bytes(width: u32, height: u32, depth: usize) -> u64 {
+    // History slots are RGBA8 texrenders at source resolution.
+    u64::from(width) * u64::from(height) * 4 * depth as u64
+}
+
+/// VRAM the frame history may use on its own, on top of the user's frame cap. One RGBA8 frame is
+/// ~8 MiB at 1080p but ~32 MiB at 4K, so a fixed frame count alone would cost ~1 GiB there.
+pub const HISTORY_VRAM_BUDGET_BYTES: u64 = 256 * 1024 * 1024;
+
+/// Deepest history that fits `budget_bytes` at the given source size (never below the minimum).
+pub fn history_depth_budget(width: u32, height: u32, budget_bytes: u64) -> usize {
+    let frame_bytes = history_vram_bytes(width, height, 1);
+    if frame_bytes == 0 {
+        return usize::MAX;
+    }
+    usize::try_from(budget_bytes / frame_bytes)
+        .unwrap_or(usize::MAX)
+        .max(MIN_HISTORY_DEPTH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FixedClock(Instant);
+
+    impl Clock for FixedClock {
+        fn now(&self) -> Instant {
+            self.0
+        }
+    }
+
+    // Frames at 10 ms spacing, ids 0..n, the last one captured at `end`.
+    fn frames(end: Instant, n: u64) -> Vec<Option<FrameStamp>> {
+        (0..n)
+            .map(|id| {
+                Some(FrameStamp {
+                    id,
+                    time: end - Duration::from_millis((n - 1 - id) * 10),
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn slot_delay_is_measured_from_the_newest_frame() {
+        let end = Instant::now() + Duration::from_secs(1);
+        let mut slots = frames(end, 8);
+        assert_eq!(slot_delay(&slots, 7), Duration::ZERO);
+        assert_eq!(slot_delay(&slots, 2), Duration::from_millis(50));
+        // Ring order does not matter, and an empty slot applies no delay.
+        slots.rotate_left(3);
+        assert_eq!(slot_delay(&slots, 0), Duration::from_millis(40));
+        slots[1] = None;
+        assert_eq!(slot_delay(&slots, 1), Duration::ZERO);
+    }
+
+    #[test]
+    fn selects_exact_frame_id() {
+        let end = Instant::now() + Duration::from_secs(1);
+        let slots = frames(end, 8);
+        let target = FrameTarget::Id { id: 5, extra_delay: Duration::ZERO };
+        assert_eq!(select_history_slot(&slots, target, &FixedClock(end)), Some(5));
+    }
+
+    #[test]
+    fn falls_back_to_nearest_id_when_recycled() {
+        let end = Instant::now() + Duration::from_secs(1);
+        let mut slots = frames(end, 8);
+        slots[2] = None;
+        let target = FrameTarget::Id { id: 2, extra_delay: Duration::ZERO };
+        // Ids 1 and 3 are equally close; prefer the older one.
+        assert_eq!(select_history_slot(&slots, target, &FixedClock(end)), Some(1));
+
+        let target = FrameTarget::Id { id: 100, extra_delay: Duration::ZERO };
+        assert_eq!(select_history_slot(&slots, target, &FixedClock(end)), Some(7));
+    }
+
+    #[test]
+    fn extra_delay_moves_back_from_paired_frame() {
+        let end = Instant::now() + Duration::from_secs(1);
+        let slots = frames(end, 8);
+        let target = FrameTarget::Id { id: 6, extra_delay: Duration::from_millis(30) };
+        assert_eq!(select_history_slot(&slots, target, &FixedClock(end)), Some(3));
+    }
+
+    #[test]
+    fn delay_target_uses_injected_clock() {
+        let end = Instant::now() + Duration::from_secs(1);
+        let slots = frames(end, 8);
+        let clock = FixedClock(end + Duration::from_millis(5));
+        let target = FrameTarget::Delay(Duration::from_millis(26));
+        // now - 26 ms = end - 21 ms: closest frame is end - 20 ms (id 5).
+        assert_eq!(select_history_slot(&slots, target, &clock), Some(5));
+    }
+
+    #[test]
+    fn delay_beyond_history_returns_oldest() {
+        let end = Instant::now() + Duration::from_secs(1);
+        let slots = frames(end, 4);
+        let target = FrameTarget::Delay(Duration::from_millis(500));
+        assert_eq!(select_history_slot(&slots, target, &FixedClock(end)), Some(0));
+    }
+
+    #[test]
+    fn empty_history_selects_nothing() {
+        let clock = FixedClock(Instant::now());
+        let slots = [None, None];
+        assert_eq!(select_history_slot(&slots, FrameTarget::Delay(Duration::from_millis(10)), &clock), None);
+        let target = FrameTarget::Id { id: 1, extra_delay: Duration::ZERO };
+        assert_eq!(select_history_slot(&slots, target, &clock), None);
+    }
+
+    #[test]
+    fn depth_covers_delay_at_frame_rate() {
+        // 60 FPS, 133 ms: 8 frames back plus current and headroom.
+        assert_eq!(required_history_depth(133.0, 1000.0 / 60.0, 64), 10);
+        // 30 FPS, 480 ms.
+        assert_eq!(required_history_depth(480.0, 1000.0 / 30.0, 64), 17);
+    }
+
+    #[test]
+    fn depth_respects_user_cap_and_minimum() {
+        assert_eq!(required_history_depth(500.0, 1000.0 / 60.0, 16), 16);
+        assert_eq!(required_history_depth(0.0, 1000.0 / 60.0, 16), 2);
+        assert_eq!(required_history_depth(100.0, 10.0, 0), 2);
+    }
+
+    #[test]
+    fn depth_without_frame_interval_uses_cap() {
+        assert_eq!(required_history_depth(100.0, 0.0, 12), 12);
+        assert_eq!(required_history_depth(f32::NAN, 16.0, 12), 12);
+    }
+
+    #[test]
+    fn depth_grows_immediately_and_shrinks_with_hysteresis() {
+        assert_eq!(next_history_depth(0, 6), 6);
+        assert_eq!(next_history_depth(6, 10), 10);
+        assert_eq!(next_history_depth(10, 8), 10);
+        assert_eq!(next_history_depth(10, 7), 7);
+    }
+
+    #[test]
+    fn vram_estimate_is_rgba8() {
+        assert_eq!(history_vram_bytes(1920, 1080, 8), 1920 * 1080 * 4 * 8);
+        assert_eq!(history_vram_bytes(3840, 2160, 0), 0);
+    }
+
+    #[test]
+    fn vram_budget_limits_depth_at_high_resolution() {
+        assert_eq!(history_depth_budget(1920, 1080, HISTORY_VRAM_BUDGET_BYTES), 32);
+        assert_eq!(history_depth_budget(3840, 2160, HISTORY_VRAM_BUDGET_BYTES), 8);
+        let depth = history_depth_budget(3840, 2160, HISTORY_VRAM_BUDGET_BYTES);
+        assert!(history_vram_bytes(3840, 2160, depth) <= HISTORY_VRAM_BUDGET_BYTES);
+        // Tiny budgets still keep the current frame and one delayed candidate.
+        assert_eq!(history_depth_budget(3840, 2160, 1), 2);
+        assert_eq!(history_depth_budget(0, 0, 1), usize::MAX);
+    }
+}
@@ -1,4 +1,13 @@
+pub mod background;
+pub mod blur;
 pub mod color;
+pub mod distance;
+pub mod half;
+pub mod history;
+pub mod lut;
+pub mod matte;
+pub mod rate;
 pub mod segmentation;
+pub mod shape;
 pub mod timing;
 
@@ -0,0 +1,331 @@
+use std::fmt;
+
+use crate::half::f32_to_f16_bits;
+
+/// Largest `LUT_3D_SIZE` accepted (65 is the biggest size grading tools export).
+pub const MAX_LUT_SIZE: usize = 65;
+
+/// A parsed 3D `.cube` LUT. `data` is in file order: red changes fastest, then green, then blue.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CubeLut {
+    pub title: Option<String>,
+    pub size: usize,
+    pub domain_min: [f32; 3],
+    pub domain_max: [f32; 3],
+    pub data: Vec<[f32; 3]>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum CubeError {
+    /// No `LUT_3D_SIZE` line.
+    MissingSize,
+    /// `LUT_3D_SIZE` outside 2..=MAX_LUT_SIZE.
+    InvalidSize(usize),
+    /// `LUT_1D_SIZE`: only 3D LUTs are supported.
+    Unsupported1d,
+    /// Keyword or data line that could not be parsed (1-based line number).
+    Malformed { line: usize },
+    /// A domain max not above its min.
+    InvalidDomain,
+    /// Number of data rows differs from size^3.
+    WrongEntryCount { expected: usize, found: usize },
+}
+
+impl fmt::Display for CubeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingSize => write!(f, "missing LUT_3D_SIZE"),
+            Self::InvalidSize(n) => write!(f, "LUT_3D_SIZE {n} is not in 2..={MAX_LUT_SIZE}"),
+            Self::Unsupported1d => write!(f, "1D LUTs are not supported"),
+            Self::Malformed { line } => write!(f, "malformed line {line}"),
+            Self::InvalidDomain => write!(f, "DOMAIN_MAX must be above DOMAIN_MIN"),
+            Self::WrongEntryCount { expected, found } => {
+                write!(f, "expected {expected} entries, found {found}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CubeError {}
+
+fn parse_floats<const N: usize>(fields: &[&str], line: usize) -> Result<[f32; N], CubeError> {
+    if fields.len() != N {
+        return Err(CubeError::Malformed { line });
+    }
+    let mut out = [0.0; N];
+    for (v, s) in out.iter_mut().zip(fields) {
+        *v = s
+            .parse::<f32>()
+            .ok()
+            .filter(|v| v.is_finite())
+            .ok_or(CubeError::Malformed { line })?;
+    }
+    Ok(out)
+}
+
+/// Parses the Adobe/Resolve `.cube` text format (3D only).
+///
+/// Supports `TITLE`, `LUT_3D_SIZE`, `DOMAIN_MIN`/`DOMAIN_MAX` and Resolve's
+/// `LUT_3D_INPUT_RANGE`; other keywords are ignored. Comments start with `#`.
+pub fn parse_cube(text: &str) -> Result<CubeLut, CubeError> {
+    let mut title = None;
+    let mut size = None;
+    let mut domain_min = [0.0; 3];
+    let mut domain_max = [1.0; 3];
+    let mut data = Vec::new();
+
+    for (i, raw) in text.lines().enumerate() {
+        let line = i + 1;
+        let content = raw.split('#').next().unwrap_or("").trim();
+        if content.is_empty() {
+            continue;
+        }
+
+        let fields: Vec<&str> = content.split_whitespace().collect();
+        let starts_numeric = fields[0]
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.'));
+        if starts_numeric {
+            data.push(parse_floats::<3>(&fields, line)?);
+            continue;
+        }
+        if !data.is_empty() {
+            // Keywords only belong in the header.
+            return Err(CubeError::Malformed { line });
+        }
+
+        match fields[0] {
+            "TITLE" => {
+                let rest = content["TITLE".len()..].trim();
+                title = Some(rest.trim_matches('"').to_string());
+            }
+            "LUT_3D_SIZE" => {
+                let [n] = parse_floats::<1>(&fields[1..], line)?;
+                if n.fract() != 0.0 || n < 0.0 {
+                    return Err(CubeError::Malformed { line });
+                }
+                let n = n as usize;
+                if !(2..=MAX_LUT_SIZE).contains(&n) {
+                    return Err(CubeError::InvalidSize(n));
+                }
+                size = Some(n);
+            }
+            "LUT_1D_SIZE" => return Err(CubeError::Unsupported1d),
+            "DOMAIN_MIN" => domain_min = parse_floats::<3>(&fields[1..], line)?,
+            "DOMAIN_MAX" => domain_max = parse_floats::<3>(&fields[1..], line)?,
+            "LUT_3D_INPUT_RANGE" => {
+                let [lo, hi] = parse_floats::<2>(&fields[1..], line)?;
+                domain_min = [lo; 3];
+                domain_max = [hi; 3];
+            }
+            _ => {}
+        }
+    }
+
+    let size = size.ok_or(CubeError::MissingSize)?;
+    if (0..3).any(|k| domain_max[k] <= domain_min[k]) {
+        return Err(CubeError::InvalidDomain);
+    }
+    let expected = size * size * size;
+    if data.len() != expected {
+        return Err(CubeError::WrongEntryCount {
+            expected,
+            found: data.len(),
+        });
+    }
+
+    Ok(CubeLut {
+        title,
+        size,
+        domain_min,
+        domain_max,
+        data,
+    })
+}
+
+impl CubeLut {
+    pub fn entry(&self, r: usize, g: usize, b: usize) -> [f32; 3] {
+        self.data[(b * self.size + g) * self.size + r]
+    }
+
+    /// Input color mapped through the domain to lattice coordinates (0..size-1).
+    fn lattice(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let n = (self.size - 1) as f32;
+        let mut t = [0.0; 3];
+        for k in 0..3 {
+            let span = self.domain_max[k] - self.domain_min[k];
+            t[k] = ((rgb[k] - self.domain_min[k]) / span).clamp(0.0, 1.0) * n;
+        }
+        t
+    }
+
+    /// Trilinear lookup; mirrors `ApplyLut` in styled_composite.effect.
+    pub fn sample(&self, rgb: [f32; 3]) -> [f32; 3] {
+        let t = self.lattice(rgb);
+        let last = self.size - 1;
+        let i0 = t.map(|v| (v.floor() as usize).min(last));
+        let i1 = i0.map(|i| (i + 1).min(last));
+        let f = [
+            t[0] - i0[0] as f32,
+            t[1] - i0[1] as f32,
+            t[2] - i0[2] as f32,
+        ];
+
+        let lerp = |a: [f32; 3], b: [f32; 3], w: f32| {
+            [
+                a[0] + (b[0] - a[0]) * w,
+                a[1] + (b[1] - a[1]) * w,
+                a[2] + (b[2] - a[2]) * w,
+            ]
+        };
+        let slice = |b: usize| {
+            let g0 = lerp(
+                self.entry(i0[0], i0[1], b),
+                self.entry(i1[0], i0[1], b),
+                f[0],
+            );
+            let g1 = lerp(
+                self.entry(i0[0], i1[1], b),
+                self.entry(i1[0], i1[1], b),
+                f[0],
+            );
+            lerp(g0, g1, f[1])
+        };
+        lerp(slice(i0[2]), slice(i1[2]), f[2])
+    }
+
+    /// Blue slices side by side: a (size * size) x size image, texel (b * size + r, g).
+    /// RGBA half floats (alpha 1), ready for a GS_RGBA16F texture.
+    pub fn strip_rgba_f16(&self) -> Vec<u16> {
+        let n = self.size;
+        let one = f32_to_f16_bits(1.0);
+        let mut out = Vec::with_capacity(n * n * n * 4);
+        for g in 0..n {
+            for b in 0..n {
+                for r in 0..n {
+                    let c = self.entry(r, g, b);
+                    out.extend(c.map(f32_to_f16_bits));
+                    out.push(one);
+                }
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identity_cube(n: usize) -> String {
+        let mut s = format!("TITLE \"Identity\"\nLUT_3D_SIZE {n}\n");
+        let d = (n - 1) as f32;
+        for b in 0..n {
+            for g in 0..n {
+                for r in 0..n {
+                    s += &format!("{} {} {}\n", r as f32 / d, g as f32 / d, b as f32 / d);
+                }
+            }
+        }
+        s
+    }
+
+    fn close(a: [f32; 3], b: [f32; 3]) -> bool {
+        a.iter().zip(b).all(|(x, y)| (x - y).abs() < 1e-4)
+    }
+
+    #[test]
+    fn parses_common_sizes() {
+        for n in [17, 33, 65] {
+            let lut = parse_cube(&identity_cube(n)).unwrap();
+            assert_eq!(lut.size, n);
+            assert_eq!(lut.data.len(), n * n * n);
+            assert_eq!(lut.title.as_deref(), Some("Identity"));
+        }
+    }
+
+    #[test]
+    fn identity_lut_samples_identity() {
+        let lut = parse_cube(&identity_cube(17)).unwrap();
+        for c in [
+            [0.0, 0.0, 0.0],
+            [1.0, 1.0, 1.0],
+            [0.3, 0.61, 0.97],
+            [0.5, 0.02, 0.8],
+        ] {
+            assert!(close(lut.sample(c), c), "{c:?}");
+        }
+    }
+
+    #[test]
+    fn data_order_is_red_fastest() {
+        let text = "LUT_3D_SIZE 2\n\
+                    0 0 0\n1 0 0\n0 1 0\n1 1 0\n0 0 1\n1 0 1\n0 1 1\n1 1 1\n";
+        let lut = parse_cube(text).unwrap();
+        assert_eq!(lut.entry(1, 0, 0), [1.0, 0.0, 0.0]);
+        assert_eq!(lut.entry(0, 1, 0), [0.0, 1.0, 0.0]);
+        assert_eq!(lut.entry(0, 0, 1), [0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn domain_maps_input_range() {
+        let text = format!("DOMAIN_MIN 0 0 0\nDOMAIN_MAX 2 2 2\n{}", identity_cube(5));
+        let lut = parse_cube(&text).unwrap();
+        assert_eq!(lut.domain_max, [2.0; 3]);
+        assert!(close(lut.sample([1.0, 0.5, 2.0]), [0.5, 0.25, 1.0]));
+        // Outside the domain clamps to the edge.
+        assert!(close(lut.sample([3.0, -1.0, 1.0]), [1.0, 0.0, 0.5]));
+
+        let text = format!("LUT_3D_INPUT_RANGE 0 4\n{}", identity_cube(3));
+        assert_eq!(parse_cube(&text).unwrap().domain_max, [4.0; 3]);
+    }
+
+    #[test]
+    fn comments_and_blank_lines_are_skipped() {
+        let text = format!("# made by hand\n\n{}\n# end\n", identity_cube(2));
+        assert!(parse_cube(&text).is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_files() {
+        assert_eq!(parse_cube("0 0 0\n"), Err(CubeError::MissingSize));
+        assert_eq!(
+            parse_cube("LUT_3D_SIZE 1\n0 0 0\n"),
+            Err(CubeError::InvalidSize(1))
+        );
+        assert_eq!(
+            parse_cube("LUT_3D_SIZE 66\n"),
+            Err(CubeError::InvalidSize(66))
+        );
+        assert_eq!(
+            parse_cube("LUT_1D_SIZE 1024\n"),
+            Err(CubeError::Unsupported1d)
+        );
+        assert_eq!(
+            parse_cube("LUT_3D_SIZE 2\n0 0 0\n1 0\n"),
+            Err(CubeError::Malformed { line: 3 })
+        );
+        assert_eq!(
+            parse_cube("LUT_3D_SIZE 2\n0 0 0\n"),
+            Err(CubeError::WrongEntryCount {
+                expected: 8,
+                found: 1
+            })
+        );
+        let text = format!("DOMAIN_MIN 0 1 0\nDOMAIN_MAX 1 1 1\n{}", identity_cube(2));
+        assert_eq!(parse_cube(&text), Err(CubeError::InvalidDomain));
+        let text = format!("{}LUT_3D_SIZE 2\n", identity_cube(2));
+        assert_eq!(parse_cube(&text), Err(CubeError::Malformed { line: 11 }));
+    }
+
+    #[test]
+    fn strip_layout_places_blue_slices_side_by_side() {
+        let lut = parse_cube(&identity_cube(3)).unwrap();
+        let strip = lut.strip_rgba_f16();
+        assert_eq!(strip.len(), 9 * 3 * 4);
+        // Texel (b * 3 + r, g) = (2 * 3 + 1, 2): r = 0.5, g = 1, b = 1.
+        let i = (2 * 9 + 2 * 3 + 1) * 4;
+        assert_eq!(&strip[i..i + 4], &[0x3800, 0x3C00, 0x3C00, 0x3C00]);
+    }
+}
@@ -0,0 +1,351 @@
+/// Directions sampled around a pixel by the edge passes (evenly spaced angles).
+pub const EDGE_TAP_DIRECTIONS: usize = 8;
+
+// Radii of the tap rings, as a fraction of the pass radius.
+const EDGE_TAP_RINGS: [f32; 2] = [0.5, 1.0];
+
+/// Tap offsets (in pixels) of the edge passes for a radius; mirrors the loops in
+/// styled_composite.effect.
+pub fn edge_taps(radius: f32) -> impl Iterator<Item = [f32; 2]> {
+    EDGE_TAP_RINGS.into_iter().flat_map(move |ring| {
+        (0..EDGE_TAP_DIRECTIONS).map(move |i| {
+            let a = i as f32 * std::f32::consts::TAU / EDGE_TAP_DIRECTIONS as f32;
+            [a.cos() * radius * ring, a.sin() * radius * ring]
+        })
+    })
+}
+
+/// How strongly a pixel with person coverage `m` sits in the transition band (1 at m = 0.5).
+pub fn edge_band(m: f32) -> f32 {
+    let m = m.clamp(0.0, 1.0);
+    4.0 * m * (1.0 - m)
+}
+
+/// CPU reference for edge color decontamination; mirrors `Decontaminate` in
+/// styled_composite.effect.
+///
+/// In the transition band the frame mixes person and background colors. The foreground color
+/// is estimated from nearby pixels weighted towards the person's interior (coverage^4), and the
+/// observed color is pulled towards it by `strength * edge_band`. `coverage` is the refined
+/// mask; taps outside the frame are clamped to the edge.
+pub fn decontaminate_edges(
+    rgb: &[[f32; 3]],
+    coverage: &[f32],
+    width: usize,
+    height: usize,
+    radius: f32,
+    strength: f32,
+) -> Vec<[f32; 3]> {
+    let n = width * height;
+    if n == 0 || rgb.len() != n || coverage.len() != n || strength <= 0.0 {
+        return rgb.to_vec();
+    }
+    let taps: Vec<[f32; 2]> = edge_taps(radius).collect();
+    let at = |x: f32, y: f32| {
+        let xi = (x.round() as isize).clamp(0, width as isize - 1) as usize;
+        let yi = (y.round() as isize).clamp(0, height as isize - 1) as usize;
+        yi * width + xi
+    };
+
+    (0..n)
+        .map(|i| {
+            let c = rgb[i];
+            let band = edge_band(coverage[i]) * strength.min(1.0);
+            if band <= 0.0 {
+                return c;
+            }
+
+            let (x, y) = ((i % width) as f32, (i / width) as f32);
+            let mut sum = [0.0; 3];
+            let mut wsum = 0.0;
+            for t in &taps {
+                let j = at(x + t[0], y + t[1]);
+                let w = coverage[j].clamp(0.0, 1.0).powi(4);
+                for k in 0..3 {
+                    sum[k] += rgb[j][k] * w;
+                }
+                wsum += w;
+            }
+            if wsum <= 1e-4 {
+                return c;
+            }
+
+            let mut out = c;
+            for k in 0..3 {
+                let fg = sum[k] / wsum;
+                out[k] = c[k] + (fg - c[k]) * band;
+            }
+            out
+        })
+        .collect()
+}
+
+/// CPU reference for the light wrap; mirrors `LightWrap` in styled_composite.effect.
+///
+/// Background light within `radius` of the silhouette bleeds over the person's edge: the
+/// background is averaged over the taps weighted by how much background each shows, and screened
+/// onto the foreground by `intensity * coverage * background share`. Deep inside the person (no
+/// background nearby) and outside it the foreground is unchanged.
+///
+/// `bg` is `None` where the mask-weighted blur has no background to resolve; those taps are
+/// left out of both the light and the background share instead of counting as black.
+pub fn light_wrap(
+    fg: &[[f32; 3]],
+    bg: &[Option<[f32; 3]>],
+    coverage: &[f32],
+    width: usize,
+    height: usize,
+    radius: f32,
+    intensity: f32,
+) -> Vec<[f32; 3]> {
+    let n = width * height;
+    if n == 0 || fg.len() != n || bg.len() != n || coverage.len() != n || intensity <= 0.0 {
+        return fg.to_vec();
+    }
+    let taps: Vec<[f32; 2]> = edge_taps(radius).collect();
+    let at = |x: f32, y: f32| {
+        let xi = (x.round() as isize).clamp(0, width as isize - 1) as usize;
+        let yi = (y.round() as isize).clamp(0, height as isize - 1) as usize;
+        yi * width + xi
+    };
+
+    (0..n)
+        .map(|i| {
+            let c = fg[i];
+            let m = coverage[i].clamp(0.0, 1.0);
+            if m <= 0.0 {
+                return c;
+            }
+
+            let (x, y) = ((i % width) as f32, (i / width) as f32);
+            let mut sum = [0.0; 3];
+            let mut wsum = 0.0;
+            let mut used = 0;
+            for t in &taps {
+                let j = at(x + t[0], y + t[1]);
+                let Some(b) = bg[j] else {
+                    continue;
+                };
+                let w = 1.0 - coverage[j].clamp(0.0, 1.0);
+                for k in 0..3 {
+                    sum[k] += b[k] * w;
+                }
+                wsum += w;
+                used += 1;
+            }
+            if wsum <= 1e-4 {
+                return c;
+            }
+
+            let amount = intensity.min(1.0) * m * (wsum / used as f32);
+            let mut out = c;
+            for k in 0..3 {
+                let light = (sum[k] / wsum).clamp(0.0, 1.0);
+                out[k] = c[k] + light * (1.0 - c[k]) * amount;
+            }
+            out
+        })
+        .collect()
+}
+
+fn smoothstep(e0: f32, e1: f32, x: f32) -> f32 {
+    let t = ((x - e0) / (e1 - e0).max(1e-6)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+/// Person coverage from a raw mask value; mirrors `RefineMask` in styled_composite.effect.
+pub fn refine_mask(raw: f32, threshold: f32, softness: f32, invert: bool) -> f32 {
+    let m = if invert { 1.0 - raw } else { raw };
+    let s = softness.max(0.0);
+    smoothstep(threshold - s, threshold + s, m)
+}
+
+/// Coverage of a silhouette outline `width` wide at signed distance `d` from the person
+/// (positive outside); mirrors `OutlineAlpha` in styled_composite.effect. The inside is covered
+/// too so the outline tucks under the person's soft edge.
+pub fn outline_alpha(d: f32, width: f32, softness: f32) -> f32 {
+    if width <= 0.0 {
+        return 0.0;
+    }
+    let s = softness.max(1e-4);
+    1.0 - smoothstep(width - s, width + s, d)
+}
+
+/// Coverage of the outer glow `size` wide beyond an outline of `width`; mirrors `GlowAlpha` in
+/// styled_composite.effect. Falls off quadratically and reaches zero at `width + size`.
+pub fn glow_alpha(d: f32, width: f32, size: f32) -> f32 {
+    if size <= 0.0 {
+        return 0.0;
+    }
+    let t = 1.0 - ((d - width.max(0.0)) / size).clamp(0.0, 1.0);
+    t * t
+}
+
+/// Coverage of the silhouette's drop shadow at signed distance `d` from the shifted silhouette;
+/// mirrors `SilhouetteShadowAlpha` in styled_composite.effect.
+pub fn silhouette_shadow_alpha(d: f32, blur: f32) -> f32 {
+    let b = blur.max(1e-4);
+    1.0 - smoothstep(-b, b, d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SKIN: [f32; 3] = [0.8, 0.6, 0.5];
+    const GREEN: [f32; 3] = [0.0, 1.0, 0.0];
+
+    // Person on the left, green background on the right, with a contaminated edge column.
+    fn edge_frame() -> (Vec<[f32; 3]>, Vec<f32>, usize, usize) {
+        let (w, h) = (24, 6);
+        let mut rgb = Vec::new();
+        let mut cov = Vec::new();
+        for _ in 0..h {
+            for x in 0..w {
+                let (c, m) = match x {
+                    0..=11 => (SKIN, 1.0),
+                    12 => ([0.4, 0.8, 0.25], 0.5),
+                    _ => (GREEN, 0.0),
+                };
+                rgb.push(c);
+                cov.push(m);
+            }
+        }
+        (rgb, cov, w, h)
+    }
+
+    #[test]
+    fn band_peaks_in_the_transition() {
+        assert_eq!(edge_band(0.0), 0.0);
+        assert_eq!(edge_band(1.0), 0.0);
+        assert_eq!(edge_band(0.5), 1.0);
+    }
+
+    #[test]
+    fn taps_form_two_rings() {
+        let taps: Vec<_> = edge_taps(4.0).collect();
+        assert_eq!(taps.len(), 2 * EDGE_TAP_DIRECTIONS);
+        let r = |t: &[f32; 2]| (t[0] * t[0] + t[1] * t[1]).sqrt();
+        assert!(taps[..EDGE_TAP_DIRECTIONS]
+            .iter()
+            .all(|t| (r(t) - 2.0).abs() < 1e-5));
+        assert!(taps[EDGE_TAP_DIRECTIONS..]
+            .iter()
+            .all(|t| (r(t) - 4.0).abs() < 1e-5));
+    }
+
+    #[test]
+    fn edge_pixels_move_towards_the_interior_color() {
+        let (rgb, cov, w, h) = edge_frame();
+        let out = decontaminate_edges(&rgb, &cov, w, h, 4.0, 1.0);
+        let i = 2 * w + 12;
+        let green_before = rgb[i][1] - SKIN[1];
+        let green_after = out[i][1] - SKIN[1];
+        assert!(green_after.abs() < green_before.abs() * 0.5, "{:?}", out[i]);
+    }
+
+    #[test]
+    fn interior_and_background_are_untouched() {
+        let (rgb, cov, w, h) = edge_frame();
+        let out = decontaminate_edges(&rgb, &cov, w, h, 4.0, 1.0);
+        assert_eq!(out[2 * w + 3], SKIN);
+        assert_eq!(out[2 * w + 20], GREEN);
+    }
+
+    #[test]
+    fn zero_strength_is_identity() {
+        let (rgb, cov, w, h) = edge_frame();
+        assert_eq!(decontaminate_edges(&rgb, &cov, w, h, 4.0, 0.0), rgb);
+    }
+
+    #[test]
+    fn strength_scales_the_correction() {
+        let (rgb, cov, w, h) = edge_frame();
+        let i = 2 * w + 12;
+        let half = decontaminate_edges(&rgb, &cov, w, h, 4.0, 0.5)[i];
+        let full = decontaminate_edges(&rgb, &cov, w, h, 4.0, 1.0)[i];
+        assert!((half[1] - rgb[i][1]).abs() < (full[1] - rgb[i][1]).abs());
+    }
+
+    #[test]
+    fn light_wrap_brightens_the_edge_with_background_light() {
+        let (rgb, cov, w, h) = edge_frame();
+        let bg = vec![Some([1.0, 0.2, 0.2]); w * h];
+        let out = light_wrap(&rgb, &bg, &cov, w, h, 4.0, 1.0);
+        let edge = 2 * w + 11;
+        assert!(out[edge][0] > rgb[edge][0]);
+        // Deep inside the person no background is in reach.
+        assert_eq!(out[2 * w + 2], rgb[2 * w + 2]);
+        // The background itself is left to the composite.
+        assert_eq!(out[2 * w + 20], rgb[2 * w + 20]);
+    }
+
+    #[test]
+    fn light_wrap_fades_with_distance_from_the_edge() {
+        let (rgb, cov, w, h) = edge_frame();
+        let bg = vec![Some([1.0, 1.0, 1.0]); w * h];
+        let out = light_wrap(&rgb, &bg, &cov, w, h, 4.0, 1.0);
+        let gain = |x: usize| out[2 * w + x][2] - rgb[2 * w + x][2];
+        assert!(gain(11) > gain(9));
+        assert!(gain(9) > 0.0);
+    }
+
+    #[test]
+    fn unresolved_background_taps_do_not_darken_the_wrap() {
+        let (rgb, cov, w, h) = edge_frame();
+        // The blur resolves no background just outside the edge.
+        let mut holes = vec![Some([1.0, 1.0, 1.0]); w * h];
+        let mut black = holes.clone();
+        for y in 0..h {
+            holes[y * w + 13] = None;
+            black[y * w + 13] = Some([0.0, 0.0, 0.0]);
+        }
+        let skipped = light_wrap(&rgb, &holes, &cov, w, h, 4.0, 1.0);
+        let darkened = light_wrap(&rgb, &black, &cov, w, h, 4.0, 1.0);
+        let edge = 2 * w + 11;
+        assert!(skipped[edge][2] > darkened[edge][2]);
+        assert!(skipped[edge][2] > rgb[edge][2]);
+    }
+
+    #[test]
+    fn zero_intensity_light_wrap_is_identity() {
+        let (rgb, cov, w, h) = edge_frame();
+        let bg = vec![Some([1.0, 1.0, 1.0]); w * h];
+        assert_eq!(light_wrap(&rgb, &bg, &cov, w, h, 4.0, 0.0), rgb);
+    }
+
+    #[test]
+    fn outline_covers_its_width_beyond_the_silhouette() {
+        assert_eq!(outline_alpha(-1.0, 0.01, 0.001), 1.0);
+        assert_eq!(outline_alpha(0.005, 0.01, 0.001), 1.0);
+        assert_eq!(outline_alpha(0.02, 0.01, 0.001), 0.0);
+        assert!((outline_alpha(0.01, 0.01, 0.002) - 0.5).abs() < 1e-6);
+        assert_eq!(outline_alpha(-1.0, 0.0, 0.001), 0.0);
+    }
+
+    #[test]
+    fn glow_fades_out_past_the_outline() {
+        assert_eq!(glow_alpha(0.01, 0.01, 0.05), 1.0);
+        assert_eq!(glow_alpha(0.07, 0.01, 0.05), 0.0);
+        assert!(glow_alpha(0.02, 0.01, 0.05) > glow_alpha(0.04, 0.01, 0.05));
+        assert_eq!(glow_alpha(0.02, 0.01, 0.0), 0.0);
+    }
+
+    #[test]
+    fn shadow_is_half_covered_on_the_silhouette() {
+        assert!((silhouette_shadow_alpha(0.0, 0.01) - 0.5).abs() < 1e-6);
+        assert_eq!(silhouette_shadow_alpha(-0.02, 0.01), 1.0);
+        assert_eq!(silhouette_shadow_alpha(0.02, 0.01), 0.0);
+    }
+
+    #[test]
+    fn refine_mask_thresholds_and_inverts() {
+        assert_eq!(refine_mask(0.2, 0.5, 0.1, false), 0.0);
+        assert_eq!(refine_mask(0.8, 0.5, 0.1, false), 1.0);
+        assert!((refine_mask(0.5, 0.5, 0.1, false) - 0.5).abs() < 1e-6);
+        assert_eq!(refine_mask(0.2, 0.5, 0.1, true), 1.0);
+        // No softness: a hard step at the threshold.
+        assert_eq!(refine_mask(0.49, 0.5, 0.0, false), 0.0);
+        assert_eq!(refine_mask(0.51, 0.5, 0.0, false), 1.0);
+    }
+}
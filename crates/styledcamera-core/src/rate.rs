@@ -0,0 +1,190 @@
+// Lower bound for automatic mask rates; below this the mask is too stale to be useful.
+const MIN_AUTO_FPS: f32 = 1.0;
+// Automatic rates never back off below this share of the requested rate, so a latency spike
+// costs at most a halved mask rate and recovers within a few updates.
+const MIN_AUTO_RATE_SHARE: f32 = 0.5;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MaskRateTarget {
+    /// Keep the segmentation worker busy for at most this fraction of wall time (0..1].
+    CpuBudget(f32),
+    /// Back off whenever the measured mask latency exceeds this many milliseconds.
+    LatencyCeilingMs(f32),
+}
+
+/// Derives the segmentation request interval from measured worker time.
+///
+/// `max_fps` is the user-requested mask FPS and acts as an upper bound for the automatic rate;
+/// the rate never drops below half of it (or 1 FPS, whichever is higher).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MaskRateGovernor {
+    infer_ema_ms: f32,
+    interval_ms: f32,
+}
+
+impl MaskRateGovernor {
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn infer_ema_ms(&self) -> f32 {
+        self.infer_ema_ms
+    }
+
+    pub fn interval_ms(&self, max_fps: f32) -> f32 {
+        let (min_ms, max_ms) = interval_bounds_ms(max_fps);
+        if self.interval_ms <= 0.0 {
+            return min_ms;
+        }
+        self.interval_ms.clamp(min_ms, max_ms)
+    }
+
+    pub fn update(&mut self, target: MaskRateTarget, infer_ms: f32, latency_ms: f32, max_fps: f32) {
+        if infer_ms.is_finite() && (0.0..=2000.0).contains(&infer_ms) {
+            if self.infer_ema_ms <= 0.0 {
+                self.infer_ema_ms = infer_ms;
+            } else {
+                self.infer_ema_ms = (self.infer_ema_ms * 0.8) + (infer_ms * 0.2);
+            }
+        }
+        if self.infer_ema_ms <= 0.0 {
+            return;
+        }
+
+        let (min_ms, max_ms) = interval_bounds_ms(max_fps);
+        let mut interval = self.interval_ms(max_fps);
+
+        match target {
+            MaskRateTarget::CpuBudget(budget) => {
+                let desired = self.infer_ema_ms / budget.clamp(0.05, 1.0);
+                interval += (desired - interval) * 0.25;
+            }
+            MaskRateTarget::LatencyCeilingMs(ceiling_ms) => {
+                // Requests faster than the worker can serve only overwrite queued frames.
+                let floor = self.infer_ema_ms * 1.1;
+                let ceiling_ms = ceiling_ms.max(1.0);
+                if latency_ms.is_finite() && latency_ms > ceiling_ms {
+                    interval *= 1.25;
+                } else if latency_ms.is_finite() && latency_ms < ceiling_ms * 0.8 {
+                    interval *= 0.95;
+                }
+                interval = interval.max(floor);
+            }
+        }
+
+        self.interval_ms = interval.clamp(min_ms, max_ms);
+    }
+}
+
+fn interval_bounds_ms(max_fps: f32) -> (f32, f32) {
+    let max_fps = if max_fps.is_finite() { max_fps.max(MIN_AUTO_FPS) } else { MIN_AUTO_FPS };
+    let min_fps = (max_fps * MIN_AUTO_RATE_SHARE).max(MIN_AUTO_FPS);
+    (1000.0 / max_fps, 1000.0 / min_fps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(gov: &mut MaskRateGovernor, target: MaskRateTarget, infer_ms: f32, latency_ms: f32, n: usize) {
+        for _ in 0..n {
+            gov.update(target, infer_ms, latency_ms, 30.0);
+        }
+    }
+
+    #[test]
+    fn starts_at_requested_rate() {
+        let gov = MaskRateGovernor::default();
+        assert!((gov.interval_ms(20.0) - 50.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn cpu_budget_converges_to_inference_over_budget() {
+        let mut gov = MaskRateGovernor::default();
+        for _ in 0..100 {
+            gov.update(MaskRateTarget::CpuBudget(0.5), 80.0, 0.0, 10.0);
+        }
+        assert!((gov.interval_ms(10.0) - 160.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn cpu_budget_is_capped_by_requested_fps() {
+        let mut gov = MaskRateGovernor::default();
+        run(&mut gov, MaskRateTarget::CpuBudget(1.0), 2.0, 0.0, 100);
+        assert!((gov.interval_ms(30.0) - 1000.0 / 30.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn interval_never_drops_below_half_the_requested_rate() {
+        let mut gov = MaskRateGovernor::default();
+        run(&mut gov, MaskRateTarget::CpuBudget(0.05), 500.0, 0.0, 100);
+        assert!((gov.interval_ms(30.0) - 2000.0 / 30.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn interval_never_exceeds_minimum_rate() {
+        let mut gov = MaskRateGovernor::default();
+        for _ in 0..100 {
+            gov.update(MaskRateTarget::CpuBudget(0.05), 500.0, 0.0, 1.5);
+        }
+        assert_eq!(gov.interval_ms(1.5), 1000.0);
+    }
+
+    #[test]
+    fn latency_ceiling_backs_off_when_exceeded() {
+        let mut gov = MaskRateGovernor::default();
+        run(&mut gov, MaskRateTarget::LatencyCeilingMs(100.0), 20.0, 150.0, 5);
+        assert!(gov.interval_ms(30.0) > 1000.0 / 30.0 * 1.5);
+    }
+
+    #[test]
+    fn latency_ceiling_recovers_quickly_after_a_single_spike() {
+        let target = MaskRateTarget::LatencyCeilingMs(100.0);
+        let mut gov = MaskRateGovernor::default();
+        run(&mut gov, target, 20.0, 40.0, 50);
+        run(&mut gov, target, 20.0, 2000.0, 1);
+        assert!(gov.interval_ms(30.0) > 1000.0 / 30.0);
+        run(&mut gov, target, 20.0, 40.0, 5);
+        assert!((gov.interval_ms(30.0) - 1000.0 / 30.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn latency_ceiling_recovers_from_a_long_stall() {
+        let target = MaskRateTarget::LatencyCeilingMs(100.0);
+        let mut gov = MaskRateGovernor::default();
+        run(&mut gov, target, 20.0, 2000.0, 500);
+        // Held at half the requested rate instead of sinking to 1 FPS.
+        assert!((gov.interval_ms(30.0) - 2000.0 / 30.0).abs() < 1e-3);
+        run(&mut gov, target, 20.0, 40.0, 15);
+        assert!((gov.interval_ms(30.0) - 1000.0 / 30.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn latency_ceiling_recovers_but_stays_above_inference() {
+        let mut gov = MaskRateGovernor::default();
+        run(&mut gov, MaskRateTarget::LatencyCeilingMs(100.0), 60.0, 150.0, 10);
+        let backed_off = gov.interval_ms(30.0);
+        run(&mut gov, MaskRateTarget::LatencyCeilingMs(100.0), 60.0, 40.0, 200);
+        let recovered = gov.interval_ms(30.0);
+        assert!(recovered < backed_off);
+        assert!((recovered - 66.0).abs() < 0.5);
+    }
+
+    #[test]
+    fn ignores_bad_inference_samples() {
+        let mut gov = MaskRateGovernor::default();
+        gov.update(MaskRateTarget::CpuBudget(0.5), f32::NAN, 0.0, 30.0);
+        gov.update(MaskRateTarget::CpuBudget(0.5), -5.0, 0.0, 30.0);
+        assert_eq!(gov.infer_ema_ms(), 0.0);
+        assert!((gov.interval_ms(30.0) - 1000.0 / 30.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn reset_clears_state() {
+        let mut gov = MaskRateGovernor::default();
+        run(&mut gov, MaskRateTarget::CpuBudget(0.25), 50.0, 0.0, 20);
+        gov.reset();
+        assert_eq!(gov.infer_ema_ms(), 0.0);
+        assert!((gov.interval_ms(10.0) - 100.0).abs() < 1e-4);
+    }
+}
@@ -14,6 +14,15 @@ pub fn postprocess_mask_u8(
     Some(to_u8_mask(prev_mask))
 }
 
+pub fn ort_version_from_build_info(info: &str) -> Option<&str> {
+    // ORT build info looks like:
+    //   "ORT Build Info: git-branch=rel-1.19.0, git-commit-id=26250ae, build type=Release, ..."
+    let start = info.find("git-branch=")? + "git-branch=".len();
+    let branch = info[start..].split(',').next()?.trim();
+    let version = branch.strip_prefix("rel-").unwrap_or(branch);
+    if version.is_empty() { None } else { Some(version) }
+}
+
 fn extract_mask_values(expected: usize, out_shape: &[i64], out_data: &[f32]) -> Option<Vec<f32>> {
     // Handle common MediaPipe-style outputs:
     // - [1, H, W, 1] or [1, 1, H, W] (single channel)
@@ -161,4 +170,18 @@ mod tests {
         let mask = postprocess_mask_u8(expected, &out_shape, &[1.0], &mut prev, 1.0).unwrap();
         assert_eq!(mask, vec![3]);
     }
+
+    #[test]
+    fn parses_ort_version_from_release_build_info() {
+        let info = "ORT Build Info: git-branch=rel-1.19.0, git-commit-id=26250ae, build type=Release";
+        assert_eq!(ort_version_from_build_info(info), Some("1.19.0"));
+    }
+
+    #[test]
+    fn ort_version_falls_back_to_branch_name() {
+        let info = "ORT Build Info: git-branch=main, git-commit-id=abc";
+        assert_eq!(ort_version_from_build_info(info), Some("main"));
+        assert_eq!(ort_version_from_build_info("ORT Build Info: build type=Release"), None);
+        assert_eq!(ort_version_from_build_info("git-branch=, build type=Release"), None);
+    }
 }
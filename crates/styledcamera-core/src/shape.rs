@@ -0,0 +1,375 @@
+//! CPU ports of the card shape signed distance functions in shape_style.effect.
+//!
+//! Points are in pixels relative to the shape center with y pointing down (as in the shader);
+//! distances are negative inside the shape.
+
+use std::f32::consts::PI;
+
+use crate::distance::{person_pixels, signed_distance_to_person};
+
+/// Height of the unit heart below, from its bottom tip to the top of the lobes.
+const HEART_HEIGHT: f32 = 0.75 + std::f32::consts::SQRT_2 / 4.0;
+
+/// Heart size relative to the inscribed radius; the lobes (about 1.21 units wide) fit in the
+/// inscribed square.
+pub const HEART_SCALE: f32 = 1.65;
+
+fn length(p: [f32; 2]) -> f32 {
+    (p[0] * p[0] + p[1] * p[1]).sqrt()
+}
+
+fn dot(a: [f32; 2], b: [f32; 2]) -> f32 {
+    a[0] * b[0] + a[1] * b[1]
+}
+
+/// Rotates a sample point so the shape appears turned clockwise by `degrees` on screen.
+pub fn rotate(p: [f32; 2], degrees: f32) -> [f32; 2] {
+    let (s, c) = (-degrees.to_radians()).sin_cos();
+    [p[0] * c - p[1] * s, p[0] * s + p[1] * c]
+}
+
+pub fn sd_circle(p: [f32; 2], r: f32) -> f32 {
+    length(p) - r
+}
+
+pub fn sd_box(p: [f32; 2], half: [f32; 2]) -> f32 {
+    let d = [p[0].abs() - half[0], p[1].abs() - half[1]];
+    length([d[0].max(0.0), d[1].max(0.0)]) + d[0].max(d[1]).min(0.0)
+}
+
+pub fn sd_round_box(p: [f32; 2], half: [f32; 2], r: f32) -> f32 {
+    sd_box(p, [half[0] - r, half[1] - r]) - r
+}
+
+/// Superellipse |x|^n + |y|^n = r^n (2 = circle, 4 = squircle, larger tends to a square).
+///
+/// There is no closed form, so the implicit function is divided by its gradient length: exact
+/// on the edge and close to it, which is where the feather, border and shadow look.
+pub fn sd_superellipse(p: [f32; 2], r: f32, exponent: f32) -> f32 {
+    let n = exponent.clamp(2.0, 12.0);
+    let r = r.max(1e-3);
+    let q = [p[0].abs() / r, p[1].abs() / r];
+    let g = (q[0].powf(n) + q[1].powf(n)).powf(1.0 / n);
+    if g < 1e-4 {
+        return -r;
+    }
+    let grad = [(q[0] / g).powf(n - 1.0), (q[1] / g).powf(n - 1.0)];
+    // The gradient length only depends on the direction and is at least 2^(1/n - 1/2) > 0.7.
+    r * (g - 1.0) / length(grad).max(0.7)
+}
+
+/// Star with `points` tips at radius `r` (the first one up) and inner corners at
+/// `inner * r`. Exact: the angle is folded into half a point, whose edge is one segment.
+pub fn sd_star(p: [f32; 2], r: f32, points: f32, inner: f32) -> f32 {
+    let an = PI / points.max(2.0);
+    // Angle from straight up, clockwise on screen.
+    let theta = p[0].atan2(-p[1]);
+    let b = (theta + an).rem_euclid(2.0 * an) - an;
+    let len = length(p);
+    let q = [len * b.abs().cos(), len * b.abs().sin()];
+
+    let a = [r, 0.0];
+    let ri = r * inner.clamp(0.01, 1.0);
+    let ba = [ri * an.cos() - a[0], ri * an.sin() - a[1]];
+    let pa = [q[0] - a[0], q[1] - a[1]];
+    let h = (dot(pa, ba) / dot(ba, ba).max(1e-8)).clamp(0.0, 1.0);
+    let d = length([pa[0] - ba[0] * h, pa[1] - ba[1] * h]);
+    // The center is on the positive side of the edge.
+    if ba[0] * pa[1] - ba[1] * pa[0] > 0.0 {
+        -d
+    } else {
+        d
+    }
+}
+
+/// Regular polygon with `sides` corners on a circle of radius `r`, the first one up.
+pub fn sd_regular_polygon(p: [f32; 2], r: f32, sides: f32) -> f32 {
+    let sides = sides.max(3.0);
+    // A star whose inner corners sit on the edge midpoints.
+    sd_star(p, r, sides, (PI / sides).cos())
+}
+
+/// Heart filling the square of inscribed radius `r`, point down.
+pub fn sd_heart(p: [f32; 2], r: f32) -> f32 {
+    let s = (r * HEART_SCALE).max(1e-3);
+    // Unit heart: tip at the origin, y up, lobes up to HEART_HEIGHT.
+    let h = [p[0].abs() / s, HEART_HEIGHT * 0.5 - p[1] / s];
+    let d = if h[0] + h[1] > 1.0 {
+        length([h[0] - 0.25, h[1] - 0.75]) - std::f32::consts::SQRT_2 / 4.0
+    } else {
+        let m = 0.5 * (h[0] + h[1]).max(0.0);
+        let d2 = dot([h[0], h[1] - 1.0], [h[0], h[1] - 1.0])
+            .min(dot([h[0] - m, h[1] - m], [h[0] - m, h[1] - m]));
+        d2.sqrt() * (h[0] - h[1]).signum()
+    };
+    d * s
+}
+
+/// Alpha (0..1) at or above which a pixel of a custom shape image is inside the shape.
+pub const IMAGE_SHAPE_ALPHA_THRESHOLD: f32 = 0.5;
+
+/// Longest side of the field built from a custom shape image; larger images are box-filtered
+/// down first.
+pub const MAX_IMAGE_SHAPE_FIELD: usize = 1024;
+
+// Field values are clamped to this (in image lengths) so fully opaque or transparent regions
+// stay finite in the texture.
+const IMAGE_SHAPE_FIELD_LIMIT: f32 = 2.0;
+
+/// Signed distance field of a custom shape image, in units of the image's longer side
+/// (negative inside). Row-major, `width * height`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageShapeField {
+    pub width: usize,
+    pub height: usize,
+    pub data: Vec<f32>,
+}
+
+impl ImageShapeField {
+    /// Builds the field from an image's alpha channel (row-major, one byte per pixel). None for
+    /// an empty image or one without any opaque pixel.
+    pub fn from_alpha(alpha: &[u8], width: usize, height: usize) -> Option<Self> {
+        if width == 0 || height == 0 || alpha.len() != width * height {
+            return None;
+        }
+        let (alpha, width, height) = downsample_alpha(alpha, width, height, MAX_IMAGE_SHAPE_FIELD);
+        let inside = person_pixels(&alpha, IMAGE_SHAPE_ALPHA_THRESHOLD, false);
+        if !inside.iter().any(|&p| p) {
+            return None;
+        }
+
+        let px = 1.0 / width.max(height) as f32;
+        let data = signed_distance_to_person(&inside, width, height, [px, px])
+            .into_iter()
+            // Each side measures to the other side's nearest pixel center; pull both half a
+            // pixel towards the edge so the field is continuous across it.
+            .map(|d| {
+                let d = if d > 0.0 { d - 0.5 * px } else { d + 0.5 * px };
+                d.clamp(-IMAGE_SHAPE_FIELD_LIMIT, IMAGE_SHAPE_FIELD_LIMIT)
+            })
+            .collect();
+        Some(Self {
+            width,
+            height,
+            data,
+        })
+    }
+
+    /// Bilinear sample at `uv` (0..1), clamped to the edge like the shader's sampler.
+    pub fn sample(&self, uv: [f32; 2]) -> f32 {
+        let x = (uv[0] * self.width as f32 - 0.5).clamp(0.0, (self.width - 1) as f32);
+        let y = (uv[1] * self.height as f32 - 0.5).clamp(0.0, (self.height - 1) as f32);
+        let (x0, y0) = (x.floor() as usize, y.floor() as usize);
+        let (x1, y1) = ((x0 + 1).min(self.width - 1), (y0 + 1).min(self.height - 1));
+        let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+        let at = |x: usize, y: usize| self.data[y * self.width + x];
+        let top = at(x0, y0) + (at(x1, y0) - at(x0, y0)) * fx;
+        let bottom = at(x0, y1) + (at(x1, y1) - at(x0, y1)) * fx;
+        top + (bottom - top) * fy
+    }
+}
+
+// Box-filters alpha down by a whole factor until its longer side fits in `max_side`.
+fn downsample_alpha(
+    alpha: &[u8],
+    width: usize,
+    height: usize,
+    max_side: usize,
+) -> (Vec<u8>, usize, usize) {
+    let k = width.max(height).div_ceil(max_side.max(1));
+    if k <= 1 {
+        return (alpha.to_vec(), width, height);
+    }
+
+    let (w, h) = (width.div_ceil(k), height.div_ceil(k));
+    let mut out = Vec::with_capacity(w * h);
+    for by in 0..h {
+        for bx in 0..w {
+            let (mut sum, mut count) = (0u32, 0u32);
+            for y in by * k..((by + 1) * k).min(height) {
+                for x in bx * k..((bx + 1) * k).min(width) {
+                    sum += alpha[y * width + x] as u32;
+                    count += 1;
+                }
+            }
+            out.push((sum / count.max(1)) as u8);
+        }
+    }
+    (out, w, h)
+}
+
+/// Half extent in pixels of a custom shape image of `image_size` contained in a shape area of
+/// `half_size`; mirrors `ImageShapeHalf` in shape_style.effect.
+pub fn image_shape_half(image_size: [f32; 2], half_size: [f32; 2]) -> [f32; 2] {
+    let (w, h) = (image_size[0].max(1.0), image_size[1].max(1.0));
+    let scale = (2.0 * half_size[0] / w).min(2.0 * half_size[1] / h);
+    [0.5 * w * scale, 0.5 * h * scale]
+}
+
+/// Signed distance to a custom image shape contained in `half_size`; mirrors `sdImageShape` in
+/// shape_style.effect. Beyond the image the field's border value is extended by the distance
+/// to the image, and the image's rectangle always bounds the shape.
+pub fn sd_image_shape(field: &ImageShapeField, p: [f32; 2], half_size: [f32; 2]) -> f32 {
+    let half = image_shape_half([field.width as f32, field.height as f32], half_size);
+    let uv = [0.5 + p[0] / (2.0 * half[0]), 0.5 + p[1] / (2.0 * half[1])];
+    let d = field.sample(uv) * 2.0 * half[0].max(half[1]);
+    let beyond = length([
+        (p[0].abs() - half[0]).max(0.0),
+        (p[1].abs() - half[1]).max(0.0),
+    ]);
+    (d + beyond).max(sd_box(p, half))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f32) -> bool {
+        (a - b).abs() < 1e-3
+    }
+
+    #[test]
+    fn superellipse_of_exponent_two_is_a_circle() {
+        for p in [[0.0, 0.0], [30.0, 40.0], [100.0, -20.0], [-5.0, 90.0]] {
+            let (a, b) = (sd_superellipse(p, 80.0, 2.0), sd_circle(p, 80.0));
+            assert!((a - b).abs() < 1e-2, "{p:?}: {a} vs {b}");
+        }
+    }
+
+    #[test]
+    fn squircle_touches_the_inscribed_square_on_the_axes() {
+        assert!(close(sd_superellipse([100.0, 0.0], 100.0, 4.0), 0.0));
+        assert!(close(sd_superellipse([0.0, -110.0], 100.0, 4.0), 10.0));
+        // Fuller than the circle towards the corners, inside the square.
+        let corner = [75.0, 75.0];
+        assert!(sd_superellipse(corner, 100.0, 4.0) < 0.0);
+        assert!(sd_circle(corner, 100.0) > 0.0);
+        assert!(sd_superellipse(corner, 100.0, 4.0) > sd_box(corner, [100.0, 100.0]));
+    }
+
+    #[test]
+    fn polygon_corners_and_edges() {
+        let r = 100.0;
+        for sides in [3.0, 5.0, 6.0, 8.0] {
+            // First corner straight up (negative y on screen).
+            assert!(close(sd_regular_polygon([0.0, -r], r, sides), 0.0));
+            assert!(close(
+                sd_regular_polygon([0.0, 0.0], r, sides),
+                -r * (PI / sides).cos()
+            ));
+            // Edge midpoint between the first two corners.
+            let mid = rotate([0.0, -r * (PI / sides).cos()], -180.0 / sides);
+            assert!(close(sd_regular_polygon(mid, r, sides), 0.0), "{sides}");
+        }
+        // A square with a corner up: the point right of the center at r sits on a corner.
+        assert!(close(sd_regular_polygon([r, 0.0], r, 4.0), 0.0));
+        assert!(close(sd_regular_polygon([r + 10.0, 0.0], r, 4.0), 10.0));
+    }
+
+    #[test]
+    fn rotation_by_one_sector_is_invisible() {
+        let p = [37.0, -52.0];
+        let a = sd_regular_polygon(p, 90.0, 6.0);
+        let b = sd_regular_polygon(rotate(p, 60.0), 90.0, 6.0);
+        assert!(close(a, b));
+        // Quarter turn clockwise moves the top corner to the right.
+        assert!(close(
+            sd_star(rotate([60.0, 0.0], 90.0), 60.0, 5.0, 0.4),
+            0.0
+        ));
+    }
+
+    #[test]
+    fn star_tips_and_inner_corners() {
+        let (r, inner) = (100.0, 0.4);
+        assert!(close(sd_star([0.0, -r], r, 5.0, inner), 0.0));
+        let inner_corner = rotate([0.0, -r * inner], 36.0);
+        assert!(close(sd_star(inner_corner, r, 5.0, inner), 0.0));
+        let center = sd_star([0.0, 0.0], r, 5.0, inner);
+        assert!(center < 0.0 && center >= -r * inner - 1e-3, "{center}");
+        // Between two tips, just beyond the inner radius, is outside.
+        let notch = rotate([0.0, -r * inner - 5.0], 36.0);
+        assert!(sd_star(notch, r, 5.0, inner) > 0.0);
+    }
+
+    #[test]
+    fn heart_is_centered_and_symmetric() {
+        let r = 100.0;
+        assert!(sd_heart([0.0, 0.0], r) < 0.0);
+        assert!(close(sd_heart([30.0, 20.0], r), sd_heart([-30.0, 20.0], r)));
+        // Tip at the bottom, dip at the top center, all within the inscribed square.
+        let tip_y = HEART_HEIGHT * 0.5 * r * HEART_SCALE;
+        assert!(close(sd_heart([0.0, tip_y], r), 0.0));
+        assert!(sd_heart([0.0, tip_y + 5.0], r) > 0.0);
+        assert!(sd_heart([0.0, -tip_y + 5.0], r) > 0.0);
+        for p in [[r, 0.0], [-r, 0.0], [0.0, r], [0.0, -r]] {
+            assert!(sd_heart(p, r) > 0.0, "{p:?}");
+        }
+    }
+
+    fn disc_alpha(size: usize, radius: f32) -> Vec<u8> {
+        let c = size as f32 / 2.0;
+        let mut alpha = Vec::with_capacity(size * size);
+        for y in 0..size {
+            for x in 0..size {
+                let d = ((x as f32 + 0.5 - c).powi(2) + (y as f32 + 0.5 - c).powi(2)).sqrt();
+                alpha.push(if d <= radius { 255 } else { 0 });
+            }
+        }
+        alpha
+    }
+
+    #[test]
+    fn image_shape_of_a_disc_measures_like_a_circle() {
+        let field = ImageShapeField::from_alpha(&disc_alpha(64, 20.0), 64, 64).unwrap();
+        // The 64 px image fills a 200 px square: the disc has a radius of 62.5 px.
+        let half = [100.0, 100.0];
+        let r = 20.0 * 200.0 / 64.0;
+        for p in [[0.0, 0.0], [r, 0.0], [0.0, -r], [40.0, 30.0], [90.0, 90.0]] {
+            let (a, b) = (sd_image_shape(&field, p, half), sd_circle(p, r));
+            assert!((a - b).abs() < 4.0, "{p:?}: {a} vs {b}");
+        }
+        // Beyond the image the distance keeps growing.
+        assert!(
+            sd_image_shape(&field, [300.0, 0.0], half) > sd_image_shape(&field, [150.0, 0.0], half)
+        );
+    }
+
+    #[test]
+    fn opaque_image_is_its_rectangle_and_transparent_has_no_shape() {
+        let field = ImageShapeField::from_alpha(&[255; 8 * 4], 8, 4).unwrap();
+        // A 2:1 image contained in a square.
+        assert_eq!(image_shape_half([8.0, 4.0], [100.0, 100.0]), [100.0, 50.0]);
+        for p in [[0.0, 0.0], [0.0, 60.0], [120.0, 10.0], [-99.0, 49.0]] {
+            let (a, b) = (
+                sd_image_shape(&field, p, [100.0, 100.0]),
+                sd_box(p, [100.0, 50.0]),
+            );
+            assert!(close(a, b), "{p:?}: {a} vs {b}");
+        }
+        assert_eq!(ImageShapeField::from_alpha(&[0; 16], 4, 4), None);
+        assert_eq!(ImageShapeField::from_alpha(&[255; 3], 2, 2), None);
+    }
+
+    #[test]
+    fn field_is_continuous_across_the_edge() {
+        // Left half opaque: the pixels either side of the edge sit half a pixel from it.
+        let alpha: Vec<u8> = (0..8 * 2)
+            .map(|i| if i % 8 < 4 { 255 } else { 0 })
+            .collect();
+        let field = ImageShapeField::from_alpha(&alpha, 8, 2).unwrap();
+        assert!(close(field.data[3], -0.5 / 8.0));
+        assert!(close(field.data[4], 0.5 / 8.0));
+        assert!(close(field.sample([0.5, 0.5]), 0.0));
+    }
+
+    #[test]
+    fn large_images_are_box_filtered_down() {
+        let (alpha, w, h) =
+            downsample_alpha(&vec![255; 3000 * 10], 3000, 10, MAX_IMAGE_SHAPE_FIELD);
+        assert_eq!((w, h), (1000, 4));
+        assert!(alpha.iter().all(|&a| a == 255));
+        let (_, w, h) = downsample_alpha(&[0; 12], 4, 3, MAX_IMAGE_SHAPE_FIELD);
+        assert_eq!((w, h), (4, 3));
+    }
+}
@@ -1,5 +1,6 @@
 pub fn update_mask_latency_ema_ms(ema_ms: &mut f32, measured_ms: f32) {
-    if !measured_ms.is_finite() || measured_ms < 0.0 || measured_ms > 2000.0 {
+    // Also rejects NaN and infinities.
+    if !(0.0..=2000.0).contains(&measured_ms) {
         return;
     }
 
@@ -10,9 +11,56 @@ pub fn update_mask_latency_ema_ms(ema_ms: &mut f32, measured_ms: f32) {
     }
 }
 
+pub fn update_rate_ema_hz(ema_hz: &mut f32, interval_s: f32) {
+    // Intervals above 10 s are treated as a pause rather than a rate sample.
+    if !interval_s.is_finite() || interval_s <= 0.0 || interval_s > 10.0 {
+        return;
+    }
+
+    let hz = 1.0 / interval_s;
+    if *ema_hz <= 0.0 {
+        *ema_hz = hz;
+    } else {
+        *ema_hz = (*ema_hz * 0.8) + (hz * 0.2);
+    }
+}
+
+/// Hysteresis for an externally applied sync offset, so small wobbles of the measured delay
+/// don't keep rewriting it.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SyncOffsetHysteresis {
+    applied_ms: Option<f32>,
+}
+
+impl SyncOffsetHysteresis {
+    pub fn applied_ms(&self) -> Option<f32> {
+        self.applied_ms
+    }
+
+    pub fn reset(&mut self) {
+        self.applied_ms = None;
+    }
+
+    /// Returns the offset to apply (whole ms) when it moved by at least `threshold_ms`.
+    pub fn update(&mut self, target_ms: f32, threshold_ms: f32) -> Option<f32> {
+        if !target_ms.is_finite() {
+            return None;
+        }
+
+        let target_ms = target_ms.max(0.0).round();
+        if let Some(applied) = self.applied_ms {
+            if (target_ms - applied).abs() < threshold_ms.max(1.0) {
+                return None;
+            }
+        }
+        self.applied_ms = Some(target_ms);
+        Some(target_ms)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::update_mask_latency_ema_ms;
+    use super::{update_mask_latency_ema_ms, update_rate_ema_hz, SyncOffsetHysteresis};
 
     #[test]
     fn mask_latency_ema_initializes() {
@@ -36,5 +84,56 @@ mod tests {
         update_mask_latency_ema_ms(&mut ema, 5000.0);
         assert_eq!(ema, 50.0);
     }
+
+    #[test]
+    fn rate_ema_initializes_from_interval() {
+        let mut ema = 0.0;
+        update_rate_ema_hz(&mut ema, 0.1);
+        assert!((ema - 10.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn rate_ema_updates_with_weights() {
+        let mut ema = 10.0;
+        update_rate_ema_hz(&mut ema, 0.05);
+        assert!((ema - 12.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn rate_ema_ignores_bad_intervals() {
+        let mut ema = 15.0;
+        update_rate_ema_hz(&mut ema, 0.0);
+        update_rate_ema_hz(&mut ema, -0.1);
+        update_rate_ema_hz(&mut ema, f32::INFINITY);
+        update_rate_ema_hz(&mut ema, 30.0);
+        assert_eq!(ema, 15.0);
+    }
+
+    #[test]
+    fn sync_offset_applies_first_value() {
+        let mut h = SyncOffsetHysteresis::default();
+        assert_eq!(h.update(42.4, 10.0), Some(42.0));
+        assert_eq!(h.applied_ms(), Some(42.0));
+    }
+
+    #[test]
+    fn sync_offset_ignores_small_wobble() {
+        let mut h = SyncOffsetHysteresis::default();
+        h.update(80.0, 10.0);
+        assert_eq!(h.update(85.0, 10.0), None);
+        assert_eq!(h.update(72.0, 10.0), None);
+        assert_eq!(h.update(91.0, 10.0), Some(91.0));
+        assert_eq!(h.applied_ms(), Some(91.0));
+    }
+
+    #[test]
+    fn sync_offset_rejects_bad_values_and_resets() {
+        let mut h = SyncOffsetHysteresis::default();
+        assert_eq!(h.update(f32::NAN, 10.0), None);
+        assert_eq!(h.update(-20.0, 10.0), Some(0.0));
+        h.reset();
+        assert_eq!(h.applied_ms(), None);
+        assert_eq!(h.update(3.0, 10.0), Some(3.0));
+    }
 }
 
@@ -0,0 +1,180 @@
+use std::ffi::{CStr, CString};
+use std::sync::Mutex;
+
+use obs_sys as obs;
+use styledcamera_core::timing::SyncOffsetHysteresis;
+
+// Changes smaller than this are below what viewers notice as lip-sync drift.
+const AUDIO_SYNC_HYSTERESIS_MS: f32 = 10.0;
+
+/// The source's own offset and the compensated offset last written over it.
+///
+/// Kept in the filter's settings: OBS saves the compensated offset with the source, so after a
+/// restart the live offset alone cannot tell the user's offset from ours.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) struct SyncOffsetState {
+    pub base_offset_ns: i64,
+    pub written_offset_ns: i64,
+}
+
+struct AppliedOffset {
+    weak: *mut obs::obs_weak_source_t,
+    // Which setting the offset was applied for (None = the filter's parent).
+    target: Option<CString>,
+    // base_offset_ns is restored when we let go.
+    state: SyncOffsetState,
+}
+
+#[derive(Default)]
+struct AudioSyncInner {
+    // Requested audio source by name; None means the filter's parent source.
+    target: Option<CString>,
+    applied: Option<AppliedOffset>,
+    // Loaded from the filter's settings; claimed by the first apply.
+    saved: Option<SyncOffsetState>,
+    hysteresis: SyncOffsetHysteresis,
+}
+
+/// Mirrors the filter's video delay onto an audio source's sync offset.
+///
+/// The target is set from `update` (UI thread) while `tick` runs on the render thread. `tick`
+/// only runs while the filter renders, so hide/deactivate and the disabled filter call `restore`.
+#[derive(Default)]
+pub(crate) struct AudioSync {
+    inner: Mutex<AudioSyncInner>,
+}
+
+impl AudioSync {
+    pub(crate) fn set_target(&self, target: Option<CString>) {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.target = target;
+        }
+    }
+
+    // The state saved with the previous session, see SyncOffsetState.
+    pub(crate) fn set_saved_state(&self, state: Option<SyncOffsetState>) {
+        if let Ok(mut inner) = self.inner.lock() {
+            inner.saved = state;
+        }
+    }
+
+    // What to save with the filter; None while no offset is applied.
+    pub(crate) fn saved_state(&self) -> Option<SyncOffsetState> {
+        let inner = self.inner.lock().ok()?;
+        Some(inner.applied.as_ref()?.state)
+    }
+
+    pub(crate) fn applied_offset_ms(&self) -> Option<f32> {
+        let inner = self.inner.lock().ok()?;
+        inner.applied.as_ref()?;
+        inner.hysteresis.applied_ms()
+    }
+
+    /// Applies `delay_ms` to the target source; `None` restores its original offset.
+    pub(crate) unsafe fn tick(&self, parent: *mut obs::obs_source_t, delay_ms: Option<f32>) {
+        let Ok(mut inner) = self.inner.lock() else {
+            return;
+        };
+
+        let target_changed = inner
+            .applied
+            .as_ref()
+            .is_some_and(|a| a.target != inner.target);
+        if target_changed || delay_ms.is_none() {
+            restore_locked(&mut inner);
+        }
+        let Some(delay_ms) = delay_ms else {
+            // Compensated when saved but off now: hand the source its own offset back.
+            if let Some(saved) = inner.saved.take() {
+                let source = resolve_target(parent, inner.target.as_deref());
+                if !source.is_null() {
+                    if obs::obs_source_get_sync_offset(source) == saved.written_offset_ns {
+                        obs::obs_source_set_sync_offset(source, saved.base_offset_ns);
+                    }
+                    obs::obs_source_release(source);
+                }
+            }
+            return;
+        };
+        inner.hysteresis.update(delay_ms, AUDIO_SYNC_HYSTERESIS_MS);
+        let Some(offset_ms) = inner.hysteresis.applied_ms() else {
+            return;
+        };
+
+        let mut source = match inner.applied.as_ref() {
+            Some(applied) => obs::obs_weak_source_get_source(applied.weak),
+            None => std::ptr::null_mut(),
+        };
+        if source.is_null() {
+            // The source we compensated was destroyed; there is nothing to restore, so resolve
+            // the target again (a source recreated under the same name starts from its own offset).
+            if let Some(applied) = inner.applied.take() {
+                obs::obs_weak_source_release(applied.weak);
+            }
+            source = resolve_target(parent, inner.target.as_deref());
+        }
+        if source.is_null() {
+            // Source missing (e.g. renamed); try again next frame.
+            inner.hysteresis.reset();
+            return;
+        }
+
+        let current_ns = obs::obs_source_get_sync_offset(source);
+        if inner.applied.is_none() {
+            // Still carrying what we wrote last session: the saved base is the user's offset.
+            let state = match inner.saved.take() {
+                Some(saved) if saved.written_offset_ns == current_ns => saved,
+                _ => SyncOffsetState { base_offset_ns: current_ns, written_offset_ns: current_ns },
+            };
+            inner.applied = Some(AppliedOffset {
+                weak: obs::obs_source_get_weak_source(source),
+                target: inner.target.clone(),
+                state,
+            });
+        }
+        if let Some(applied) = inner.applied.as_mut() {
+            // Anything but our own value is the user editing Sync Offset: compensate on top.
+            if current_ns != applied.state.written_offset_ns {
+                applied.state.base_offset_ns = current_ns;
+            }
+            let offset_ns = applied.state.base_offset_ns + (offset_ms as i64) * 1_000_000;
+            if offset_ns != current_ns {
+                obs::obs_source_set_sync_offset(source, offset_ns);
+            }
+            applied.state.written_offset_ns = offset_ns;
+        }
+        obs::obs_source_release(source);
+    }
+
+    pub(crate) unsafe fn restore(&self) {
+        if let Ok(mut inner) = self.inner.lock() {
+            restore_locked(&mut inner);
+        }
+    }
+}
+
+unsafe fn restore_locked(inner: &mut AudioSyncInner) {
+    inner.hysteresis.reset();
+    let Some(applied) = inner.applied.take() else {
+        return;
+    };
+
+    let source = obs::obs_weak_source_get_source(applied.weak);
+    if !source.is_null() {
+        // Edited since the last tick: the user's value is already the one to keep.
+        if obs::obs_source_get_sync_offset(source) == applied.state.written_offset_ns {
+            obs::obs_source_set_sync_offset(source, applied.state.base_offset_ns);
+        }
+        obs::obs_source_release(source);
+    }
+    obs::obs_weak_source_release(applied.weak);
+}
+
+// Returns a strong reference (release with obs_source_release) or null.
+unsafe fn resolve_target(parent: *mut obs::obs_source_t, target: Option<&CStr>) -> *mut obs::obs_source_t {
+    match target {
+        Some(name) => obs::obs_get_source_by_name(name.as_ptr()),
+        None if !parent.is_null() => obs::obs_source_get_ref(parent),
+        None => std::ptr::null_mut(),
+    }
+}
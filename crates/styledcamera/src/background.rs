@@ -0,0 +1,179 @@
+use std::ffi::{CStr, CString};
+use std::sync::Mutex;
+
+use obs_sys as obs;
+
+use crate::file_loader::{FileLoader, LoadEvent};
+use crate::util::cstr;
+
+// Image decoded by OBS's image decoder; the texture is created on upload.
+struct DecodedImage(obs::gs_image_file_t);
+
+// gs_image_file_t is plain data owned by this wrapper.
+unsafe impl Send for DecodedImage {}
+
+impl Drop for DecodedImage {
+    fn drop(&mut self) {
+        unsafe {
+            obs::obs_enter_graphics();
+            obs::gs_image_file_free(&mut self.0);
+            obs::obs_leave_graphics();
+        }
+    }
+}
+
+fn decode_image(path: &CStr) -> Result<DecodedImage, String> {
+    // gs_image_file_t is plain data; all-zero is its "not loaded" state.
+    let mut image = DecodedImage(unsafe { std::mem::zeroed() });
+    unsafe { obs::gs_image_file_init(&mut image.0, path.as_ptr()) };
+    if image.0.loaded {
+        Ok(image)
+    } else {
+        Err("could not decode the image".to_string())
+    }
+}
+
+// Static replacement background loaded through OBS's image decoder.
+pub(crate) struct BackgroundImage {
+    image: Option<DecodedImage>,
+    loader: FileLoader<DecodedImage>,
+}
+
+impl Default for BackgroundImage {
+    fn default() -> Self {
+        Self {
+            image: None,
+            loader: FileLoader::new(decode_image),
+        }
+    }
+}
+
+impl BackgroundImage {
+    // Starts decoding the image when the path or the file changed; see poll.
+    pub(crate) fn set_path(&self, path: Option<CString>) {
+        self.loader.set_path(path);
+    }
+
+    pub(crate) fn reload_if_modified(&self) {
+        self.loader.reload_if_modified();
+    }
+
+    // Uploads a finished decode. Must be called on the graphics thread.
+    pub(crate) unsafe fn poll(&mut self) {
+        let Some(event) = self.loader.take_event() else {
+            return;
+        };
+
+        obs::obs_enter_graphics();
+        self.image = None;
+        match event {
+            LoadEvent::Loaded(mut image) => {
+                obs::gs_image_file_init_texture(&mut image.0);
+                self.image = Some(image);
+            }
+            LoadEvent::Failed(_) => {
+                let path = self.loader.path().unwrap_or_default();
+                obs::blog(
+                    obs::LOG_WARNING as i32,
+                    cstr(b"StyledCamera: failed to load background image '%s'\n\0"),
+                    path.as_ptr(),
+                );
+            }
+            LoadEvent::Cleared => {}
+        }
+        obs::obs_leave_graphics();
+    }
+
+    pub(crate) fn texture(&self) -> *mut obs::gs_texture_t {
+        match self.image.as_ref() {
+            Some(image) => image.0.texture,
+            None => std::ptr::null_mut(),
+        }
+    }
+
+    pub(crate) fn size(&self) -> (u32, u32) {
+        match self.image.as_ref() {
+            Some(image) => (image.0.cx, image.0.cy),
+            None => (0, 0),
+        }
+    }
+
+    pub(crate) unsafe fn free(&mut self) {
+        self.loader.set_path(None);
+        self.image = None;
+    }
+}
+
+#[derive(Default)]
+struct BackgroundSourceInner {
+    name: Option<CString>,
+    // Resolved lazily: the source may be created after the filter while a scene collection loads.
+    weak: Option<*mut obs::obs_weak_source_t>,
+}
+
+// Live OBS source used as the background. The name is set from update (UI thread) while the
+// source is resolved and rendered on the graphics thread.
+#[derive(Default)]
+pub(crate) struct BackgroundSource {
+    inner: Mutex<BackgroundSourceInner>,
+}
+
+impl BackgroundSource {
+    pub(crate) unsafe fn set_name(&self, name: Option<CString>) {
+        let Ok(mut inner) = self.inner.lock() else {
+            return;
+        };
+        if inner.name == name {
+            return;
+        }
+        release_locked(&mut inner);
+        inner.name = name;
+    }
+
+    /// Strong reference to the background source (release with obs_source_release), or null.
+    pub(crate) unsafe fn get(&self) -> *mut obs::obs_source_t {
+        let Ok(mut inner) = self.inner.lock() else {
+            return std::ptr::null_mut();
+        };
+
+        if let Some(weak) = inner.weak {
+            let source = obs::obs_weak_source_get_source(weak);
+            if !source.is_null() {
+                return source;
+            }
+            // Source was removed; drop it and look the name up again.
+            release_locked(&mut inner);
+        }
+
+        let Some(name) = inner.name.as_ref() else {
+            return std::ptr::null_mut();
+        };
+        let source = obs::obs_get_source_by_name(name.as_ptr());
+        if !source.is_null() {
+            // Media and browser sources only play while shown.
+            obs::obs_source_inc_showing(source);
+            inner.weak = Some(obs::obs_source_get_weak_source(source));
+        }
+        source
+    }
+
+    pub(crate) unsafe fn release(&self) {
+        if let Ok(mut inner) = self.inner.lock() {
+            release_locked(&mut inner);
+            inner.name = None;
+        }
+    }
+}
+
+unsafe fn release_locked(inner: &mut BackgroundSourceInner) {
+    let Some(weak) = inner.weak.take() else {
+        return;
+    };
+
+    let source = obs::obs_weak_source_get_source(weak);
+    if !source.is_null() {
+        obs::obs_source_dec_showing(source);
+        obs::obs_source_release(source);
+    }
+    obs::obs_weak_source_release(weak);
+}
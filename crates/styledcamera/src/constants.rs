@@ -6,20 +6,62 @@ pub(crate) const MODULE_VERSION: &str = env!("CARGO_PKG_VERSION");
 
 pub(crate) static FILTER_ID: &[u8] = b"styled_camera_filter\0";
 pub(crate) static FILTER_DISPLAY_NAME: &[u8] = b"Styled Camera Filter\0";
+pub(crate) static MASK_SOURCE_ID: &[u8] = b"styled_camera_mask_source\0";
+pub(crate) static MASK_SOURCE_DISPLAY_NAME: &[u8] = b"Styled Camera Mask\0";
 
 pub(crate) static SETTING_BLUR_INTENSITY: &[u8] = b"blur_intensity\0";
+pub(crate) static SETTING_BLUR_QUALITY: &[u8] = b"blur_quality\0";
+pub(crate) static SETTING_BLUR_STYLE: &[u8] = b"blur_style\0";
+pub(crate) static SETTING_BOKEH_SHAPE: &[u8] = b"bokeh_shape\0";
+pub(crate) static SETTING_BOKEH_HIGHLIGHTS: &[u8] = b"bokeh_highlights\0";
+pub(crate) static SETTING_DEPTH_BLUR: &[u8] = b"depth_blur\0";
+pub(crate) static SETTING_DEPTH_FALLOFF: &[u8] = b"depth_falloff\0";
+pub(crate) static SETTING_BLUR_MASK_AWARE: &[u8] = b"blur_mask_aware\0";
 pub(crate) static SETTING_DEBUG_SHOW_MASK: &[u8] = b"debug_show_mask\0";
+pub(crate) static SETTING_DEBUG_MASK_VIEW: &[u8] = b"debug_mask_view\0";
 pub(crate) static SETTING_MASK_FPS: &[u8] = b"mask_fps\0";
+pub(crate) static SETTING_MASK_RATE_MODE: &[u8] = b"mask_rate_mode\0";
+pub(crate) static SETTING_MASK_CPU_BUDGET: &[u8] = b"mask_cpu_budget\0";
+pub(crate) static SETTING_MASK_LATENCY_CEILING_MS: &[u8] = b"mask_latency_ceiling_ms\0";
 pub(crate) static SETTING_MASK_TEMPORAL: &[u8] = b"mask_temporal_smoothing\0";
 pub(crate) static SETTING_MASK_THRESHOLD: &[u8] = b"mask_threshold\0";
 pub(crate) static SETTING_MASK_SOFTNESS: &[u8] = b"mask_softness\0";
 pub(crate) static SETTING_MASK_INVERT: &[u8] = b"mask_invert\0";
 pub(crate) static SETTING_SYNC_VIDEO_EXTRA_DELAY_MS: &[u8] = b"sync_video_extra_delay_ms\0";
+pub(crate) static SETTING_HISTORY_MAX_FRAMES: &[u8] = b"history_max_frames\0";
+pub(crate) static SETTING_AUDIO_COMPENSATE: &[u8] = b"audio_compensate\0";
+pub(crate) static SETTING_AUDIO_SYNC_SOURCE: &[u8] = b"audio_sync_source\0";
+// Not shown in the UI; see audio_sync::SyncOffsetState.
+pub(crate) static SETTING_AUDIO_SYNC_BASE_OFFSET: &[u8] = b"audio_sync_base_offset_ns\0";
+pub(crate) static SETTING_AUDIO_SYNC_WRITTEN_OFFSET: &[u8] = b"audio_sync_written_offset_ns\0";
+pub(crate) static SETTING_BG_TYPE: &[u8] = b"bg_type\0";
+pub(crate) static SETTING_BG_IMAGE_PATH: &[u8] = b"bg_image_path\0";
+pub(crate) static SETTING_BG_IMAGE_FIT: &[u8] = b"bg_image_fit\0";
+pub(crate) static SETTING_BG_SOURCE: &[u8] = b"bg_source\0";
+pub(crate) static SETTING_BG_COLOR_1: &[u8] = b"bg_color_1\0";
+pub(crate) static SETTING_BG_COLOR_2: &[u8] = b"bg_color_2\0";
+pub(crate) static SETTING_BG_COLOR_3: &[u8] = b"bg_color_3\0";
+pub(crate) static SETTING_BG_COLOR_4: &[u8] = b"bg_color_4\0";
+pub(crate) static SETTING_BG_GRADIENT_STOPS: &[u8] = b"bg_gradient_stops\0";
+pub(crate) static SETTING_BG_GRADIENT_ANGLE: &[u8] = b"bg_gradient_angle\0";
+pub(crate) static SETTING_BG_ANIM_SPEED: &[u8] = b"bg_anim_speed\0";
+pub(crate) static SETTING_BG_PLATE_FREEZE: &[u8] = b"bg_plate_freeze\0";
+pub(crate) static SETTING_BG_PRIVACY_STYLE: &[u8] = b"bg_privacy_style\0";
+pub(crate) static SETTING_BG_PRIVACY_BLOCK: &[u8] = b"bg_privacy_block\0";
 pub(crate) static SETTING_BG_DIM: &[u8] = b"bg_dim\0";
 pub(crate) static SETTING_BG_DESAT: &[u8] = b"bg_desat\0";
+pub(crate) static SETTING_EDGE_DECONTAM: &[u8] = b"edge_decontam\0";
+pub(crate) static SETTING_LIGHT_WRAP: &[u8] = b"light_wrap\0";
+pub(crate) static SETTING_LIGHT_WRAP_WIDTH: &[u8] = b"light_wrap_width\0";
 pub(crate) static SETTING_SHAPE_TYPE: &[u8] = b"shape_type\0";
 pub(crate) static SETTING_CORNER_RADIUS: &[u8] = b"corner_radius\0";
 pub(crate) static SETTING_FEATHER: &[u8] = b"feather\0";
+pub(crate) static SETTING_SHAPE_EXPONENT: &[u8] = b"shape_exponent\0";
+pub(crate) static SETTING_SHAPE_SIDES: &[u8] = b"shape_sides\0";
+pub(crate) static SETTING_SHAPE_INNER_RADIUS: &[u8] = b"shape_inner_radius\0";
+pub(crate) static SETTING_SHAPE_ROTATION: &[u8] = b"shape_rotation\0";
+pub(crate) static SETTING_SHAPE_IMAGE_PATH: &[u8] = b"shape_image_path\0";
+pub(crate) static SETTING_SHAPE_IMAGE_STATUS: &[u8] = b"shape_image_status\0";
 pub(crate) static SETTING_BORDER_THICKNESS: &[u8] = b"border_thickness\0";
 pub(crate) static SETTING_BORDER_COLOR: &[u8] = b"border_color\0";
 pub(crate) static SETTING_PADDING: &[u8] = b"padding\0";
@@ -32,20 +74,97 @@ pub(crate) static SETTING_SHADOW_BLUR: &[u8] = b"shadow_blur\0";
 pub(crate) static SETTING_SHADOW_OFFSET_X: &[u8] = b"shadow_offset_x\0";
 pub(crate) static SETTING_SHADOW_OFFSET_Y: &[u8] = b"shadow_offset_y\0";
 pub(crate) static SETTING_SHADOW_COLOR: &[u8] = b"shadow_color\0";
+pub(crate) static SETTING_SIL_OUTLINE_WIDTH: &[u8] = b"silhouette_outline_width\0";
+pub(crate) static SETTING_SIL_OUTLINE_COLOR: &[u8] = b"silhouette_outline_color\0";
+pub(crate) static SETTING_SIL_SOFTNESS: &[u8] = b"silhouette_softness\0";
+pub(crate) static SETTING_SIL_GLOW_SIZE: &[u8] = b"silhouette_glow_size\0";
+pub(crate) static SETTING_SIL_GLOW_COLOR: &[u8] = b"silhouette_glow_color\0";
+pub(crate) static SETTING_SIL_SHADOW_OPACITY: &[u8] = b"silhouette_shadow_opacity\0";
+pub(crate) static SETTING_SIL_SHADOW_BLUR: &[u8] = b"silhouette_shadow_blur\0";
+pub(crate) static SETTING_SIL_SHADOW_OFFSET_X: &[u8] = b"silhouette_shadow_offset_x\0";
+pub(crate) static SETTING_SIL_SHADOW_OFFSET_Y: &[u8] = b"silhouette_shadow_offset_y\0";
+pub(crate) static SETTING_SIL_SHADOW_COLOR: &[u8] = b"silhouette_shadow_color\0";
+pub(crate) static SETTING_FG_EXPOSURE: &[u8] = b"fg_exposure\0";
+pub(crate) static SETTING_FG_CONTRAST: &[u8] = b"fg_contrast\0";
+pub(crate) static SETTING_FG_SATURATION: &[u8] = b"fg_saturation\0";
+pub(crate) static SETTING_FG_TEMPERATURE: &[u8] = b"fg_temperature\0";
+pub(crate) static SETTING_FG_TINT: &[u8] = b"fg_tint\0";
+pub(crate) static SETTING_FG_VIGNETTE: &[u8] = b"fg_vignette\0";
+pub(crate) static SETTING_BG_EXPOSURE: &[u8] = b"bg_exposure\0";
+pub(crate) static SETTING_BG_CONTRAST: &[u8] = b"bg_contrast\0";
+pub(crate) static SETTING_BG_SATURATION: &[u8] = b"bg_saturation\0";
+pub(crate) static SETTING_BG_TEMPERATURE: &[u8] = b"bg_temperature\0";
+pub(crate) static SETTING_BG_TINT: &[u8] = b"bg_tint\0";
+pub(crate) static SETTING_BG_VIGNETTE: &[u8] = b"bg_vignette\0";
+pub(crate) static SETTING_LUT_PATH: &[u8] = b"lut_path\0";
+pub(crate) static SETTING_LUT_TARGET: &[u8] = b"lut_target\0";
+pub(crate) static SETTING_LUT_AMOUNT: &[u8] = b"lut_amount\0";
+pub(crate) static SETTING_MASK_SOURCE_FILTER: &[u8] = b"mask_source_filter\0";
+pub(crate) static SETTING_SEG_STATUS: &[u8] = b"seg_status\0";
+pub(crate) static SETTING_SEG_STATUS_REFRESH: &[u8] = b"seg_status_refresh\0";
 
 pub(crate) static PROP_BLUR_INTENSITY: &[u8] = b"Blur intensity\0";
+pub(crate) static PROP_BLUR_QUALITY: &[u8] = b"Blur quality\0";
+pub(crate) static PROP_BLUR_STYLE: &[u8] = b"Blur style\0";
+pub(crate) static PROP_BOKEH_SHAPE: &[u8] = b"Aperture shape\0";
+pub(crate) static PROP_BOKEH_HIGHLIGHTS: &[u8] = b"Highlight bloom\0";
+pub(crate) static PROP_BLUR_MAX: &[u8] = b"Max blur\0";
+pub(crate) static PROP_DEPTH_BLUR: &[u8] = b"Depth-graded blur\0";
+pub(crate) static PROP_DEPTH_FALLOFF: &[u8] = b"Focus falloff\0";
+pub(crate) static PROP_BLUR_MASK_AWARE: &[u8] = b"Keep person out of the blur\0";
 pub(crate) static PROP_DEBUG_SHOW_MASK: &[u8] = b"Debug: show mask\0";
+pub(crate) static PROP_DEBUG_MASK_VIEW: &[u8] = b"Mask view\0";
 pub(crate) static PROP_MASK_FPS: &[u8] = b"Mask FPS\0";
+pub(crate) static PROP_MASK_FPS_LONG: &[u8] = b"In Auto modes this is the upper limit for the mask rate.\0";
+pub(crate) static PROP_MASK_RATE_MODE: &[u8] = b"Mask rate\0";
+pub(crate) static PROP_MASK_CPU_BUDGET: &[u8] = b"CPU budget (%)\0";
+pub(crate) static PROP_MASK_LATENCY_CEILING_MS: &[u8] = b"Latency ceiling (ms)\0";
 pub(crate) static PROP_MASK_TEMPORAL: &[u8] = b"Mask temporal smoothing\0";
 pub(crate) static PROP_MASK_THRESHOLD: &[u8] = b"Mask threshold\0";
 pub(crate) static PROP_MASK_SOFTNESS: &[u8] = b"Mask softness\0";
 pub(crate) static PROP_MASK_INVERT: &[u8] = b"Invert mask\0";
 pub(crate) static PROP_SYNC_VIDEO_EXTRA_DELAY_MS: &[u8] = b"Extra delay (ms)\0";
+pub(crate) static PROP_HISTORY_MAX_FRAMES: &[u8] = b"Max history frames\0";
+pub(crate) static PROP_HISTORY_MAX_FRAMES_LONG: &[u8] =
+    b"Upper limit for delayed video frames kept in VRAM (one RGBA copy of the source each). \
+Large sources are held to fewer frames so the history stays within 256 MiB.\0";
+pub(crate) static PROP_AUDIO_COMPENSATE: &[u8] = b"Compensate audio delay\0";
+pub(crate) static PROP_AUDIO_COMPENSATE_LONG: &[u8] =
+    b"Adds the filter's video delay to the audio source's sync offset. The source's own offset is kept and restored when this is turned off.\0";
+pub(crate) static PROP_AUDIO_SYNC_SOURCE: &[u8] = b"Audio source\0";
+pub(crate) static PROP_AUDIO_SYNC_SOURCE_PARENT: &[u8] = b"(filtered source)\0";
+pub(crate) static PROP_BG_TYPE: &[u8] = b"Background type\0";
+pub(crate) static PROP_BG_IMAGE_PATH: &[u8] = b"Background image\0";
+pub(crate) static PROP_BG_IMAGE_PATH_FILTER: &[u8] =
+    b"Image files (*.png *.jpg *.jpeg *.bmp *.tga *.gif *.webp);;All files (*.*)\0";
+pub(crate) static PROP_BG_IMAGE_FIT: &[u8] = b"Image fit\0";
+pub(crate) static PROP_BG_SOURCE: &[u8] = b"Background source\0";
+pub(crate) static PROP_BG_COLOR_1: &[u8] = b"Color 1\0";
+pub(crate) static PROP_BG_COLOR_2: &[u8] = b"Color 2\0";
+pub(crate) static PROP_BG_COLOR_3: &[u8] = b"Color 3\0";
+pub(crate) static PROP_BG_COLOR_4: &[u8] = b"Color 4\0";
+pub(crate) static PROP_BG_GRADIENT_STOPS: &[u8] = b"Color stops\0";
+pub(crate) static PROP_BG_GRADIENT_ANGLE: &[u8] = b"Angle\0";
+pub(crate) static PROP_BG_ANIM_SPEED: &[u8] = b"Animation speed\0";
+pub(crate) static PROP_BG_PLATE_FREEZE: &[u8] = b"Freeze background\0";
+pub(crate) static PROP_BG_PRIVACY_STYLE: &[u8] = b"Privacy style\0";
+pub(crate) static PROP_BG_PRIVACY_BLOCK: &[u8] = b"Block size\0";
 pub(crate) static PROP_BG_DIM: &[u8] = b"Background dim\0";
 pub(crate) static PROP_BG_DESAT: &[u8] = b"Background desaturate\0";
+pub(crate) static PROP_EDGE_DECONTAM: &[u8] = b"Edge color cleanup\0";
+pub(crate) static PROP_LIGHT_WRAP: &[u8] = b"Light wrap\0";
+pub(crate) static PROP_LIGHT_WRAP_WIDTH: &[u8] = b"Light wrap width\0";
 pub(crate) static PROP_SHAPE_TYPE: &[u8] = b"Shape\0";
 pub(crate) static PROP_CORNER_RADIUS: &[u8] = b"Corner radius\0";
 pub(crate) static PROP_FEATHER: &[u8] = b"Feather\0";
+pub(crate) static PROP_SHAPE_EXPONENT: &[u8] = b"Squircle exponent\0";
+pub(crate) static PROP_SHAPE_SIDES: &[u8] = b"Sides\0";
+pub(crate) static PROP_SHAPE_POINTS: &[u8] = b"Points\0";
+pub(crate) static PROP_SHAPE_INNER_RADIUS: &[u8] = b"Inner radius\0";
+pub(crate) static PROP_SHAPE_ROTATION: &[u8] = b"Rotation\0";
+pub(crate) static PROP_SHAPE_IMAGE_PATH: &[u8] = b"Shape image\0";
+pub(crate) static PROP_SHAPE_IMAGE_PATH_FILTER: &[u8] =
+    b"Images with transparency (*.png *.webp *.tga *.gif);;All files (*.*)\0";
 pub(crate) static PROP_BORDER_THICKNESS: &[u8] = b"Border thickness\0";
 pub(crate) static PROP_BORDER_COLOR: &[u8] = b"Border color\0";
 pub(crate) static PROP_PADDING: &[u8] = b"Padding\0";
@@ -58,30 +177,80 @@ pub(crate) static PROP_SHADOW_BLUR: &[u8] = b"Shadow blur\0";
 pub(crate) static PROP_SHADOW_OFFSET_X: &[u8] = b"Shadow offset X\0";
 pub(crate) static PROP_SHADOW_OFFSET_Y: &[u8] = b"Shadow offset Y\0";
 pub(crate) static PROP_SHADOW_COLOR: &[u8] = b"Shadow color\0";
+pub(crate) static PROP_SIL_OUTLINE_WIDTH: &[u8] = b"Outline width\0";
+pub(crate) static PROP_SIL_OUTLINE_COLOR: &[u8] = b"Outline color\0";
+pub(crate) static PROP_SIL_SOFTNESS: &[u8] = b"Outline softness\0";
+pub(crate) static PROP_SIL_GLOW_SIZE: &[u8] = b"Glow size\0";
+pub(crate) static PROP_SIL_GLOW_COLOR: &[u8] = b"Glow color\0";
+pub(crate) static PROP_SIL_SHADOW_OPACITY: &[u8] = b"Silhouette shadow opacity\0";
+pub(crate) static PROP_SIL_SHADOW_BLUR: &[u8] = b"Silhouette shadow blur\0";
+pub(crate) static PROP_SIL_SHADOW_OFFSET_X: &[u8] = b"Silhouette shadow offset X\0";
+pub(crate) static PROP_SIL_SHADOW_OFFSET_Y: &[u8] = b"Silhouette shadow offset Y\0";
+pub(crate) static PROP_SIL_SHADOW_COLOR: &[u8] = b"Silhouette shadow color\0";
+pub(crate) static PROP_GRADE_EXPOSURE: &[u8] = b"Exposure\0";
+pub(crate) static PROP_GRADE_CONTRAST: &[u8] = b"Contrast\0";
+pub(crate) static PROP_GRADE_SATURATION: &[u8] = b"Saturation\0";
+pub(crate) static PROP_GRADE_TEMPERATURE: &[u8] = b"Temperature\0";
+pub(crate) static PROP_GRADE_TINT: &[u8] = b"Tint\0";
+pub(crate) static PROP_GRADE_VIGNETTE: &[u8] = b"Vignette\0";
+pub(crate) static PROP_LUT_PATH: &[u8] = b"LUT file\0";
+pub(crate) static PROP_LUT_PATH_FILTER: &[u8] = b"Cube LUTs (*.cube);;All files (*.*)\0";
+pub(crate) static PROP_LUT_TARGET: &[u8] = b"Apply to\0";
+pub(crate) static PROP_LUT_AMOUNT: &[u8] = b"Amount\0";
+pub(crate) static PROP_MASK_SOURCE_FILTER: &[u8] = b"Styled Camera filter\0";
+pub(crate) static PROP_MASK_SOURCE_FILTER_NONE: &[u8] = b"(none)\0";
+pub(crate) static PROP_SEG_STATUS_REFRESH: &[u8] = b"Refresh\0";
 
 pub(crate) static GROUP_SEGMENTATION: &[u8] = b"group_segmentation\0";
 pub(crate) static GROUP_BACKGROUND: &[u8] = b"group_background\0";
+pub(crate) static GROUP_SILHOUETTE: &[u8] = b"group_silhouette\0";
+pub(crate) static GROUP_FG_GRADE: &[u8] = b"group_fg_grade\0";
+pub(crate) static GROUP_BG_GRADE: &[u8] = b"group_bg_grade\0";
+pub(crate) static GROUP_LUT: &[u8] = b"group_lut\0";
 pub(crate) static GROUP_SHAPE: &[u8] = b"group_shape\0";
 pub(crate) static GROUP_BORDER: &[u8] = b"group_border\0";
 pub(crate) static GROUP_SHADOW: &[u8] = b"group_shadow\0";
 pub(crate) static GROUP_DEBUG: &[u8] = b"group_debug\0";
+pub(crate) static GROUP_STATUS: &[u8] = b"group_status\0";
+pub(crate) static GROUP_AUDIO_SYNC: &[u8] = b"group_audio_sync\0";
 
 pub(crate) static GROUP_LABEL_SEGMENTATION: &[u8] = b"Segmentation\0";
 pub(crate) static GROUP_LABEL_BACKGROUND: &[u8] = b"Background\0";
+pub(crate) static GROUP_LABEL_SILHOUETTE: &[u8] = b"Silhouette outline\0";
+pub(crate) static GROUP_LABEL_FG_GRADE: &[u8] = b"Foreground grading\0";
+pub(crate) static GROUP_LABEL_BG_GRADE: &[u8] = b"Background grading\0";
+pub(crate) static GROUP_LABEL_LUT: &[u8] = b"LUT\0";
 pub(crate) static GROUP_LABEL_SHAPE: &[u8] = b"Shape\0";
 pub(crate) static GROUP_LABEL_BORDER: &[u8] = b"Border\0";
 pub(crate) static GROUP_LABEL_SHADOW: &[u8] = b"Shadow\0";
 pub(crate) static GROUP_LABEL_DEBUG: &[u8] = b"Debug\0";
+pub(crate) static GROUP_LABEL_STATUS: &[u8] = b"Status\0";
+pub(crate) static GROUP_LABEL_AUDIO_SYNC: &[u8] = b"Audio sync\0";
 
+pub(crate) static EFFECT_BACKGROUND_FIT: &[u8] = b"background_fit.effect\0";
+pub(crate) static EFFECT_BACKGROUND_GENERATE: &[u8] = b"background_generate.effect\0";
+pub(crate) static EFFECT_BACKGROUND_PLATE: &[u8] = b"background_plate.effect\0";
+pub(crate) static EFFECT_BACKGROUND_PRIVACY: &[u8] = b"background_privacy.effect\0";
 pub(crate) static EFFECT_BLUR_DOWNSAMPLE: &[u8] = b"blur_downsample.effect\0";
 pub(crate) static EFFECT_BLUR_PASS: &[u8] = b"blur_pass.effect\0";
+pub(crate) static EFFECT_BOKEH_BLUR: &[u8] = b"bokeh_blur.effect\0";
 pub(crate) static EFFECT_COMPOSITE: &[u8] = b"styled_composite.effect\0";
+pub(crate) static EFFECT_MASK_DEBUG: &[u8] = b"mask_debug.effect\0";
 pub(crate) static EFFECT_SHAPE_STYLE: &[u8] = b"shape_style.effect\0";
 
+pub(crate) static TECH_BACKGROUND_FIT: &[u8] = b"Fit\0";
+pub(crate) static TECH_BACKGROUND_GENERATE: &[u8] = b"Generate\0";
+pub(crate) static TECH_PLATE_ACCUMULATE: &[u8] = b"Accumulate\0";
+pub(crate) static TECH_PLATE_RESOLVE: &[u8] = b"Resolve\0";
+pub(crate) static TECH_PRIVACY: &[u8] = b"Privacy\0";
+pub(crate) static TECH_MASK_DEBUG: &[u8] = b"Debug\0";
 pub(crate) static TECH_DOWNSAMPLE: &[u8] = b"Downsample\0";
-pub(crate) static TECH_BLUR_H: &[u8] = b"BlurH\0";
-pub(crate) static TECH_BLUR_V: &[u8] = b"BlurV\0";
+pub(crate) static TECH_DOWNSAMPLE_MASKED: &[u8] = b"DownsampleMasked\0";
+pub(crate) static TECH_DUAL_DOWN: &[u8] = b"DualDown\0";
+pub(crate) static TECH_DUAL_UP: &[u8] = b"DualUp\0";
+pub(crate) static TECH_BOKEH: &[u8] = b"Bokeh\0";
 pub(crate) static TECH_COMPOSITE: &[u8] = b"Composite\0";
+pub(crate) static TECH_MASK_MATTE: &[u8] = b"MaskMatte\0";
 pub(crate) static TECH_SHAPE_STYLE: &[u8] = b"ShapeStyle\0";
 
 pub(crate) static MODEL_FILE: &[u8] = b"models/selfie_segmentation.onnx\0";
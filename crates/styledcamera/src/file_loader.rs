@@ -0,0 +1,142 @@
+use std::ffi::{CStr, CString};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+pub(crate) enum LoadEvent<T> {
+    Loaded(T),
+    Failed(String),
+    // The path was cleared; the resource should be released.
+    Cleared,
+}
+
+struct LoaderInner<T> {
+    path: Option<CString>,
+    // Modification time of the file when the current load started.
+    modified: Option<SystemTime>,
+    // Bumped for every load; results of superseded loads are dropped.
+    generation: u64,
+    event: Option<LoadEvent<T>>,
+    // Why the latest load failed, kept (unlike the event) for the properties UI.
+    error: Option<String>,
+}
+
+// Loads a file-backed resource (image, LUT) on a worker thread and reloads it when the file
+// changes on disk, so neither picking a file nor editing it stalls the update or render thread.
+//
+// The path is set from update (UI thread); events are taken on the graphics thread, where the
+// owner uploads the result.
+pub(crate) struct FileLoader<T> {
+    inner: Arc<Mutex<LoaderInner<T>>>,
+    load: fn(&CStr) -> Result<T, String>,
+}
+
+impl<T: Send + 'static> FileLoader<T> {
+    pub(crate) fn new(load: fn(&CStr) -> Result<T, String>) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(LoaderInner {
+                path: None,
+                modified: None,
+                generation: 0,
+                event: None,
+                error: None,
+            })),
+            load,
+        }
+    }
+
+    // Starts loading `path` unless it is already loaded (or loading) and unchanged on disk.
+    pub(crate) fn set_path(&self, path: Option<CString>) {
+        let modified = path.as_deref().and_then(modified_time);
+        let stale = {
+            let Ok(mut inner) = self.inner.lock() else {
+                return;
+            };
+            if inner.path == path && inner.modified == modified {
+                return;
+            }
+            inner.path = path;
+            self.start_locked(&mut inner, modified)
+        };
+        // Dropped outside the lock: releasing a decoded image enters the graphics context.
+        drop(stale);
+    }
+
+    // Reloads the file if it changed on disk since the last load.
+    pub(crate) fn reload_if_modified(&self) {
+        let stale = {
+            let Ok(mut inner) = self.inner.lock() else {
+                return;
+            };
+            let Some(path) = inner.path.as_deref() else {
+                return;
+            };
+            let modified = modified_time(path);
+            if modified == inner.modified {
+                return;
+            }
+            self.start_locked(&mut inner, modified)
+        };
+        drop(stale);
+    }
+
+    // Returns the event it replaced, to be dropped once the lock is released.
+    fn start_locked(
+        &self,
+        inner: &mut LoaderInner<T>,
+        modified: Option<SystemTime>,
+    ) -> Option<LoadEvent<T>> {
+        inner.generation += 1;
+        inner.modified = modified;
+        inner.error = None;
+        let Some(path) = inner.path.clone() else {
+            return inner.event.replace(LoadEvent::Cleared);
+        };
+        let stale = inner.event.take();
+
+        let generation = inner.generation;
+        let shared = Arc::clone(&self.inner);
+        let load = self.load;
+        let spawned = std::thread::Builder::new()
+            .name("styledcamera-file-load".to_owned())
+            .spawn(move || {
+                let event = match load(&path) {
+                    Ok(value) => LoadEvent::Loaded(value),
+                    Err(err) => LoadEvent::Failed(err),
+                };
+                let superseded = match shared.lock() {
+                    Ok(mut inner) if inner.generation == generation => {
+                        if let LoadEvent::Failed(err) = &event {
+                            inner.error = Some(err.clone());
+                        }
+                        inner.event.replace(event)
+                    }
+                    _ => Some(event),
+                };
+                drop(superseded);
+            });
+        if let Err(err) = spawned {
+            inner.error = Some(err.to_string());
+            inner.event = Some(LoadEvent::Failed(err.to_string()));
+        }
+        stale
+    }
+
+    // The outcome of the latest load, once.
+    pub(crate) fn take_event(&self) -> Option<LoadEvent<T>> {
+        self.inner.lock().ok()?.event.take()
+    }
+
+    pub(crate) fn path(&self) -> Option<CString> {
+        self.inner.lock().ok()?.path.clone()
+    }
+
+    // Why the current file could not be loaded; None while it loads or once it loaded.
+    pub(crate) fn error(&self) -> Option<String> {
+        self.inner.lock().ok()?.error.clone()
+    }
+}
+
+fn modified_time(path: &CStr) -> Option<SystemTime> {
+    let path = path.to_str().ok()?;
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
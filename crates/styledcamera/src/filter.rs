@@ -1,24 +1,48 @@
-use std::ffi::c_void;
+use std::ffi::{c_void, CStr, CString};
 use std::os::raw::c_char;
-use std::sync::mpsc;
+use std::sync::{mpsc, Arc, Mutex};
 use std::time::{Duration, Instant};
 
 use obs_sys as obs;
-use styledcamera_core::timing::update_mask_latency_ema_ms;
+use styledcamera_core::background::{
+    advance_anim_time, background_fit_transform, plate_blend, privacy_block_uv, BackgroundFit,
+};
+use styledcamera_core::blur::{blur_pyramid_plan, BlurQuality, BokehShape};
+use styledcamera_core::color::obs_abgr_to_rgba_vec4;
+use styledcamera_core::history::{
+    history_depth_budget, required_history_depth, FrameStamp, FrameTarget,
+    HISTORY_VRAM_BUDGET_BYTES,
+};
+use styledcamera_core::rate::MaskRateGovernor;
+use styledcamera_core::timing::{update_mask_latency_ema_ms, update_rate_ema_hz};
 
+use crate::audio_sync::AudioSync;
+use crate::background::{BackgroundImage, BackgroundSource};
 use crate::constants::*;
 use crate::frame_history::FrameHistory;
 use crate::graphics::{
-    draw_shape_to_screen, render_effect_to_texrender, render_source_to_texrender, set_float_param,
-    set_vec2_param, GraphicsState,
+    clear_texrender, draw_shape_to_screen, render_effect_to_texrender,
+    render_source_scaled_to_texrender, render_source_to_texrender, set_float_param, set_vec2_param,
+    set_vec3_param, set_vec4_param, GraphicsState, SegReadbackSlot, SEG_READBACK_LAG,
 };
+use crate::lut::LutTexture;
 use crate::perf::RenderPerf;
-use crate::segmentation::{SegInput, SegmentationState, SegOutput};
-use crate::settings::{self, FilterSettings};
+use crate::segmentation::{
+    DistanceFieldRequest, RenderStats, SegInput, SegOutput, SegmentationState,
+    SILHOUETTE_FIELD_RANGE,
+};
+use crate::settings::{self, BackgroundType, BlurStyle, DebugMaskView, FilterSettings};
+use crate::shape_image::ShapeImage;
 use crate::util::cstr;
 
 const SEG_SIZE: u32 = 256;
 const DOWNSCALE_DIV: u32 = 2;
+// Lens blur aperture radius at full intensity, in downsampled texels.
+const MAX_BOKEH_RADIUS: f32 = 24.0;
+// How often the background image, LUT and shape image files are checked for edits.
+const FILE_CHECK_INTERVAL_S: f32 = 1.0;
+// Edge decontamination looks this far (in pixels of a 1080p frame) for the person's interior.
+const DECONTAM_RADIUS_1080P: f32 = 6.0;
 
 #[repr(C)]
 struct StyledCameraFilter {
@@ -26,12 +50,35 @@ struct StyledCameraFilter {
     settings: FilterSettings,
 
     mask_latency_ema_ms: f32,
+    mask_output_fps_ema: f32,
     last_mask_request: Option<Instant>,
+    last_mask_output: Option<Instant>,
+    mask_rate: MaskRateGovernor,
+    // How many frames behind the newest history entry the last mask's source frame was.
+    mask_frame_lag: Option<u64>,
+    // Source frame of the newest mask until the clean plate has learned from it.
+    plate_pending_frame: Option<u64>,
 
     perf: RenderPerf,
     graphics: GraphicsState,
     segmentation: SegmentationState,
     frame_history: FrameHistory,
+    audio_sync: AudioSync,
+    bg_image: BackgroundImage,
+    bg_source: BackgroundSource,
+    lut: LutTexture,
+    shape_image: ShapeImage,
+    // Set while the background source renders; guards against it containing this filter.
+    rendering_bg_source: bool,
+    // Animation clock for generated backgrounds, advanced in video_tick.
+    bg_time: f64,
+    // Time since the image and LUT files were last checked for changes.
+    file_check_elapsed: f32,
+    // Target size at the last render.
+    output_width: u32,
+    output_height: u32,
+    // Shared with the mask source through the registry (see publish_mask).
+    published_mask: Arc<Mutex<PublishedMask>>,
 }
 
 impl StyledCameraFilter {
@@ -41,22 +88,180 @@ impl StyledCameraFilter {
             settings: FilterSettings::default(),
 
             mask_latency_ema_ms: 0.0,
+            mask_output_fps_ema: 0.0,
             last_mask_request: None,
+            last_mask_output: None,
+            mask_rate: MaskRateGovernor::default(),
+            mask_frame_lag: None,
+            plate_pending_frame: None,
 
             perf: RenderPerf::new(),
             graphics: GraphicsState::default(),
             segmentation: SegmentationState::default(),
             frame_history: FrameHistory::default(),
+            audio_sync: AudioSync::default(),
+            bg_image: BackgroundImage::default(),
+            bg_source: BackgroundSource::default(),
+            lut: LutTexture::default(),
+            shape_image: ShapeImage::default(),
+            rendering_bg_source: false,
+            bg_time: 0.0,
+            file_check_elapsed: 0.0,
+            output_width: 0,
+            output_height: 0,
+            published_mask: Arc::new(Mutex::new(PublishedMask::default())),
         }
     }
 }
 
+// What a filter shares with the mask source. The graphics objects belong to the filter's
+// GraphicsState: they are published and cleared in graphics context, and only used there.
+#[derive(Clone, Copy)]
+pub(crate) struct PublishedMask {
+    pub width: u32,
+    pub height: u32,
+    mask_tex: *mut obs::gs_texture_t,
+    effect: *mut obs::gs_effect_t,
+    mask_image: *mut obs::gs_eparam_t,
+    mask_threshold: *mut obs::gs_eparam_t,
+    mask_softness: *mut obs::gs_eparam_t,
+    mask_invert: *mut obs::gs_eparam_t,
+    threshold: f32,
+    softness: f32,
+    invert: bool,
+}
+
+impl Default for PublishedMask {
+    fn default() -> Self {
+        Self {
+            width: 0,
+            height: 0,
+            mask_tex: std::ptr::null_mut(),
+            effect: std::ptr::null_mut(),
+            mask_image: std::ptr::null_mut(),
+            mask_threshold: std::ptr::null_mut(),
+            mask_softness: std::ptr::null_mut(),
+            mask_invert: std::ptr::null_mut(),
+            threshold: 0.0,
+            softness: 0.0,
+            invert: false,
+        }
+    }
+}
+
+// The pointers are only dereferenced by libobs in graphics context (see above).
+unsafe impl Send for PublishedMask {}
+
+// Live filter instances, so the mask source can find a filter's published mask.
+struct RegisteredFilter {
+    // Stable across renames and saved with the mask source's settings.
+    uuid: CString,
+    // For the "Parent / Filter" label only; the filter itself is never reached through it.
+    weak: *mut obs::obs_weak_source_t,
+    mask: Arc<Mutex<PublishedMask>>,
+}
+
+// Weak source references are thread-safe; entries are added in create and removed in destroy.
+unsafe impl Send for RegisteredFilter {}
+
+static FILTER_REGISTRY: Mutex<Vec<RegisteredFilter>> = Mutex::new(Vec::new());
+
+// "Parent / Filter" as shown in the mask source's filter list.
+unsafe fn filter_label(weak: *mut obs::obs_weak_source_t) -> Option<CString> {
+    let source = obs::obs_weak_source_get_source(weak);
+    if source.is_null() {
+        return None;
+    }
+    let parent = obs::obs_filter_get_parent(source);
+    let filter_name = obs::obs_source_get_name(source);
+    let parent_name = if parent.is_null() {
+        std::ptr::null()
+    } else {
+        obs::obs_source_get_name(parent)
+    };
+    let label = if parent_name.is_null() || filter_name.is_null() {
+        None
+    } else {
+        let label = format!(
+            "{} / {}",
+            CStr::from_ptr(parent_name).to_string_lossy(),
+            CStr::from_ptr(filter_name).to_string_lossy()
+        );
+        CString::new(label).ok()
+    };
+    obs::obs_source_release(source);
+    label
+}
+
+// (uuid, label) of every live filter.
+pub(crate) unsafe fn registered_filters() -> Vec<(CString, CString)> {
+    let Ok(registry) = FILTER_REGISTRY.lock() else {
+        return Vec::new();
+    };
+    registry
+        .iter()
+        .filter_map(|e| Some((e.uuid.clone(), filter_label(e.weak)?)))
+        .collect()
+}
+
+pub(crate) fn published_mask(uuid: &CStr) -> Option<Arc<Mutex<PublishedMask>>> {
+    let registry = FILTER_REGISTRY.lock().ok()?;
+    let entry = registry.iter().find(|e| e.uuid.as_c_str() == uuid)?;
+    Some(entry.mask.clone())
+}
+
+// Must be called while in graphics context, after the mask texture was last replaced.
+unsafe fn publish_mask(filter: &StyledCameraFilter, cx: u32, cy: u32) {
+    let gfx = &filter.graphics;
+    let settings = filter.settings;
+    let has_mask = filter.segmentation.inbox.is_some();
+    if let Ok(mut published) = filter.published_mask.lock() {
+        *published = PublishedMask {
+            width: cx,
+            height: cy,
+            mask_tex: if has_mask {
+                gfx.mask_tex
+            } else {
+                std::ptr::null_mut()
+            },
+            effect: gfx.effect_composite,
+            mask_image: gfx.composite_mask_image,
+            mask_threshold: gfx.composite_mask_threshold,
+            mask_softness: gfx.composite_mask_softness,
+            mask_invert: gfx.composite_mask_invert,
+            threshold: settings.mask_threshold.clamp(0.0, 1.0),
+            softness: settings.mask_softness.clamp(0.0, 1.0),
+            invert: settings.mask_invert,
+        };
+    }
+}
+
+// Until the filter renders again the mask source draws nothing.
+fn clear_published_mask(filter: &StyledCameraFilter) {
+    if let Ok(mut published) = filter.published_mask.lock() {
+        *published = PublishedMask::default();
+    }
+}
+
+// Must be called while in graphics context, with the published mask locked.
+// Draws the filter's refined mask as a grayscale matte; false if it has no mask yet.
+pub(crate) unsafe fn draw_published_mask(mask: &PublishedMask, cx: u32, cy: u32) -> bool {
+    if mask.effect.is_null() || mask.mask_tex.is_null() {
+        return false;
+    }
+
+    obs::gs_effect_set_texture(mask.mask_image, mask.mask_tex);
+    set_float_param(mask.mask_threshold, mask.threshold);
+    set_float_param(mask.mask_softness, mask.softness);
+    set_float_param(mask.mask_invert, if mask.invert { 1.0 } else { 0.0 });
+    while obs::gs_effect_loop(mask.effect, cstr(TECH_MASK_MATTE)) {
+        obs::gs_draw_sprite(mask.mask_tex, 0, cx, cy);
+    }
+    true
+}
+
 // Must be called while in graphics context.
-unsafe fn apply_segmentation_output(
-    gfx: &mut GraphicsState,
-    mask_latency_ema_ms: &mut f32,
-    out: SegOutput,
-) {
+unsafe fn apply_segmentation_output(gfx: &mut GraphicsState, out: &SegOutput) {
     if out.width == 0 || out.height == 0 {
         return;
     }
@@ -85,26 +290,100 @@ unsafe fn apply_segmentation_output(
         obs::gs_texture_set_image(gfx.mask_tex, out.mask.as_ptr(), out.width, false);
     }
 
+    if let Some(distance) = out.distance.as_ref() {
+        upload_field_texture(
+            &mut gfx.depth_tex,
+            &mut gfx.depth_size,
+            distance,
+            out.width,
+            out.height,
+        );
+    }
+    if let Some(silhouette) = out.silhouette.as_ref() {
+        upload_field_texture(
+            &mut gfx.silhouette_tex,
+            &mut gfx.silhouette_size,
+            silhouette,
+            out.width,
+            out.height,
+        );
+    }
+}
+
+// Uploads a worker-computed field next to the mask, (re)creating the texture on size changes.
+// Must be called while in graphics context.
+unsafe fn upload_field_texture(
+    tex: &mut *mut obs::gs_texture_t,
+    size: &mut (u32, u32),
+    data: &[u8],
+    width: u32,
+    height: u32,
+) {
+    if data.len() != (width * height) as usize {
+        return;
+    }
+    if !tex.is_null() && *size != (width, height) {
+        obs::gs_texture_destroy(*tex);
+        *tex = std::ptr::null_mut();
+    }
+    if tex.is_null() {
+        let mut data_ptrs = [data.as_ptr()];
+        *tex = obs::gs_texture_create(
+            width,
+            height,
+            obs::gs_color_format_GS_R8,
+            1,
+            data_ptrs.as_mut_ptr(),
+            obs::GS_DYNAMIC,
+        );
+        *size = (width, height);
+    } else {
+        obs::gs_texture_set_image(*tex, data.as_ptr(), width, false);
+    }
+}
+
+fn record_mask_timing(filter: &mut StyledCameraFilter, out: &SegOutput) {
+    // Outputs are consumed before the current frame is captured, so measure the lag against the
+    // upcoming id: that frame then composites exactly the mask's source frame.
+    let upcoming_id = filter.frame_history.upcoming_frame_id();
+    filter.mask_frame_lag = Some(upcoming_id.saturating_sub(out.frame_id));
+
     let measured_ms = out.capture_time.elapsed().as_secs_f32() * 1000.0;
-    update_mask_latency_ema_ms(mask_latency_ema_ms, measured_ms);
+    update_mask_latency_ema_ms(&mut filter.mask_latency_ema_ms, measured_ms);
+
+    let now = Instant::now();
+    if let Some(prev) = filter.last_mask_output.replace(now) {
+        update_rate_ema_hz(
+            &mut filter.mask_output_fps_ema,
+            now.duration_since(prev).as_secs_f32(),
+        );
+    }
+
+    if let Some(target) = filter.settings.mask_rate_target() {
+        filter.mask_rate.update(
+            target,
+            out.infer_ms,
+            filter.mask_latency_ema_ms,
+            filter.settings.mask_fps as f32,
+        );
+    }
 }
 
 // Must be called while in graphics context.
 unsafe fn consume_segmentation_outputs(filter: &mut StyledCameraFilter) {
     let mut disconnected = false;
 
-    let gfx = &mut filter.graphics;
-    let mask_latency_ema_ms = &mut filter.mask_latency_ema_ms;
-
-    if let Some(rx) = filter.segmentation.rx.as_ref() {
-        loop {
-            match rx.try_recv() {
-                Ok(out) => apply_segmentation_output(gfx, mask_latency_ema_ms, out),
-                Err(mpsc::TryRecvError::Empty) => break,
-                Err(mpsc::TryRecvError::Disconnected) => {
-                    disconnected = true;
-                    break;
-                }
+    while let Some(rx) = filter.segmentation.rx.as_ref() {
+        match rx.try_recv() {
+            Ok(out) => {
+                apply_segmentation_output(&mut filter.graphics, &out);
+                record_mask_timing(filter, &out);
+                filter.plate_pending_frame = Some(out.frame_id);
+            }
+            Err(mpsc::TryRecvError::Empty) => break,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                disconnected = true;
+                break;
             }
         }
     }
@@ -114,6 +393,73 @@ unsafe fn consume_segmentation_outputs(filter: &mut StyledCameraFilter) {
     }
 }
 
+// Must be called while in graphics context.
+unsafe fn collect_staged_segmentation(filter: &mut StyledCameraFilter, settings: FilterSettings) {
+    let Some(inbox) = filter.segmentation.inbox.as_ref() else {
+        return;
+    };
+    let field = DistanceFieldRequest {
+        threshold: settings.mask_threshold.clamp(0.0, 1.0),
+        invert: settings.mask_invert,
+        aspect: filter.output_width.max(1) as f32 / filter.output_height.max(1) as f32,
+    };
+    let depth = settings.depth_blur.then_some(field);
+    let silhouette = settings.silhouette_active().then_some(field);
+    let gfx = &mut filter.graphics;
+    let frame = gfx.stage_seg_frame;
+
+    // The worker only keeps the latest input, so map the newest aged slot and release older ones.
+    let is_ready = |slot: &SegReadbackSlot| {
+        slot.capture_time.is_some() && slot.staged_frame + SEG_READBACK_LAG <= frame
+    };
+    let newest = gfx
+        .stage_seg
+        .iter()
+        .enumerate()
+        .filter(|(_, slot)| is_ready(slot))
+        .max_by_key(|(_, slot)| slot.capture_time)
+        .map(|(i, _)| i);
+    for (i, slot) in gfx.stage_seg.iter_mut().enumerate() {
+        if Some(i) != newest && is_ready(slot) {
+            slot.capture_time = None;
+        }
+    }
+
+    let Some(i) = newest else {
+        return;
+    };
+    let slot = &mut gfx.stage_seg[i];
+    let Some(capture_time) = slot.capture_time.take() else {
+        return;
+    };
+    let frame_id = slot.frame_id;
+
+    let mut data: *mut u8 = std::ptr::null_mut();
+    let mut linesize: u32 = 0;
+    if !obs::gs_stagesurface_map(slot.surf, &mut data, &mut linesize) {
+        return;
+    }
+
+    let mut rgba = vec![0u8; (SEG_SIZE * SEG_SIZE * 4) as usize];
+    for y in 0..SEG_SIZE {
+        let src_row = data.add((y * linesize) as usize);
+        let dst_row = &mut rgba[(y * SEG_SIZE * 4) as usize..][..(SEG_SIZE * 4) as usize];
+        std::ptr::copy_nonoverlapping(src_row, dst_row.as_mut_ptr(), dst_row.len());
+    }
+    obs::gs_stagesurface_unmap(slot.surf);
+
+    inbox.push_latest(SegInput {
+        rgba,
+        width: SEG_SIZE,
+        height: SEG_SIZE,
+        temporal_smoothing: settings.mask_temporal_smoothing,
+        capture_time,
+        frame_id,
+        depth,
+        silhouette,
+    });
+}
+
 // Must be called while in graphics context.
 unsafe fn maybe_request_segmentation(
     filter: &mut StyledCameraFilter,
@@ -122,13 +468,22 @@ unsafe fn maybe_request_segmentation(
     cx: u32,
     cy: u32,
     frame_time: Instant,
+    frame_id: u64,
 ) {
     if filter.segmentation.inbox.is_none() {
         return;
     }
 
+    // Map frames staged on earlier render frames before queueing a new copy.
+    filter.graphics.stage_seg_frame += 1;
+    collect_staged_segmentation(filter, settings);
+
     let now = Instant::now();
-    let interval = Duration::from_secs_f32(1.0 / (settings.mask_fps.max(1) as f32));
+    let interval = if settings.mask_rate_target().is_some() {
+        Duration::from_secs_f32(filter.mask_rate.interval_ms(settings.mask_fps as f32) / 1000.0)
+    } else {
+        Duration::from_secs_f32(1.0 / (settings.mask_fps.max(1) as f32))
+    };
     let due = filter
         .last_mask_request
         .map(|t| now.duration_since(t) >= interval)
@@ -138,6 +493,16 @@ unsafe fn maybe_request_segmentation(
         return;
     }
 
+    // All slots still in flight: skip this request rather than stall on a map.
+    let Some(slot_index) = filter
+        .graphics
+        .stage_seg
+        .iter()
+        .position(|slot| !slot.surf.is_null() && slot.capture_time.is_none())
+    else {
+        return;
+    };
+
     if !render_effect_to_texrender(
         filter.graphics.tex_seg,
         SEG_SIZE,
@@ -159,127 +524,771 @@ unsafe fn maybe_request_segmentation(
         return;
     }
 
-    let Some(inbox) = filter.segmentation.inbox.as_ref() else {
-        return;
-    };
     let tex_seg = obs::gs_texrender_get_texture(filter.graphics.tex_seg);
-    if tex_seg.is_null() || filter.graphics.stage_seg.is_null() {
+    if tex_seg.is_null() {
         return;
     }
 
-    obs::gs_stage_texture(filter.graphics.stage_seg, tex_seg);
+    let frame = filter.graphics.stage_seg_frame;
+    let slot = &mut filter.graphics.stage_seg[slot_index];
+    obs::gs_stage_texture(slot.surf, tex_seg);
+    slot.capture_time = Some(frame_time);
+    slot.frame_id = frame_id;
+    slot.staged_frame = frame;
 
-    let mut data: *mut u8 = std::ptr::null_mut();
-    let mut linesize: u32 = 0;
-    if !obs::gs_stagesurface_map(filter.graphics.stage_seg, &mut data, &mut linesize) {
-        return;
-    }
-
-    let mut rgba = vec![0u8; (SEG_SIZE * SEG_SIZE * 4) as usize];
-    for y in 0..SEG_SIZE {
-        let src_row = data.add((y * linesize) as usize);
-        let dst_row = &mut rgba[(y * SEG_SIZE * 4) as usize..][..(SEG_SIZE * 4) as usize];
-        std::ptr::copy_nonoverlapping(src_row, dst_row.as_mut_ptr(), dst_row.len());
-    }
-    obs::gs_stagesurface_unmap(filter.graphics.stage_seg);
+    filter.last_mask_request = Some(now);
+}
 
-    inbox.push_latest(SegInput {
-        rgba,
-        width: SEG_SIZE,
-        height: SEG_SIZE,
-        temporal_smoothing: settings.mask_temporal_smoothing,
-        capture_time: frame_time,
+// Last frame's measurements for status_text, which runs on the UI thread.
+fn publish_render_stats(filter: &StyledCameraFilter) {
+    let running = filter.segmentation.inbox.is_some();
+    let rate_auto = filter.settings.mask_rate_target().is_some();
+    filter.segmentation.publish_render_stats(RenderStats {
+        mask_fps: Some(filter.mask_output_fps_ema).filter(|&fps| running && fps > 0.0),
+        mask_latency_ms: filter.mask_latency_ema_ms,
+        history_frames: filter.frame_history.depth(),
+        history_vram_bytes: filter.frame_history.vram_bytes(),
+        mask_rate: rate_auto.then(|| {
+            let interval_ms = filter
+                .mask_rate
+                .interval_ms(filter.settings.mask_fps as f32);
+            (1000.0 / interval_ms, filter.mask_rate.infer_ema_ms())
+        }),
     });
-    filter.last_mask_request = Some(now);
+}
+
+// How far back in time the compositor may need to reach into the frame history.
+fn history_delay_ms(
+    filter: &StyledCameraFilter,
+    settings: FilterSettings,
+    frame_interval_ms: f32,
+) -> f32 {
+    let lag_ms = filter
+        .mask_frame_lag
+        .map(|lag| lag as f32 * frame_interval_ms)
+        .unwrap_or(0.0);
+    let mask_delay_ms = filter.mask_latency_ema_ms.max(lag_ms).max(0.0);
+    (mask_delay_ms + settings.sync_video_extra_delay_ms.max(0.0)).clamp(0.0, 500.0)
+}
+
+#[derive(Clone, Copy)]
+struct BlurOutput {
+    tex: *mut obs::gs_texture_t,
+    // First blurred pyramid level (one DualDown halving), the near end of the depth-graded blur.
+    near: *mut obs::gs_texture_t,
+    // Both textures carry background weights in alpha (see DownsampleMasked).
+    weighted: bool,
+}
+
+impl BlurOutput {
+    fn unblurred(tex: *mut obs::gs_texture_t) -> Self {
+        Self {
+            tex,
+            near: tex,
+            weighted: false,
+        }
+    }
 }
 
 // Must be called while in graphics context.
+// With `mask_weight` the samples are weighted by background coverage (see DownsampleMasked).
 unsafe fn render_blur(
     gfx: &mut GraphicsState,
+    settings: FilterSettings,
     tex_for_comp: *mut obs::gs_texture_t,
     cx: u32,
     cy: u32,
     blur_amount: f32,
-) -> *mut obs::gs_texture_t {
-    let mut blur_tex = tex_for_comp;
+    mask_weight: bool,
+) -> BlurOutput {
     if blur_amount <= 0.0001 {
-        return blur_tex;
+        return BlurOutput::unblurred(tex_for_comp);
     }
 
     let down_cx = (cx / DOWNSCALE_DIV).max(1);
     let down_cy = (cy / DOWNSCALE_DIV).max(1);
+    let mask_tex = gfx.mask_tex;
+    let mask_weight = mask_weight && !mask_tex.is_null();
 
     if !render_effect_to_texrender(
         gfx.tex_down,
         down_cx,
         down_cy,
         gfx.effect_downsample,
-        TECH_DOWNSAMPLE,
+        if mask_weight {
+            TECH_DOWNSAMPLE_MASKED
+        } else {
+            TECH_DOWNSAMPLE
+        },
         || {
-            set_vec2_param(gfx.downsample_texel_size, 1.0 / (cx as f32), 1.0 / (cy as f32));
+            set_vec2_param(
+                gfx.downsample_texel_size,
+                1.0 / (cx as f32),
+                1.0 / (cy as f32),
+            );
             if !gfx.downsample_image.is_null() {
                 obs::gs_effect_set_texture(gfx.downsample_image, tex_for_comp);
             }
+            if mask_weight {
+                if !gfx.downsample_mask_image.is_null() {
+                    obs::gs_effect_set_texture(gfx.downsample_mask_image, mask_tex);
+                }
+                set_float_param(
+                    gfx.downsample_mask_threshold,
+                    settings.mask_threshold.clamp(0.0, 1.0),
+                );
+                set_float_param(
+                    gfx.downsample_mask_softness,
+                    settings.mask_softness.clamp(0.0, 1.0),
+                );
+                set_float_param(
+                    gfx.downsample_mask_invert,
+                    if settings.mask_invert { 1.0 } else { 0.0 },
+                );
+            }
         },
         tex_for_comp,
     ) {
-        return blur_tex;
+        return BlurOutput::unblurred(tex_for_comp);
     }
 
     let tex_down = obs::gs_texrender_get_texture(gfx.tex_down);
     if tex_down.is_null() {
-        return blur_tex;
+        return BlurOutput::unblurred(tex_for_comp);
     }
 
-    let blur_radius = (blur_amount * 24.0).max(0.0);
-    let texel_x = 1.0 / (down_cx as f32);
-    let texel_y = 1.0 / (down_cy as f32);
+    if settings.blur_style == BlurStyle::Lens {
+        let Some(tex) = render_bokeh(gfx, settings, tex_down, (down_cx, down_cy), blur_amount)
+        else {
+            return BlurOutput::unblurred(tex_for_comp);
+        };
+        // Fast quality already rendered the first level for the gather.
+        let near = if settings.blur_quality == BlurQuality::Fast {
+            Some(obs::gs_texrender_get_texture(gfx.tex_blur_down[0]))
+        } else if gfx.ensure_blur_levels(1) {
+            let down_size = (down_cx, down_cy);
+            let size = ((down_cx / 2).max(1), (down_cy / 2).max(1));
+            let texrender = gfx.tex_blur_down[0];
+            render_blur_pass(
+                gfx,
+                texrender,
+                TECH_DUAL_DOWN,
+                tex_down,
+                down_size,
+                size,
+                0.0,
+            )
+        } else {
+            None
+        };
+        let near = near.filter(|t| !t.is_null()).unwrap_or(tex_down);
+        return BlurOutput {
+            tex,
+            near,
+            weighted: mask_weight,
+        };
+    }
 
-    let ok_h = render_effect_to_texrender(
-        gfx.tex_ping,
-        down_cx,
-        down_cy,
-        gfx.effect_blur,
-        TECH_BLUR_H,
+    let plan = blur_pyramid_plan(blur_amount, settings.blur_quality, down_cx, down_cy);
+    let levels = plan.levels as usize;
+    if levels == 0 || !gfx.ensure_blur_levels(levels) {
+        return BlurOutput {
+            tex: tex_down,
+            near: tex_down,
+            weighted: mask_weight,
+        };
+    }
+
+    // Level sizes: [0] is the downsampled frame, each further level halves it.
+    gfx.blur_level_sizes.clear();
+    gfx.blur_level_sizes.push((down_cx, down_cy));
+    for i in 0..levels {
+        let (w, h) = gfx.blur_level_sizes[i];
+        gfx.blur_level_sizes.push(((w / 2).max(1), (h / 2).max(1)));
+    }
+
+    let mut src = tex_down;
+    let mut src_size = gfx.blur_level_sizes[0];
+    let mut near = tex_down;
+    for i in 0..levels {
+        let size = gfx.blur_level_sizes[i + 1];
+        let texrender = gfx.tex_blur_down[i];
+        let Some(t) = render_blur_pass(
+            gfx,
+            texrender,
+            TECH_DUAL_DOWN,
+            src,
+            src_size,
+            size,
+            plan.offset,
+        ) else {
+            return BlurOutput::unblurred(tex_for_comp);
+        };
+        if i == 0 {
+            near = t;
+        }
+        (src, src_size) = (t, size);
+    }
+    for i in (0..levels).rev() {
+        let size = gfx.blur_level_sizes[i];
+        let texrender = gfx.tex_blur_up[i];
+        let Some(t) = render_blur_pass(
+            gfx,
+            texrender,
+            TECH_DUAL_UP,
+            src,
+            src_size,
+            size,
+            plan.offset,
+        ) else {
+            return BlurOutput::unblurred(tex_for_comp);
+        };
+        (src, src_size) = (t, size);
+    }
+
+    BlurOutput {
+        tex: src,
+        near,
+        weighted: mask_weight,
+    }
+}
+
+// Must be called while in graphics context.
+// Lens blur of the downsampled frame; Fast quality gathers at quarter resolution instead.
+unsafe fn render_bokeh(
+    gfx: &mut GraphicsState,
+    settings: FilterSettings,
+    tex_down: *mut obs::gs_texture_t,
+    down_size: (u32, u32),
+    blur_amount: f32,
+) -> Option<*mut obs::gs_texture_t> {
+    let mut src = tex_down;
+    let mut src_size = down_size;
+    let mut radius = blur_amount.clamp(0.0, 1.0) * MAX_BOKEH_RADIUS;
+
+    if !gfx.ensure_bokeh() {
+        return None;
+    }
+    if settings.blur_quality == BlurQuality::Fast {
+        if !gfx.ensure_blur_levels(1) {
+            return None;
+        }
+        let size = ((src_size.0 / 2).max(1), (src_size.1 / 2).max(1));
+        src = render_blur_pass(
+            gfx,
+            gfx.tex_blur_down[0],
+            TECH_DUAL_DOWN,
+            src,
+            src_size,
+            size,
+            0.0,
+        )?;
+        src_size = size;
+        radius *= 0.5;
+    }
+
+    let ok = render_effect_to_texrender(
+        gfx.tex_bokeh,
+        src_size.0,
+        src_size.1,
+        gfx.effect_bokeh,
+        TECH_BOKEH,
         || {
-            set_vec2_param(gfx.blur_texel_size, texel_x, texel_y);
-            set_float_param(gfx.blur_radius, blur_radius);
-            if !gfx.blur_image.is_null() {
-                obs::gs_effect_set_texture(gfx.blur_image, tex_down);
+            set_vec2_param(
+                gfx.bokeh_texel_size,
+                1.0 / (src_size.0 as f32),
+                1.0 / (src_size.1 as f32),
+            );
+            set_float_param(gfx.bokeh_radius, radius);
+            set_float_param(
+                gfx.bokeh_shape,
+                if settings.bokeh_shape == BokehShape::Hexagon {
+                    1.0
+                } else {
+                    0.0
+                },
+            );
+            set_float_param(
+                gfx.bokeh_highlight,
+                settings.bokeh_highlights.clamp(0.0, 1.0),
+            );
+            if !gfx.bokeh_image.is_null() {
+                obs::gs_effect_set_texture(gfx.bokeh_image, src);
             }
         },
-        tex_down,
+        src,
     );
 
-    let tex_ping = obs::gs_texrender_get_texture(gfx.tex_ping);
-    if !ok_h || tex_ping.is_null() {
-        return blur_tex;
+    let tex = obs::gs_texrender_get_texture(gfx.tex_bokeh);
+    if ok && !tex.is_null() {
+        Some(tex)
+    } else {
+        None
     }
+}
 
-    let ok_v = render_effect_to_texrender(
-        gfx.tex_pong,
-        down_cx,
-        down_cy,
+// Must be called while in graphics context.
+// One DualDown/DualUp pass from `src` into `texrender` at `size`.
+unsafe fn render_blur_pass(
+    gfx: &GraphicsState,
+    texrender: *mut obs::gs_texrender_t,
+    technique: &'static [u8],
+    src: *mut obs::gs_texture_t,
+    src_size: (u32, u32),
+    size: (u32, u32),
+    offset: f32,
+) -> Option<*mut obs::gs_texture_t> {
+    let ok = render_effect_to_texrender(
+        texrender,
+        size.0,
+        size.1,
         gfx.effect_blur,
-        TECH_BLUR_V,
+        technique,
         || {
-            set_vec2_param(gfx.blur_texel_size, texel_x, texel_y);
-            set_float_param(gfx.blur_radius, blur_radius);
+            set_vec2_param(
+                gfx.blur_texel_size,
+                1.0 / (src_size.0 as f32),
+                1.0 / (src_size.1 as f32),
+            );
+            set_float_param(gfx.blur_offset, offset);
             if !gfx.blur_image.is_null() {
-                obs::gs_effect_set_texture(gfx.blur_image, tex_ping);
+                obs::gs_effect_set_texture(gfx.blur_image, src);
             }
         },
-        tex_ping,
+        src,
     );
 
-    if ok_v {
-        let t = obs::gs_texrender_get_texture(gfx.tex_pong);
-        if !t.is_null() {
-            blur_tex = t;
+    let tex = obs::gs_texrender_get_texture(texrender);
+    if ok && !tex.is_null() {
+        Some(tex)
+    } else {
+        None
+    }
+}
+
+// Must be called while in graphics context.
+// Draws the background image fitted to the frame; None while no image is loaded.
+unsafe fn render_background_image(
+    gfx: &mut GraphicsState,
+    image: &BackgroundImage,
+    fit: BackgroundFit,
+    cx: u32,
+    cy: u32,
+) -> Option<*mut obs::gs_texture_t> {
+    let image_tex = image.texture();
+    if image_tex.is_null() {
+        return None;
+    }
+
+    let (image_w, image_h) = image.size();
+    let transform = background_fit_transform(fit, image_w, image_h, cx, cy);
+    let ok = render_effect_to_texrender(
+        gfx.tex_bg,
+        cx,
+        cy,
+        gfx.effect_bg_fit,
+        TECH_BACKGROUND_FIT,
+        || {
+            if !gfx.bg_fit_image.is_null() {
+                obs::gs_effect_set_texture(gfx.bg_fit_image, image_tex);
+            }
+            set_vec2_param(gfx.bg_fit_uv_scale, transform.scale[0], transform.scale[1]);
+            set_vec2_param(
+                gfx.bg_fit_uv_offset,
+                transform.offset[0],
+                transform.offset[1],
+            );
+            set_float_param(
+                gfx.bg_fit_tile,
+                if fit == BackgroundFit::Tile { 1.0 } else { 0.0 },
+            );
+        },
+        image_tex,
+    );
+
+    let tex_bg = obs::gs_texrender_get_texture(gfx.tex_bg);
+    if ok && !tex_bg.is_null() {
+        Some(tex_bg)
+    } else {
+        None
+    }
+}
+
+// Must be called while in graphics context.
+// Renders the background source stretched to the frame; None while it isn't available.
+unsafe fn render_background_source(
+    filter: &mut StyledCameraFilter,
+    cx: u32,
+    cy: u32,
+) -> Option<*mut obs::gs_texture_t> {
+    let source = filter.bg_source.get();
+    if source.is_null() {
+        return None;
+    }
+
+    let source_cx = obs::obs_source_get_width(source);
+    let source_cy = obs::obs_source_get_height(source);
+    let mut ok = false;
+    if source_cx > 0 && source_cy > 0 {
+        filter.rendering_bg_source = true;
+        ok = render_source_scaled_to_texrender(
+            filter.graphics.tex_bg_source,
+            cx,
+            cy,
+            source,
+            source_cx,
+            source_cy,
+        );
+        filter.rendering_bg_source = false;
+    }
+    obs::obs_source_release(source);
+
+    let tex = obs::gs_texrender_get_texture(filter.graphics.tex_bg_source);
+    if ok && !tex.is_null() {
+        Some(tex)
+    } else {
+        None
+    }
+}
+
+// Must be called while in graphics context.
+// Teaches the clean background plate the background pixels of `learn` (the frame a new mask was
+// computed for; None without a new mask, or when frozen), then resolves the plate into the
+// background texrender; areas never seen show `frame` itself.
+// None until the plate has learned from a masked frame.
+unsafe fn render_background_plate(
+    gfx: &mut GraphicsState,
+    settings: FilterSettings,
+    frame: *mut obs::gs_texture_t,
+    learn: Option<*mut obs::gs_texture_t>,
+    cx: u32,
+    cy: u32,
+) -> Option<*mut obs::gs_texture_t> {
+    if !gfx.ensure_plate() {
+        return None;
+    }
+    if gfx.plate_size != (cx, cy) {
+        gfx.plate_size = (cx, cy);
+        gfx.plate_valid = false;
+    }
+
+    // Each mask is learned once, against its own frame: a mask reused on later frames would let
+    // the person leak into the plate wherever they moved since.
+    let learn = learn.filter(|_| !settings.bg_plate_freeze || !gfx.plate_valid);
+    if let Some(learn) = learn {
+        let prev = obs::gs_texrender_get_texture(gfx.tex_plate[gfx.plate_index]);
+        let reset = !gfx.plate_valid || prev.is_null();
+        let next = 1 - gfx.plate_index;
+        let mask_tex = gfx.mask_tex;
+        let ok = render_effect_to_texrender(
+            gfx.tex_plate[next],
+            cx,
+            cy,
+            gfx.effect_plate,
+            TECH_PLATE_ACCUMULATE,
+            || {
+                if !gfx.plate_image.is_null() {
+                    obs::gs_effect_set_texture(gfx.plate_image, learn);
+                }
+                if !gfx.plate_prev_image.is_null() {
+                    obs::gs_effect_set_texture(gfx.plate_prev_image, prev);
+                }
+                if !gfx.plate_mask_image.is_null() {
+                    obs::gs_effect_set_texture(gfx.plate_mask_image, mask_tex);
+                }
+                set_float_param(
+                    gfx.plate_mask_threshold,
+                    settings.mask_threshold.clamp(0.0, 1.0),
+                );
+                set_float_param(
+                    gfx.plate_mask_softness,
+                    settings.mask_softness.clamp(0.0, 1.0),
+                );
+                set_float_param(
+                    gfx.plate_mask_invert,
+                    if settings.mask_invert { 1.0 } else { 0.0 },
+                );
+                // A running average over as many masks as the frame history holds frames.
+                set_float_param(gfx.plate_blend, plate_blend(settings.history_max_frames));
+                set_float_param(gfx.plate_reset, if reset { 1.0 } else { 0.0 });
+            },
+            learn,
+        );
+        if ok {
+            gfx.plate_index = next;
+            gfx.plate_valid = true;
         }
     }
 
-    blur_tex
+    if !gfx.plate_valid {
+        return None;
+    }
+    let plate = obs::gs_texrender_get_texture(gfx.tex_plate[gfx.plate_index]);
+    if plate.is_null() {
+        return None;
+    }
+
+    let ok = render_effect_to_texrender(
+        gfx.tex_bg,
+        cx,
+        cy,
+        gfx.effect_plate,
+        TECH_PLATE_RESOLVE,
+        || {
+            if !gfx.plate_image.is_null() {
+                obs::gs_effect_set_texture(gfx.plate_image, frame);
+            }
+            if !gfx.plate_prev_image.is_null() {
+                obs::gs_effect_set_texture(gfx.plate_prev_image, plate);
+            }
+        },
+        frame,
+    );
+
+    let tex_bg = obs::gs_texrender_get_texture(gfx.tex_bg);
+    if ok && !tex_bg.is_null() {
+        Some(tex_bg)
+    } else {
+        None
+    }
+}
+
+// Must be called while in graphics context.
+// Draws the selected mask debug view to the output in place of the styled frame; the shader
+// views fall back to the raw mask if their effect failed to load.
+unsafe fn draw_mask_debug(
+    gfx: &mut GraphicsState,
+    settings: FilterSettings,
+    frame: *mut obs::gs_texture_t,
+    cx: u32,
+    cy: u32,
+) {
+    let shader_view = settings.debug_mask_view.shader_view();
+    if let Some(view) = shader_view.filter(|_| gfx.ensure_mask_debug()) {
+        let effect = gfx.effect_mask_debug;
+        if !gfx.mask_debug_image.is_null() {
+            obs::gs_effect_set_texture(gfx.mask_debug_image, frame);
+        }
+        if !gfx.mask_debug_mask_image.is_null() {
+            obs::gs_effect_set_texture(gfx.mask_debug_mask_image, gfx.mask_tex);
+        }
+        set_float_param(
+            gfx.mask_debug_threshold,
+            settings.mask_threshold.clamp(0.0, 1.0),
+        );
+        set_float_param(
+            gfx.mask_debug_softness,
+            settings.mask_softness.clamp(0.0, 1.0),
+        );
+        set_float_param(
+            gfx.mask_debug_invert,
+            if settings.mask_invert { 1.0 } else { 0.0 },
+        );
+        set_float_param(gfx.mask_debug_view, view);
+        while obs::gs_effect_loop(effect, cstr(TECH_MASK_DEBUG)) {
+            obs::gs_draw_sprite(frame, 0, cx, cy);
+        }
+        return;
+    }
+
+    // The raw mask is drawn as the model returned it, without mask_invert, like the raw half of
+    // the side by side view.
+    // The segmentation input only exists once a frame has been sent to the model.
+    let seg_input = match settings.debug_mask_view {
+        DebugMaskView::SegInput => obs::gs_texrender_get_texture(gfx.tex_seg),
+        _ => std::ptr::null_mut(),
+    };
+    let tex = if seg_input.is_null() {
+        gfx.mask_tex
+    } else {
+        seg_input
+    };
+    let effect = obs::obs_get_base_effect(obs::obs_base_effect_OBS_EFFECT_DEFAULT);
+    let image_param = obs::gs_effect_get_param_by_name(effect, cstr(b"image\0"));
+    obs::gs_effect_set_texture(image_param, tex);
+    while obs::gs_effect_loop(effect, cstr(b"Draw\0")) {
+        obs::gs_draw_sprite(tex, 0, cx, cy);
+    }
+}
+
+// Must be called while in graphics context.
+// Pixelates the frame into the background texrender; the person is composited back on top.
+unsafe fn render_background_privacy(
+    gfx: &mut GraphicsState,
+    settings: FilterSettings,
+    frame: *mut obs::gs_texture_t,
+    cx: u32,
+    cy: u32,
+) -> Option<*mut obs::gs_texture_t> {
+    if !gfx.ensure_privacy() {
+        return None;
+    }
+    let ok = render_effect_to_texrender(
+        gfx.tex_bg,
+        cx,
+        cy,
+        gfx.effect_privacy,
+        TECH_PRIVACY,
+        || {
+            if !gfx.privacy_image.is_null() {
+                obs::gs_effect_set_texture(gfx.privacy_image, frame);
+            }
+            let [bw, bh] = privacy_block_uv(settings.bg_privacy_block, cx, cy);
+            set_vec2_param(gfx.privacy_block_size, bw, bh);
+            set_float_param(gfx.privacy_style, settings.bg_privacy_style.shader_value());
+        },
+        frame,
+    );
+
+    let tex_bg = obs::gs_texrender_get_texture(gfx.tex_bg);
+    if ok && !tex_bg.is_null() {
+        Some(tex_bg)
+    } else {
+        None
+    }
+}
+
+// Must be called while in graphics context.
+// Stand-in for the privacy background when it cannot be rendered: opaque black, never the camera
+// frame.
+unsafe fn render_background_blackout(
+    gfx: &mut GraphicsState,
+    cx: u32,
+    cy: u32,
+) -> Option<*mut obs::gs_texture_t> {
+    if !clear_texrender(gfx.tex_bg, cx, cy, [0.0, 0.0, 0.0, 1.0]) {
+        return None;
+    }
+    let tex_bg = obs::gs_texrender_get_texture(gfx.tex_bg);
+    if tex_bg.is_null() {
+        None
+    } else {
+        Some(tex_bg)
+    }
+}
+
+// Must be called while in graphics context.
+// Renders a generated background (solid, gradient, noise, aurora) into the background texrender.
+unsafe fn render_background_generated(
+    gfx: &mut GraphicsState,
+    settings: FilterSettings,
+    gen_type: f32,
+    time: f32,
+    cx: u32,
+    cy: u32,
+) -> Option<*mut obs::gs_texture_t> {
+    let ok = render_effect_to_texrender(
+        gfx.tex_bg,
+        cx,
+        cy,
+        gfx.effect_bg_generate,
+        TECH_BACKGROUND_GENERATE,
+        || {
+            set_vec2_param(gfx.bg_gen_size, cx as f32, cy as f32);
+            set_float_param(gfx.bg_gen_type, gen_type);
+            for (param, abgr) in gfx.bg_gen_colors.iter().zip(settings.bg_colors_abgr) {
+                set_vec4_param(*param, obs_abgr_to_rgba_vec4(abgr));
+            }
+            set_float_param(gfx.bg_gen_stop_count, settings.bg_gradient_stops as f32);
+            set_float_param(gfx.bg_gen_angle, settings.bg_gradient_angle);
+            set_float_param(gfx.bg_gen_time, time);
+        },
+        // The generator needs no input texture; the sprite only supplies geometry.
+        std::ptr::null_mut(),
+    );
+
+    let tex_bg = obs::gs_texrender_get_texture(gfx.tex_bg);
+    if ok && !tex_bg.is_null() {
+        Some(tex_bg)
+    } else {
+        None
+    }
+}
+
+// Pixel sizes in the UI are for a 1080p frame; the composite works in the frame's own texels.
+fn scale_to_frame(px_1080p: f32, cy: u32) -> f32 {
+    px_1080p * cy as f32 / 1080.0
+}
+
+// The mask is coarser than the frame, so the transition band scales with the frame height.
+fn decontam_radius(cy: u32) -> f32 {
+    scale_to_frame(DECONTAM_RADIUS_1080P, cy).max(2.0)
+}
+
+// Sizes in the UI are pixels of a 1080p frame; the silhouette field measures in frame heights.
+// Must be called while in graphics context.
+unsafe fn set_silhouette_params(
+    gfx: &GraphicsState,
+    settings: FilterSettings,
+    active: bool,
+    cx: u32,
+    cy: u32,
+) {
+    set_float_param(gfx.composite_silhouette, if active { 1.0 } else { 0.0 });
+    if !active {
+        return;
+    }
+    if !gfx.composite_silhouette_image.is_null() {
+        obs::gs_effect_set_texture(gfx.composite_silhouette_image, gfx.silhouette_tex);
+    }
+    let frame_heights = |px: f32| px / 1080.0;
+    set_float_param(gfx.composite_silhouette_range, SILHOUETTE_FIELD_RANGE);
+    set_float_param(
+        gfx.composite_outline_width,
+        frame_heights(settings.sil_outline_width.clamp(0.0, 40.0)),
+    );
+    set_float_param(
+        gfx.composite_outline_softness,
+        frame_heights(settings.sil_softness.clamp(0.0, 20.0)),
+    );
+    set_vec4_param(
+        gfx.composite_outline_color,
+        obs_abgr_to_rgba_vec4(settings.sil_outline_color_argb),
+    );
+    set_float_param(
+        gfx.composite_glow_size,
+        frame_heights(settings.sil_glow_size.clamp(0.0, 80.0)),
+    );
+    set_vec4_param(
+        gfx.composite_glow_color,
+        obs_abgr_to_rgba_vec4(settings.sil_glow_color_argb),
+    );
+
+    let mut shadow = obs_abgr_to_rgba_vec4(settings.sil_shadow_color_argb);
+    shadow[3] *= settings.sil_shadow_opacity.clamp(0.0, 1.0);
+    set_vec4_param(gfx.composite_sil_shadow_color, shadow);
+    set_float_param(
+        gfx.composite_sil_shadow_blur,
+        frame_heights(settings.sil_shadow_blur.clamp(0.0, 40.0)),
+    );
+    let offset_px = |v: f32| scale_to_frame(v.clamp(-60.0, 60.0), cy);
+    set_vec2_param(
+        gfx.composite_sil_shadow_offset,
+        offset_px(settings.sil_shadow_offset_x) / cx.max(1) as f32,
+        offset_px(settings.sil_shadow_offset_y) / cy.max(1) as f32,
+    );
+}
+
+// Must be called while in graphics context.
+unsafe fn set_lut_params(gfx: &GraphicsState, settings: FilterSettings, lut: &LutTexture) {
+    // Until a LUT has loaded (or when it failed to) neither layer uses it.
+    let amount = if lut.texture().is_null() {
+        0.0
+    } else {
+        settings.lut_amount.clamp(0.0, 1.0)
+    };
+    let (fg, bg) = settings.lut_target.amounts(amount);
+    set_vec2_param(gfx.composite_lut_amount, fg, bg);
+    if amount <= 0.0 {
+        return;
+    }
+    if !gfx.composite_lut_image.is_null() {
+        obs::gs_effect_set_texture(gfx.composite_lut_image, lut.texture());
+    }
+    set_float_param(gfx.composite_lut_size, lut.size() as f32);
+    let (min, max) = lut.domain();
+    set_vec3_param(gfx.composite_lut_domain_min, min);
+    set_vec3_param(gfx.composite_lut_domain_max, max);
 }
 
 // Must be called while in graphics context.
@@ -287,10 +1296,14 @@ unsafe fn render_composite(
     gfx: &mut GraphicsState,
     settings: FilterSettings,
     tex_for_comp: *mut obs::gs_texture_t,
-    blur_tex: *mut obs::gs_texture_t,
+    blur: BlurOutput,
+    lut: &LutTexture,
     cx: u32,
     cy: u32,
 ) -> Option<*mut obs::gs_texture_t> {
+    // Depth grading needs a distance field from the worker; until then the blur stays uniform.
+    let depth_blur = settings.depth_blur && !gfx.depth_tex.is_null() && blur.near != blur.tex;
+    let silhouette = settings.silhouette_active() && !gfx.silhouette_tex.is_null();
     let ok = render_effect_to_texrender(
         gfx.tex_comp,
         cx,
@@ -302,8 +1315,21 @@ unsafe fn render_composite(
                 obs::gs_effect_set_texture(gfx.composite_image, tex_for_comp);
             }
             if !gfx.composite_blur_image.is_null() {
-                obs::gs_effect_set_texture(gfx.composite_blur_image, blur_tex);
+                obs::gs_effect_set_texture(gfx.composite_blur_image, blur.tex);
             }
+            if depth_blur {
+                if !gfx.composite_blur_near_image.is_null() {
+                    obs::gs_effect_set_texture(gfx.composite_blur_near_image, blur.near);
+                }
+                if !gfx.composite_depth_image.is_null() {
+                    obs::gs_effect_set_texture(gfx.composite_depth_image, gfx.depth_tex);
+                }
+                set_float_param(
+                    gfx.composite_depth_falloff,
+                    settings.depth_falloff.clamp(0.01, 1.0),
+                );
+            }
+            set_float_param(gfx.composite_depth_blur, if depth_blur { 1.0 } else { 0.0 });
             if !gfx.composite_mask_image.is_null() {
                 obs::gs_effect_set_texture(gfx.composite_mask_image, gfx.mask_tex);
             }
@@ -312,16 +1338,62 @@ unsafe fn render_composite(
                 gfx.composite_mask_threshold,
                 settings.mask_threshold.clamp(0.0, 1.0),
             );
-            set_float_param(gfx.composite_mask_softness, settings.mask_softness.clamp(0.0, 1.0));
-            set_float_param(gfx.composite_mask_invert, if settings.mask_invert { 1.0 } else { 0.0 });
+            set_float_param(
+                gfx.composite_mask_softness,
+                settings.mask_softness.clamp(0.0, 1.0),
+            );
+            set_float_param(
+                gfx.composite_mask_invert,
+                if settings.mask_invert { 1.0 } else { 0.0 },
+            );
             set_float_param(gfx.composite_bg_dim, settings.bg_dim.clamp(0.0, 1.0));
             set_float_param(gfx.composite_bg_desat, settings.bg_desat.clamp(0.0, 1.0));
+            set_float_param(
+                gfx.composite_bg_remove,
+                if settings.bg_type == BackgroundType::Transparent {
+                    1.0
+                } else {
+                    0.0
+                },
+            );
+            set_float_param(
+                gfx.composite_blur_weighted,
+                if blur.weighted { 1.0 } else { 0.0 },
+            );
+            set_vec2_param(
+                gfx.composite_texel_size,
+                1.0 / (cx as f32),
+                1.0 / (cy as f32),
+            );
+            set_float_param(
+                gfx.composite_decontam_strength,
+                settings.edge_decontam.clamp(0.0, 1.0),
+            );
+            set_float_param(gfx.composite_decontam_radius, decontam_radius(cy));
+            set_float_param(
+                gfx.composite_wrap_intensity,
+                settings.light_wrap.clamp(0.0, 1.0),
+            );
+            set_float_param(
+                gfx.composite_wrap_radius,
+                scale_to_frame(settings.light_wrap_width.clamp(2.0, 64.0), cy),
+            );
+            set_silhouette_params(gfx, settings, silhouette, cx, cy);
+            set_vec4_param(gfx.composite_fg_grade, settings.fg_grade.shader_params());
+            set_vec3_param(gfx.composite_fg_balance, settings.fg_grade.white_balance());
+            set_vec4_param(gfx.composite_bg_grade, settings.bg_grade.shader_params());
+            set_vec3_param(gfx.composite_bg_balance, settings.bg_grade.white_balance());
+            set_lut_params(gfx, settings, lut);
         },
         tex_for_comp,
     );
 
     let tex_comp = obs::gs_texrender_get_texture(gfx.tex_comp);
-    if ok && !tex_comp.is_null() { Some(tex_comp) } else { None }
+    if ok && !tex_comp.is_null() {
+        Some(tex_comp)
+    } else {
+        None
+    }
 }
 
 pub(crate) unsafe fn register_sources() {
@@ -337,6 +1409,10 @@ pub(crate) unsafe fn register_sources() {
     info.get_defaults = Some(styled_camera_filter_get_defaults);
     info.get_properties = Some(styled_camera_filter_get_properties);
     info.video_render = Some(styled_camera_filter_video_render);
+    info.video_tick = Some(styled_camera_filter_video_tick);
+    info.save = Some(styled_camera_filter_save);
+    info.hide = Some(styled_camera_filter_hide);
+    info.deactivate = Some(styled_camera_filter_hide);
 
     obs::obs_register_source_s(
         &info as *const obs::obs_source_info,
@@ -354,12 +1430,38 @@ unsafe extern "C" fn styled_camera_filter_create(
 ) -> *mut c_void {
     let mut filter = Box::new(StyledCameraFilter::new(source));
     filter.settings = FilterSettings::load(settings_data);
+    filter
+        .audio_sync
+        .set_target(settings::load_audio_sync_source(settings_data));
+    filter
+        .audio_sync
+        .set_saved_state(settings::load_audio_sync_state(settings_data));
+    filter
+        .bg_image
+        .set_path(settings::load_bg_image_path(settings_data));
+    filter
+        .bg_source
+        .set_name(settings::load_bg_source_name(settings_data));
+    filter.lut.set_path(settings::load_lut_path(settings_data));
+    filter
+        .shape_image
+        .set_path(settings::load_shape_image_path(settings_data));
 
     filter.graphics.init();
     if filter.settings.needs_segmentation() {
         filter.segmentation.ensure_running();
     }
 
+    let uuid = obs::obs_source_get_uuid(source);
+    if !uuid.is_null() {
+        if let Ok(mut registry) = FILTER_REGISTRY.lock() {
+            registry.push(RegisteredFilter {
+                uuid: CStr::from_ptr(uuid).to_owned(),
+                weak: obs::obs_source_get_weak_source(source),
+                mask: filter.published_mask.clone(),
+            });
+        }
+    }
     Box::into_raw(filter).cast()
 }
 
@@ -368,27 +1470,71 @@ unsafe extern "C" fn styled_camera_filter_destroy(data: *mut c_void) {
         return;
     }
     let mut filter = Box::from_raw(data.cast::<StyledCameraFilter>());
+    if let Ok(mut registry) = FILTER_REGISTRY.lock() {
+        registry.retain(|e| {
+            let ours = Arc::ptr_eq(&e.mask, &filter.published_mask);
+            if ours {
+                obs::obs_weak_source_release(e.weak);
+            }
+            !ours
+        });
+    }
+    // A mask source may still hold the Arc; it must not see the textures destroyed below.
+    clear_published_mask(&filter);
     filter.segmentation.stop();
+    filter.audio_sync.restore();
+    filter.bg_image.free();
+    filter.bg_source.release();
+    filter.lut.free();
+    filter.shape_image.free();
     filter.graphics.destroy(&mut filter.frame_history);
 }
 
-unsafe extern "C" fn styled_camera_filter_update(data: *mut c_void, settings_data: *mut obs::obs_data_t) {
+unsafe extern "C" fn styled_camera_filter_update(
+    data: *mut c_void,
+    settings_data: *mut obs::obs_data_t,
+) {
     if data.is_null() {
         return;
     }
     let filter = &mut *data.cast::<StyledCameraFilter>();
     let old_needs_segmentation = filter.settings.needs_segmentation();
+    let old_rate_mode = filter.settings.mask_rate_mode;
     filter.settings = FilterSettings::load(settings_data);
+    filter.settings.lut_enabled = filter.lut.is_loaded();
+    filter
+        .audio_sync
+        .set_target(settings::load_audio_sync_source(settings_data));
+    filter
+        .bg_image
+        .set_path(settings::load_bg_image_path(settings_data));
+    filter
+        .bg_source
+        .set_name(settings::load_bg_source_name(settings_data));
+    filter.lut.set_path(settings::load_lut_path(settings_data));
+    filter
+        .shape_image
+        .set_path(settings::load_shape_image_path(settings_data));
     let new_needs_segmentation = filter.settings.needs_segmentation();
 
+    if filter.settings.mask_rate_mode != old_rate_mode {
+        filter.mask_rate.reset();
+    }
+
     if new_needs_segmentation && filter.segmentation.inbox.is_none() {
         filter.segmentation.ensure_running();
     } else if old_needs_segmentation && !new_needs_segmentation {
         filter.segmentation.stop();
+        clear_published_mask(filter);
         filter.mask_latency_ema_ms = 0.0;
+        filter.mask_output_fps_ema = 0.0;
         filter.last_mask_request = None;
+        filter.last_mask_output = None;
+        filter.mask_rate.reset();
+        filter.mask_frame_lag = None;
 
         obs::obs_enter_graphics();
+        filter.graphics.reset_seg_readback();
         filter.frame_history.destroy();
         obs::obs_leave_graphics();
     }
@@ -398,43 +1544,245 @@ unsafe extern "C" fn styled_camera_filter_get_defaults(settings_data: *mut obs::
     settings::set_defaults(settings_data);
 }
 
-unsafe extern "C" fn styled_camera_filter_get_properties(_data: *mut c_void) -> *mut obs::obs_properties_t {
-    settings::get_properties()
+fn status_text(filter: &StyledCameraFilter) -> CString {
+    // Render-thread measurements come from the snapshot the render thread publishes.
+    let status = filter.segmentation.status_snapshot();
+
+    let mut lines = Vec::new();
+    lines.push(format!("Worker: {}", status.worker.label()));
+    lines.push(format!(
+        "Model: {}",
+        status
+            .model_path
+            .as_ref()
+            .map(|p| p.display().to_string())
+            .unwrap_or_else(|| "(not resolved)".to_owned())
+    ));
+    lines.push(format!(
+        "ONNX Runtime: {}",
+        status.ort_version.as_deref().unwrap_or("(not loaded)")
+    ));
+    lines.push(format!(
+        "Input layout: {}",
+        match status.input_is_nchw {
+            Some(true) => "NCHW",
+            Some(false) => "NHWC",
+            None => "(not detected yet)",
+        }
+    ));
+    let stats = status.render;
+    match stats.mask_fps {
+        Some(fps) => {
+            lines.push(format!(
+                "Mask FPS: {:.1} (requested {})",
+                fps, filter.settings.mask_fps
+            ));
+            lines.push(format!("Mask latency: {:.1} ms", stats.mask_latency_ms));
+        }
+        None => {
+            lines.push(format!(
+                "Mask FPS: - (requested {})",
+                filter.settings.mask_fps
+            ));
+            lines.push("Mask latency: -".to_owned());
+        }
+    }
+    if stats.history_frames > 0 {
+        lines.push(format!(
+            "Frame history: {} frames (~{:.1} MiB VRAM)",
+            stats.history_frames,
+            stats.history_vram_bytes as f64 / (1024.0 * 1024.0)
+        ));
+    } else {
+        lines.push("Frame history: not allocated".to_owned());
+    }
+    match filter.audio_sync.applied_offset_ms() {
+        Some(ms) => lines.push(format!("Audio offset: +{ms:.0} ms")),
+        None if filter.settings.audio_compensate => lines.push("Audio offset: pending".to_owned()),
+        None => lines.push("Audio offset: off".to_owned()),
+    }
+    match stats.mask_rate {
+        Some((fps, infer_ms)) => lines.push(format!(
+            "Mask rate: auto, {fps:.1} FPS target (worker {infer_ms:.1} ms/frame)"
+        )),
+        None => lines.push("Mask rate: fixed".to_owned()),
+    }
+    lines.push(format!(
+        "Last error: {}",
+        status.last_error.as_deref().unwrap_or("none")
+    ));
+
+    // Status strings come from paths and our own messages; strip NULs defensively.
+    CString::new(lines.join("\n").replace('\0', "")).unwrap_or_default()
+}
+
+fn shape_image_status_text(filter: &StyledCameraFilter) -> CString {
+    CString::new(filter.shape_image.status().replace('\0', "")).unwrap_or_default()
 }
 
-unsafe extern "C" fn styled_camera_filter_video_render(data: *mut c_void, _effect: *mut obs::gs_effect_t) {
+unsafe extern "C" fn styled_camera_filter_status_refresh_clicked(
+    props: *mut obs::obs_properties_t,
+    _property: *mut obs::obs_property_t,
+    data: *mut c_void,
+) -> bool {
+    if props.is_null() || data.is_null() {
+        return false;
+    }
+    let filter = &*data.cast::<StyledCameraFilter>();
+
+    let p = obs::obs_properties_get(props, cstr(SETTING_SEG_STATUS));
+    if p.is_null() {
+        return false;
+    }
+    let text = status_text(filter);
+    obs::obs_property_set_description(p, text.as_ptr());
+    let shape_status = obs::obs_properties_get(props, cstr(SETTING_SHAPE_IMAGE_STATUS));
+    if !shape_status.is_null() {
+        let text = shape_image_status_text(filter);
+        obs::obs_property_set_description(shape_status, text.as_ptr());
+    }
+
+    // Description changes require a refresh.
+    true
+}
+
+unsafe extern "C" fn styled_camera_filter_get_properties(
+    data: *mut c_void,
+) -> *mut obs::obs_properties_t {
+    let (text, shape_text, parent) = if data.is_null() {
+        (CString::default(), CString::default(), std::ptr::null_mut())
+    } else {
+        let filter = &*data.cast::<StyledCameraFilter>();
+        let parent = obs::obs_filter_get_parent(filter.source);
+        (status_text(filter), shape_image_status_text(filter), parent)
+    };
+    settings::get_properties(
+        &text,
+        &shape_text,
+        Some(styled_camera_filter_status_refresh_clicked),
+        parent,
+    )
+}
+
+unsafe extern "C" fn styled_camera_filter_save(
+    data: *mut c_void,
+    settings_data: *mut obs::obs_data_t,
+) {
+    if data.is_null() {
+        return;
+    }
+    let filter = &*data.cast::<StyledCameraFilter>();
+    settings::save_audio_sync_state(settings_data, filter.audio_sync.saved_state());
+}
+
+// Hidden or inactive sources stop rendering, so the audio offset would otherwise stay applied
+// and the mask source would keep showing the last matte.
+unsafe extern "C" fn styled_camera_filter_hide(data: *mut c_void) {
+    if data.is_null() {
+        return;
+    }
+    let filter = &*data.cast::<StyledCameraFilter>();
+    filter.audio_sync.restore();
+    clear_published_mask(filter);
+}
+
+unsafe extern "C" fn styled_camera_filter_video_tick(data: *mut c_void, seconds: f32) {
     if data.is_null() {
         return;
     }
     let filter = &mut *data.cast::<StyledCameraFilter>();
 
-    if !filter.graphics.ensure() {
+    // Disabled filters are still ticked but never rendered.
+    if !obs::obs_source_enabled(filter.source) {
+        filter.audio_sync.restore();
+    }
+
+    // Upload finished loads and pick up files edited in place.
+    filter.bg_image.poll();
+    filter.lut.poll();
+    filter.shape_image.poll();
+    if seconds.is_finite() && seconds > 0.0 {
+        filter.file_check_elapsed += seconds;
+        if filter.file_check_elapsed >= FILE_CHECK_INTERVAL_S {
+            filter.file_check_elapsed = 0.0;
+            filter.bg_image.reload_if_modified();
+            filter.lut.reload_if_modified();
+            filter.shape_image.reload_if_modified();
+        }
+    }
+
+    if filter.settings.bg_type.is_animated() && seconds.is_finite() && seconds > 0.0 {
+        let speed = filter.settings.bg_anim_speed.max(0.0);
+        filter.bg_time = advance_anim_time(filter.bg_time, (seconds * speed) as f64);
+    }
+}
+
+// The only caller of obs_source_skip_video_filter: every render path that cannot draw the
+// styled frame ends here. The privacy background must never fall back to the unfiltered
+// camera (it shows exactly what the background hides), so nothing is drawn instead.
+unsafe fn skip_video_filter(filter: &StyledCameraFilter) {
+    if filter.settings.bg_type != BackgroundType::Privacy {
         obs::obs_source_skip_video_filter(filter.source);
+    }
+}
+
+unsafe extern "C" fn styled_camera_filter_video_render(
+    data: *mut c_void,
+    _effect: *mut obs::gs_effect_t,
+) {
+    if data.is_null() {
+        return;
+    }
+    let filter = &mut *data.cast::<StyledCameraFilter>();
+
+    // The background source contains this filter (e.g. a scene with the camera in it).
+    if filter.rendering_bg_source {
+        skip_video_filter(filter);
+        return;
+    }
+
+    if !filter.graphics.ensure() {
+        skip_video_filter(filter);
         return;
     }
 
     let target = obs::obs_filter_get_target(filter.source);
     if target.is_null() {
-        obs::obs_source_skip_video_filter(filter.source);
+        skip_video_filter(filter);
         return;
     }
 
     let cx = obs::obs_source_get_base_width(target);
     let cy = obs::obs_source_get_base_height(target);
     if cx == 0 || cy == 0 {
-        obs::obs_source_skip_video_filter(filter.source);
+        skip_video_filter(filter);
         return;
     }
+    filter.output_width = cx;
+    filter.output_height = cy;
 
-    let settings = filter.settings;
+    let mut settings = filter.settings;
+    settings.lut_enabled = filter.lut.is_loaded();
     let needs_segmentation = settings.needs_segmentation();
     let needs_background_composite = settings.needs_background_composite();
     let blur_amount = settings.blur_intensity.clamp(0.0, 1.0);
+    let remove_background = settings.bg_type == BackgroundType::Transparent;
 
     if needs_segmentation {
         filter.segmentation.ensure_running();
     }
 
+    // Audio follows the delay the history ring applied to the previous frame; it changes by at
+    // most a frame at a time, well below the sync offset hysteresis.
+    let frame_interval_ms = obs::obs_get_frame_interval_ns() as f32 / 1_000_000.0;
+    let audio_delay_ms = (settings.audio_compensate && needs_segmentation)
+        .then(|| filter.frame_history.applied_delay().as_secs_f32() * 1000.0);
+    filter
+        .audio_sync
+        .tick(obs::obs_filter_get_parent(filter.source), audio_delay_ms);
+
+    publish_render_stats(filter);
+
     let t_frame = filter.perf.start();
 
     obs::obs_enter_graphics();
@@ -443,38 +1791,62 @@ unsafe extern "C" fn styled_camera_filter_video_render(data: *mut c_void, _effec
         let t = filter.perf.start();
         consume_segmentation_outputs(filter);
         filter.perf.record_consume_outputs(t);
+    }
+    publish_mask(filter, cx, cy);
 
+    if needs_segmentation {
         // Render source into current history slot (used both for segmentation capture and as delay buffer).
-        filter.frame_history.ensure_allocated();
+        let depth = required_history_depth(
+            history_delay_ms(filter, settings, frame_interval_ms),
+            frame_interval_ms,
+            (settings.history_max_frames as usize).min(history_depth_budget(
+                cx,
+                cy,
+                HISTORY_VRAM_BUDGET_BYTES,
+            )),
+        );
+        filter.frame_history.ensure_allocated(depth, cx, cy);
         let frame_time = Instant::now();
+        let frame_id = filter.frame_history.next_frame_id();
         let mut tex_current: *mut obs::gs_texture_t = std::ptr::null_mut();
         if let Some(entry) = filter.frame_history.next_slot_mut() {
             let t = filter.perf.start();
             let ok = !entry.tex.is_null() && render_source_to_texrender(entry.tex, cx, cy, target);
             filter.perf.record_render_source(t);
             if ok {
-                entry.time = Some(frame_time);
+                entry.stamp = Some(FrameStamp {
+                    id: frame_id,
+                    time: frame_time,
+                });
                 tex_current = obs::gs_texrender_get_texture(entry.tex);
             }
         }
 
         if !tex_current.is_null() {
-            let delay_ms =
-                (filter.mask_latency_ema_ms.max(0.0) + settings.sync_video_extra_delay_ms.max(0.0))
-                    .clamp(0.0, 500.0);
-            let tex_for_comp = filter.frame_history.select_delayed_texture(tex_current, delay_ms);
+            let extra_delay_ms = settings.sync_video_extra_delay_ms.clamp(0.0, 500.0);
+            // Pair video with the frame the mask was computed from; fall back to the latency
+            // estimate until the first mask arrives.
+            let frame_target = match filter.mask_frame_lag {
+                Some(lag) => FrameTarget::Id {
+                    id: frame_id.saturating_sub(lag),
+                    extra_delay: Duration::from_secs_f32(extra_delay_ms / 1000.0),
+                },
+                None => {
+                    let delay_ms =
+                        (filter.mask_latency_ema_ms.max(0.0) + extra_delay_ms).clamp(0.0, 500.0);
+                    FrameTarget::Delay(Duration::from_secs_f32(delay_ms / 1000.0))
+                }
+            };
+            let tex_for_comp = filter
+                .frame_history
+                .select_delayed_texture(tex_current, frame_target);
 
             let t = filter.perf.start();
-            maybe_request_segmentation(filter, settings, tex_current, cx, cy, frame_time);
+            maybe_request_segmentation(filter, settings, tex_current, cx, cy, frame_time, frame_id);
             filter.perf.record_seg_request(t);
 
             if settings.debug_show_mask && !filter.graphics.mask_tex.is_null() {
-                let effect = obs::obs_get_base_effect(obs::obs_base_effect_OBS_EFFECT_DEFAULT);
-                let image_param = obs::gs_effect_get_param_by_name(effect, cstr(b"image\0"));
-                obs::gs_effect_set_texture(image_param, filter.graphics.mask_tex);
-                while obs::gs_effect_loop(effect, cstr(b"Draw\0")) {
-                    obs::gs_draw_sprite(filter.graphics.mask_tex, 0, cx, cy);
-                }
+                draw_mask_debug(&mut filter.graphics, settings, tex_for_comp, cx, cy);
                 obs::obs_leave_graphics();
                 filter.perf.record_frame(t_frame);
                 return;
@@ -482,16 +1854,93 @@ unsafe extern "C" fn styled_camera_filter_video_render(data: *mut c_void, _effec
 
             let tex_out = if needs_background_composite {
                 let t = filter.perf.start();
-                let blur_tex = render_blur(&mut filter.graphics, tex_for_comp, cx, cy, blur_amount);
+                // The plate starts over whenever it is switched back on.
+                if settings.bg_type != BackgroundType::CleanPlate {
+                    filter.graphics.plate_valid = false;
+                }
+                // Replacement backgrounds fall back to the camera frame until they are available.
+                let bg_tex = match settings.bg_type {
+                    BackgroundType::Image => {
+                        let fit = settings.bg_image_fit;
+                        render_background_image(&mut filter.graphics, &filter.bg_image, fit, cx, cy)
+                            .unwrap_or(tex_for_comp)
+                    }
+                    BackgroundType::Source => {
+                        render_background_source(filter, cx, cy).unwrap_or(tex_for_comp)
+                    }
+                    BackgroundType::CleanPlate => {
+                        let learn = filter
+                            .plate_pending_frame
+                            .take()
+                            .and_then(|id| filter.frame_history.texture_for_frame(id));
+                        render_background_plate(
+                            &mut filter.graphics,
+                            settings,
+                            tex_for_comp,
+                            learn,
+                            cx,
+                            cy,
+                        )
+                        .unwrap_or(tex_for_comp)
+                    }
+                    // Fails closed: the readable frame must never stand in for the background,
+                    // so without any background nothing is drawn this frame.
+                    BackgroundType::Privacy => {
+                        let gfx = &mut filter.graphics;
+                        let bg = render_background_privacy(gfx, settings, tex_for_comp, cx, cy)
+                            .or_else(|| render_background_blackout(gfx, cx, cy));
+                        let Some(bg) = bg else {
+                            obs::obs_leave_graphics();
+                            filter.perf.record_frame(t_frame);
+                            return;
+                        };
+                        bg
+                    }
+                    bg_type => match bg_type.generator() {
+                        Some(gen_type) => render_background_generated(
+                            &mut filter.graphics,
+                            settings,
+                            gen_type,
+                            filter.bg_time as f32,
+                            cx,
+                            cy,
+                        )
+                        .unwrap_or(tex_for_comp),
+                        None => tex_for_comp,
+                    },
+                };
+                // Blurring the camera frame itself: keep the person's pixels out of it.
+                let mask_weight = settings.blur_mask_aware && bg_tex == tex_for_comp;
+                let blur = if remove_background {
+                    BlurOutput::unblurred(tex_for_comp)
+                } else {
+                    render_blur(
+                        &mut filter.graphics,
+                        settings,
+                        bg_tex,
+                        cx,
+                        cy,
+                        blur_amount,
+                        mask_weight,
+                    )
+                };
                 filter.perf.record_blur(t);
 
                 let t = filter.perf.start();
-                let res = render_composite(&mut filter.graphics, settings, tex_for_comp, blur_tex, cx, cy);
+                let res = render_composite(
+                    &mut filter.graphics,
+                    settings,
+                    tex_for_comp,
+                    blur,
+                    &filter.lut,
+                    cx,
+                    cy,
+                );
                 filter.perf.record_composite(t);
                 let Some(tex_comp) = res else {
                     obs::obs_leave_graphics();
                     filter.perf.record_frame(t_frame);
-                    obs::obs_source_skip_video_filter(filter.source);
+                    skip_video_filter(filter);
                     return;
                 };
                 tex_comp
@@ -501,7 +1950,17 @@ unsafe extern "C" fn styled_camera_filter_video_render(data: *mut c_void, _effec
 
             if !tex_out.is_null() {
                 let t = filter.perf.start();
-                draw_shape_to_screen(&filter.graphics, &settings, tex_out, cx, cy);
+                // The transparent composite is premultiplied; everything else is straight alpha.
+                let premultiplied = needs_background_composite && remove_background;
+                draw_shape_to_screen(
+                    &filter.graphics,
+                    &settings,
+                    &filter.shape_image,
+                    tex_out,
+                    premultiplied,
+                    cx,
+                    cy,
+                );
                 filter.perf.record_shape(t);
                 obs::obs_leave_graphics();
                 filter.perf.record_frame(t_frame);
@@ -511,7 +1970,7 @@ unsafe extern "C" fn styled_camera_filter_video_render(data: *mut c_void, _effec
 
         obs::obs_leave_graphics();
         filter.perf.record_frame(t_frame);
-        obs::obs_source_skip_video_filter(filter.source);
+        skip_video_filter(filter);
         return;
     }
 
@@ -523,7 +1982,8 @@ unsafe extern "C" fn styled_camera_filter_video_render(data: *mut c_void, _effec
         let tex = obs::gs_texrender_get_texture(filter.graphics.tex_comp);
         if !tex.is_null() {
             let t = filter.perf.start();
-            draw_shape_to_screen(&filter.graphics, &settings, tex, cx, cy);
+            let shape_image = &filter.shape_image;
+            draw_shape_to_screen(&filter.graphics, &settings, shape_image, tex, false, cx, cy);
             filter.perf.record_shape(t);
             obs::obs_leave_graphics();
             filter.perf.record_frame(t_frame);
@@ -533,5 +1993,5 @@ unsafe extern "C" fn styled_camera_filter_video_render(data: *mut c_void, _effec
 
     obs::obs_leave_graphics();
     filter.perf.record_frame(t_frame);
-    obs::obs_source_skip_video_filter(filter.source);
+    skip_video_filter(filter);
 }
@@ -1,15 +1,30 @@
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
 use obs_sys as obs;
+use styledcamera_core::history::{
+    history_vram_bytes, next_history_depth, select_history_slot, slot_delay, FrameStamp,
+    FrameTarget, SystemClock,
+};
+
+use crate::util::cstr;
 
 pub(crate) struct FrameHistoryEntry {
     pub tex: *mut obs::gs_texrender_t,
-    pub time: Option<Instant>,
+    pub stamp: Option<FrameStamp>,
 }
 
 pub(crate) struct FrameHistory {
     entries: Vec<FrameHistoryEntry>,
     next: usize,
+    // Monotonic across reallocations so stale ids never match a new frame.
+    next_frame_id: u64,
+    // Source size the slots were last rendered at (for VRAM accounting).
+    width: u32,
+    height: u32,
+    // Per-frame scratch for select_delayed_texture, kept to avoid allocating while rendering.
+    slot_stamps: Vec<Option<FrameStamp>>,
+    // Delay of the frame select_delayed_texture last picked behind the newest one.
+    applied_delay: Duration,
 }
 
 impl Default for FrameHistory {
@@ -17,6 +32,11 @@ impl Default for FrameHistory {
         Self {
             entries: Vec::new(),
             next: 0,
+            next_frame_id: 0,
+            width: 0,
+            height: 0,
+            slot_stamps: Vec::new(),
+            applied_delay: Duration::ZERO,
         }
     }
 }
@@ -32,23 +52,80 @@ impl FrameHistory {
         Some(&mut self.entries[i])
     }
 
+    // Id the next rendered frame will receive.
+    pub(crate) fn upcoming_frame_id(&self) -> u64 {
+        self.next_frame_id
+    }
+
+    pub(crate) fn next_frame_id(&mut self) -> u64 {
+        let id = self.next_frame_id;
+        self.next_frame_id += 1;
+        id
+    }
+
+    pub(crate) fn depth(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub(crate) fn vram_bytes(&self) -> u64 {
+        history_vram_bytes(self.width, self.height, self.entries.len())
+    }
+
     // Must be called while in graphics context.
-    pub(crate) unsafe fn ensure_allocated(&mut self) {
-        if !self.entries.is_empty() {
+    // Grows or shrinks the ring towards `required` slots, keeping the newest frames.
+    pub(crate) unsafe fn ensure_allocated(&mut self, required: usize, cx: u32, cy: u32) {
+        let size_changed = self.width != cx || self.height != cy;
+        self.width = cx;
+        self.height = cy;
+
+        let current = self.entries.len();
+        let depth = next_history_depth(current, required);
+        if depth == current {
+            if size_changed && current > 0 {
+                self.log_allocation();
+            }
             return;
         }
 
-        // Keep this small to avoid large VRAM usage; enough for typical inference latency (tens of ms).
-        const N: usize = 8;
-        self.entries.reserve(N);
-        for _ in 0..N {
-            let tr = obs::gs_texrender_create(
-                obs::gs_color_format_GS_RGBA,
-                obs::gs_zstencil_format_GS_ZS_NONE,
-            );
-            self.entries.push(FrameHistoryEntry { tex: tr, time: None });
+        // Rotate so the oldest slot is first and the write position is index 0.
+        if current > 0 {
+            self.entries.rotate_left(self.next % current);
         }
         self.next = 0;
+
+        if depth > current {
+            // New slots go first: they are empty and get written next, ahead of the oldest frames.
+            let new_slots: Vec<FrameHistoryEntry> = (current..depth)
+                .map(|_| FrameHistoryEntry {
+                    tex: obs::gs_texrender_create(
+                        obs::gs_color_format_GS_RGBA,
+                        obs::gs_zstencil_format_GS_ZS_NONE,
+                    ),
+                    stamp: None,
+                })
+                .collect();
+            self.entries.splice(0..0, new_slots);
+        } else {
+            // Drop the oldest frames.
+            for entry in self.entries.drain(0..current - depth) {
+                if !entry.tex.is_null() {
+                    obs::gs_texrender_destroy(entry.tex);
+                }
+            }
+        }
+
+        self.log_allocation();
+    }
+
+    unsafe fn log_allocation(&self) {
+        obs::blog(
+            obs::LOG_INFO as i32,
+            cstr(b"StyledCamera: frame history %d frames at %ux%u (~%.1f MiB VRAM)\n\0"),
+            self.entries.len() as i32,
+            self.width,
+            self.height,
+            self.vram_bytes() as f64 / (1024.0 * 1024.0),
+        );
     }
 
     // Must be called while in graphics context.
@@ -59,44 +136,61 @@ impl FrameHistory {
             }
         }
         self.next = 0;
+        self.width = 0;
+        self.height = 0;
+        self.slot_stamps = Vec::new();
+        self.applied_delay = Duration::ZERO;
+    }
+
+    // The video delay of the last composited frame (zero when it fell back to the live frame).
+    pub(crate) fn applied_delay(&self) -> Duration {
+        self.applied_delay
+    }
+
+    // The frame with exactly this id, if it is still in the ring.
+    pub(crate) unsafe fn texture_for_frame(&self, id: u64) -> Option<*mut obs::gs_texture_t> {
+        let entry = self.entries.iter().find(|e| e.stamp.is_some_and(|s| s.id == id))?;
+        let tex = obs::gs_texrender_get_texture(entry.tex);
+        if tex.is_null() { None } else { Some(tex) }
     }
 
     pub(crate) unsafe fn select_delayed_texture(
-        &self,
+        &mut self,
         fallback: *mut obs::gs_texture_t,
-        delay_ms: f32,
+        target: FrameTarget,
     ) -> *mut obs::gs_texture_t {
+        self.applied_delay = Duration::ZERO;
         if self.entries.is_empty() {
             return fallback;
         }
 
-        let delay_ms = delay_ms.clamp(0.0, 500.0);
-        if delay_ms <= 0.5 {
-            return fallback;
-        }
-
-        let desired = Instant::now() - Duration::from_secs_f32(delay_ms / 1000.0);
-        let mut best_tex: *mut obs::gs_texture_t = std::ptr::null_mut();
-        let mut best_dt: Option<Duration> = None;
-
-        for entry in self.entries.iter() {
-            let Some(t) = entry.time else {
-                continue;
-            };
-            let dt = if t <= desired {
-                desired.duration_since(t)
-            } else {
-                t.duration_since(desired)
-            };
-            if best_dt.map(|b| dt < b).unwrap_or(true) {
-                let tex = obs::gs_texrender_get_texture(entry.tex);
-                if !tex.is_null() {
-                    best_dt = Some(dt);
-                    best_tex = tex;
+        let max_delay = Duration::from_millis(500);
+        let target = match target {
+            FrameTarget::Delay(delay) => {
+                if delay <= Duration::from_micros(500) {
+                    return fallback;
                 }
+                FrameTarget::Delay(delay.min(max_delay))
             }
+            FrameTarget::Id { id, extra_delay } => FrameTarget::Id {
+                id,
+                extra_delay: extra_delay.min(max_delay),
+            },
+        };
+
+        self.slot_stamps.clear();
+        for entry in self.entries.iter() {
+            let has_tex =
+                entry.stamp.is_some() && !obs::gs_texrender_get_texture(entry.tex).is_null();
+            self.slot_stamps.push(if has_tex { entry.stamp } else { None });
         }
 
-        if best_tex.is_null() { fallback } else { best_tex }
+        match select_history_slot(&self.slot_stamps, target, &SystemClock) {
+            Some(i) => {
+                self.applied_delay = slot_delay(&self.slot_stamps, i);
+                obs::gs_texrender_get_texture(self.entries[i].tex)
+            }
+            None => fallback,
+        }
     }
 }
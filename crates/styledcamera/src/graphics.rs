@@ -1,25 +1,62 @@
 use std::os::raw::c_char;
+use std::time::Instant;
 
 use obs_sys as obs;
 
 use crate::constants::*;
 use crate::frame_history::FrameHistory;
 use crate::settings::FilterSettings;
+use crate::shape_image::ShapeImage;
 use crate::util::cstr;
 use styledcamera_core::color::obs_abgr_to_rgba_vec4;
 
+// Segmentation readback ring: frame N is staged while an older slot is mapped, so the
+// render thread never waits on the GPU copy it just queued.
+pub(crate) const SEG_READBACK_SLOTS: usize = 3;
+// Render frames a staged slot must age before it is mapped.
+pub(crate) const SEG_READBACK_LAG: u64 = 2;
+
+pub(crate) struct SegReadbackSlot {
+    pub surf: *mut obs::gs_stagesurf_t,
+    // Capture time of the frame staged into this slot; None while the slot is free.
+    pub capture_time: Option<Instant>,
+    pub frame_id: u64,
+    pub staged_frame: u64,
+}
+
+impl Default for SegReadbackSlot {
+    fn default() -> Self {
+        Self {
+            surf: std::ptr::null_mut(),
+            capture_time: None,
+            frame_id: 0,
+            staged_frame: 0,
+        }
+    }
+}
+
 pub(crate) struct GraphicsState {
     pub effect_downsample: *mut obs::gs_effect_t,
     pub effect_blur: *mut obs::gs_effect_t,
     pub effect_composite: *mut obs::gs_effect_t,
     pub effect_shape: *mut obs::gs_effect_t,
+    pub effect_bg_fit: *mut obs::gs_effect_t,
+    pub effect_bg_generate: *mut obs::gs_effect_t,
+    pub effect_bokeh: *mut obs::gs_effect_t,
+    pub effect_plate: *mut obs::gs_effect_t,
+    pub effect_privacy: *mut obs::gs_effect_t,
+    pub effect_mask_debug: *mut obs::gs_effect_t,
 
     pub downsample_image: *mut obs::gs_eparam_t,
     pub downsample_texel_size: *mut obs::gs_eparam_t,
+    pub downsample_mask_image: *mut obs::gs_eparam_t,
+    pub downsample_mask_threshold: *mut obs::gs_eparam_t,
+    pub downsample_mask_softness: *mut obs::gs_eparam_t,
+    pub downsample_mask_invert: *mut obs::gs_eparam_t,
 
     pub blur_image: *mut obs::gs_eparam_t,
     pub blur_texel_size: *mut obs::gs_eparam_t,
-    pub blur_radius: *mut obs::gs_eparam_t,
+    pub blur_offset: *mut obs::gs_eparam_t,
 
     pub composite_image: *mut obs::gs_eparam_t,
     pub composite_blur_image: *mut obs::gs_eparam_t,
@@ -29,6 +66,75 @@ pub(crate) struct GraphicsState {
     pub composite_mask_invert: *mut obs::gs_eparam_t,
     pub composite_bg_dim: *mut obs::gs_eparam_t,
     pub composite_bg_desat: *mut obs::gs_eparam_t,
+    pub composite_bg_remove: *mut obs::gs_eparam_t,
+    pub composite_blur_weighted: *mut obs::gs_eparam_t,
+    pub composite_blur_near_image: *mut obs::gs_eparam_t,
+    pub composite_depth_image: *mut obs::gs_eparam_t,
+    pub composite_depth_blur: *mut obs::gs_eparam_t,
+    pub composite_depth_falloff: *mut obs::gs_eparam_t,
+    pub composite_texel_size: *mut obs::gs_eparam_t,
+    pub composite_decontam_strength: *mut obs::gs_eparam_t,
+    pub composite_decontam_radius: *mut obs::gs_eparam_t,
+    pub composite_wrap_intensity: *mut obs::gs_eparam_t,
+    pub composite_wrap_radius: *mut obs::gs_eparam_t,
+    pub composite_silhouette_image: *mut obs::gs_eparam_t,
+    pub composite_silhouette: *mut obs::gs_eparam_t,
+    pub composite_silhouette_range: *mut obs::gs_eparam_t,
+    pub composite_outline_width: *mut obs::gs_eparam_t,
+    pub composite_outline_softness: *mut obs::gs_eparam_t,
+    pub composite_outline_color: *mut obs::gs_eparam_t,
+    pub composite_glow_size: *mut obs::gs_eparam_t,
+    pub composite_glow_color: *mut obs::gs_eparam_t,
+    pub composite_sil_shadow_offset: *mut obs::gs_eparam_t,
+    pub composite_sil_shadow_blur: *mut obs::gs_eparam_t,
+    pub composite_sil_shadow_color: *mut obs::gs_eparam_t,
+    pub composite_fg_grade: *mut obs::gs_eparam_t,
+    pub composite_fg_balance: *mut obs::gs_eparam_t,
+    pub composite_bg_grade: *mut obs::gs_eparam_t,
+    pub composite_bg_balance: *mut obs::gs_eparam_t,
+    pub composite_lut_image: *mut obs::gs_eparam_t,
+    pub composite_lut_size: *mut obs::gs_eparam_t,
+    pub composite_lut_domain_min: *mut obs::gs_eparam_t,
+    pub composite_lut_domain_max: *mut obs::gs_eparam_t,
+    pub composite_lut_amount: *mut obs::gs_eparam_t,
+
+    pub bg_fit_image: *mut obs::gs_eparam_t,
+    pub bg_fit_uv_scale: *mut obs::gs_eparam_t,
+    pub bg_fit_uv_offset: *mut obs::gs_eparam_t,
+    pub bg_fit_tile: *mut obs::gs_eparam_t,
+
+    pub bg_gen_size: *mut obs::gs_eparam_t,
+    pub bg_gen_type: *mut obs::gs_eparam_t,
+    pub bg_gen_colors: [*mut obs::gs_eparam_t; 4],
+    pub bg_gen_stop_count: *mut obs::gs_eparam_t,
+    pub bg_gen_angle: *mut obs::gs_eparam_t,
+    pub bg_gen_time: *mut obs::gs_eparam_t,
+
+    pub bokeh_image: *mut obs::gs_eparam_t,
+    pub bokeh_texel_size: *mut obs::gs_eparam_t,
+    pub bokeh_radius: *mut obs::gs_eparam_t,
+    pub bokeh_shape: *mut obs::gs_eparam_t,
+    pub bokeh_highlight: *mut obs::gs_eparam_t,
+
+    pub plate_image: *mut obs::gs_eparam_t,
+    pub plate_prev_image: *mut obs::gs_eparam_t,
+    pub plate_mask_image: *mut obs::gs_eparam_t,
+    pub plate_mask_threshold: *mut obs::gs_eparam_t,
+    pub plate_mask_softness: *mut obs::gs_eparam_t,
+    pub plate_mask_invert: *mut obs::gs_eparam_t,
+    pub plate_blend: *mut obs::gs_eparam_t,
+    pub plate_reset: *mut obs::gs_eparam_t,
+
+    pub privacy_image: *mut obs::gs_eparam_t,
+    pub privacy_block_size: *mut obs::gs_eparam_t,
+    pub privacy_style: *mut obs::gs_eparam_t,
+
+    pub mask_debug_image: *mut obs::gs_eparam_t,
+    pub mask_debug_mask_image: *mut obs::gs_eparam_t,
+    pub mask_debug_threshold: *mut obs::gs_eparam_t,
+    pub mask_debug_softness: *mut obs::gs_eparam_t,
+    pub mask_debug_invert: *mut obs::gs_eparam_t,
+    pub mask_debug_view: *mut obs::gs_eparam_t,
 
     pub shape_image: *mut obs::gs_eparam_t,
     pub shape_size: *mut obs::gs_eparam_t,
@@ -42,17 +148,44 @@ pub(crate) struct GraphicsState {
     pub shape_shadow_offset: *mut obs::gs_eparam_t,
     pub shape_shadow_blur: *mut obs::gs_eparam_t,
     pub shape_shadow_color: *mut obs::gs_eparam_t,
+    pub shape_image_premultiplied: *mut obs::gs_eparam_t,
+    pub shape_exponent: *mut obs::gs_eparam_t,
+    pub shape_sides: *mut obs::gs_eparam_t,
+    pub shape_inner_radius: *mut obs::gs_eparam_t,
+    pub shape_rotation: *mut obs::gs_eparam_t,
+    pub shape_field: *mut obs::gs_eparam_t,
+    pub shape_field_size: *mut obs::gs_eparam_t,
 
     pub tex_down: *mut obs::gs_texrender_t,
-    pub tex_ping: *mut obs::gs_texrender_t,
-    pub tex_pong: *mut obs::gs_texrender_t,
+    // Blur pyramid levels (see ensure_blur_levels); index i is one halving below i - 1.
+    pub tex_blur_down: Vec<*mut obs::gs_texrender_t>,
+    pub tex_blur_up: Vec<*mut obs::gs_texrender_t>,
+    // Per-frame level sizes for the pyramid, kept to avoid allocating while rendering.
+    pub blur_level_sizes: Vec<(u32, u32)>,
     pub tex_comp: *mut obs::gs_texrender_t,
     pub tex_seg: *mut obs::gs_texrender_t,
+    pub tex_bg: *mut obs::gs_texrender_t,
+    pub tex_bg_source: *mut obs::gs_texrender_t,
+    pub tex_bokeh: *mut obs::gs_texrender_t,
+    // Clean background plate, ping-ponged between the two (full float: the running average
+    // moves in steps below half float precision over long history windows).
+    pub tex_plate: [*mut obs::gs_texrender_t; 2],
+    pub plate_index: usize,
+    // False until the plate has learned from a frame at the current size.
+    pub plate_valid: bool,
+    pub plate_size: (u32, u32),
 
     pub mask_tex: *mut obs::gs_texture_t,
     pub mask_w: u32,
     pub mask_h: u32,
-    pub stage_seg: *mut obs::gs_stagesurf_t,
+    // Distance from the person (depth-graded blur); created with the first distance field.
+    pub depth_tex: *mut obs::gs_texture_t,
+    pub depth_size: (u32, u32),
+    // Signed distance to the silhouette (outline, glow, silhouette shadow); same lifecycle.
+    pub silhouette_tex: *mut obs::gs_texture_t,
+    pub silhouette_size: (u32, u32),
+    pub stage_seg: [SegReadbackSlot; SEG_READBACK_SLOTS],
+    pub stage_seg_frame: u64,
 }
 
 impl Default for GraphicsState {
@@ -62,13 +195,23 @@ impl Default for GraphicsState {
             effect_blur: std::ptr::null_mut(),
             effect_composite: std::ptr::null_mut(),
             effect_shape: std::ptr::null_mut(),
+            effect_bg_fit: std::ptr::null_mut(),
+            effect_bg_generate: std::ptr::null_mut(),
+            effect_bokeh: std::ptr::null_mut(),
+            effect_plate: std::ptr::null_mut(),
+            effect_privacy: std::ptr::null_mut(),
+            effect_mask_debug: std::ptr::null_mut(),
 
             downsample_image: std::ptr::null_mut(),
             downsample_texel_size: std::ptr::null_mut(),
+            downsample_mask_image: std::ptr::null_mut(),
+            downsample_mask_threshold: std::ptr::null_mut(),
+            downsample_mask_softness: std::ptr::null_mut(),
+            downsample_mask_invert: std::ptr::null_mut(),
 
             blur_image: std::ptr::null_mut(),
             blur_texel_size: std::ptr::null_mut(),
-            blur_radius: std::ptr::null_mut(),
+            blur_offset: std::ptr::null_mut(),
 
             composite_image: std::ptr::null_mut(),
             composite_blur_image: std::ptr::null_mut(),
@@ -78,6 +221,75 @@ impl Default for GraphicsState {
             composite_mask_invert: std::ptr::null_mut(),
             composite_bg_dim: std::ptr::null_mut(),
             composite_bg_desat: std::ptr::null_mut(),
+            composite_bg_remove: std::ptr::null_mut(),
+            composite_blur_weighted: std::ptr::null_mut(),
+            composite_blur_near_image: std::ptr::null_mut(),
+            composite_depth_image: std::ptr::null_mut(),
+            composite_depth_blur: std::ptr::null_mut(),
+            composite_depth_falloff: std::ptr::null_mut(),
+            composite_texel_size: std::ptr::null_mut(),
+            composite_decontam_strength: std::ptr::null_mut(),
+            composite_decontam_radius: std::ptr::null_mut(),
+            composite_wrap_intensity: std::ptr::null_mut(),
+            composite_wrap_radius: std::ptr::null_mut(),
+            composite_silhouette_image: std::ptr::null_mut(),
+            composite_silhouette: std::ptr::null_mut(),
+            composite_silhouette_range: std::ptr::null_mut(),
+            composite_outline_width: std::ptr::null_mut(),
+            composite_outline_softness: std::ptr::null_mut(),
+            composite_outline_color: std::ptr::null_mut(),
+            composite_glow_size: std::ptr::null_mut(),
+            composite_glow_color: std::ptr::null_mut(),
+            composite_sil_shadow_offset: std::ptr::null_mut(),
+            composite_sil_shadow_blur: std::ptr::null_mut(),
+            composite_sil_shadow_color: std::ptr::null_mut(),
+            composite_fg_grade: std::ptr::null_mut(),
+            composite_fg_balance: std::ptr::null_mut(),
+            composite_bg_grade: std::ptr::null_mut(),
+            composite_bg_balance: std::ptr::null_mut(),
+            composite_lut_image: std::ptr::null_mut(),
+            composite_lut_size: std::ptr::null_mut(),
+            composite_lut_domain_min: std::ptr::null_mut(),
+            composite_lut_domain_max: std::ptr::null_mut(),
+            composite_lut_amount: std::ptr::null_mut(),
+
+            bg_fit_image: std::ptr::null_mut(),
+            bg_fit_uv_scale: std::ptr::null_mut(),
+            bg_fit_uv_offset: std::ptr::null_mut(),
+            bg_fit_tile: std::ptr::null_mut(),
+
+            bg_gen_size: std::ptr::null_mut(),
+            bg_gen_type: std::ptr::null_mut(),
+            bg_gen_colors: [std::ptr::null_mut(); 4],
+            bg_gen_stop_count: std::ptr::null_mut(),
+            bg_gen_angle: std::ptr::null_mut(),
+            bg_gen_time: std::ptr::null_mut(),
+
+            bokeh_image: std::ptr::null_mut(),
+            bokeh_texel_size: std::ptr::null_mut(),
+            bokeh_radius: std::ptr::null_mut(),
+            bokeh_shape: std::ptr::null_mut(),
+            bokeh_highlight: std::ptr::null_mut(),
+
+            plate_image: std::ptr::null_mut(),
+            plate_prev_image: std::ptr::null_mut(),
+            plate_mask_image: std::ptr::null_mut(),
+            plate_mask_threshold: std::ptr::null_mut(),
+            plate_mask_softness: std::ptr::null_mut(),
+            plate_mask_invert: std::ptr::null_mut(),
+            plate_blend: std::ptr::null_mut(),
+            plate_reset: std::ptr::null_mut(),
+
+            privacy_image: std::ptr::null_mut(),
+            privacy_block_size: std::ptr::null_mut(),
+            privacy_style: std::ptr::null_mut(),
+
+            mask_debug_image: std::ptr::null_mut(),
+            mask_debug_mask_image: std::ptr::null_mut(),
+            mask_debug_threshold: std::ptr::null_mut(),
+            mask_debug_softness: std::ptr::null_mut(),
+            mask_debug_invert: std::ptr::null_mut(),
+            mask_debug_view: std::ptr::null_mut(),
 
             shape_image: std::ptr::null_mut(),
             shape_size: std::ptr::null_mut(),
@@ -91,50 +303,74 @@ impl Default for GraphicsState {
             shape_shadow_offset: std::ptr::null_mut(),
             shape_shadow_blur: std::ptr::null_mut(),
             shape_shadow_color: std::ptr::null_mut(),
+            shape_image_premultiplied: std::ptr::null_mut(),
+            shape_exponent: std::ptr::null_mut(),
+            shape_sides: std::ptr::null_mut(),
+            shape_inner_radius: std::ptr::null_mut(),
+            shape_rotation: std::ptr::null_mut(),
+            shape_field: std::ptr::null_mut(),
+            shape_field_size: std::ptr::null_mut(),
 
             tex_down: std::ptr::null_mut(),
-            tex_ping: std::ptr::null_mut(),
-            tex_pong: std::ptr::null_mut(),
+            tex_blur_down: Vec::new(),
+            tex_blur_up: Vec::new(),
+            blur_level_sizes: Vec::new(),
             tex_comp: std::ptr::null_mut(),
             tex_seg: std::ptr::null_mut(),
+            tex_bg: std::ptr::null_mut(),
+            tex_bg_source: std::ptr::null_mut(),
+            tex_bokeh: std::ptr::null_mut(),
+            tex_plate: [std::ptr::null_mut(); 2],
+            plate_index: 0,
+            plate_valid: false,
+            plate_size: (0, 0),
 
             mask_tex: std::ptr::null_mut(),
             mask_w: 0,
             mask_h: 0,
-            stage_seg: std::ptr::null_mut(),
+            depth_tex: std::ptr::null_mut(),
+            depth_size: (0, 0),
+            silhouette_tex: std::ptr::null_mut(),
+            silhouette_size: (0, 0),
+            stage_seg: Default::default(),
+            stage_seg_frame: 0,
         }
     }
 }
 
 impl GraphicsState {
-    pub(crate) unsafe fn ensure(&mut self) -> bool {
-        if !self.effect_downsample.is_null()
+    // Core effects and targets every frame needs; optional features load theirs on first use
+    // (see ensure_bokeh and friends), so a broken optional shader only disables that feature.
+    fn is_ready(&self) -> bool {
+        !self.effect_downsample.is_null()
             && !self.effect_blur.is_null()
             && !self.effect_composite.is_null()
             && !self.effect_shape.is_null()
+            && !self.effect_bg_fit.is_null()
+            && !self.effect_bg_generate.is_null()
             && !self.tex_down.is_null()
-            && !self.tex_ping.is_null()
-            && !self.tex_pong.is_null()
             && !self.tex_comp.is_null()
             && !self.tex_seg.is_null()
+            && !self.tex_bg.is_null()
+            && !self.tex_bg_source.is_null()
             && !self.mask_tex.is_null()
-            && !self.stage_seg.is_null()
-        {
+            && self.stage_seg.iter().all(|slot| !slot.surf.is_null())
+    }
+
+    pub(crate) unsafe fn ensure(&mut self) -> bool {
+        if self.is_ready() {
             return true;
         }
 
         self.init();
-        !self.effect_downsample.is_null()
-            && !self.effect_blur.is_null()
-            && !self.effect_composite.is_null()
-            && !self.effect_shape.is_null()
-            && !self.tex_down.is_null()
-            && !self.tex_ping.is_null()
-            && !self.tex_pong.is_null()
-            && !self.tex_comp.is_null()
-            && !self.tex_seg.is_null()
-            && !self.mask_tex.is_null()
-            && !self.stage_seg.is_null()
+        self.is_ready()
+    }
+
+    // Drops frames still in flight (e.g. when segmentation is switched off).
+    pub(crate) fn reset_seg_readback(&mut self) {
+        for slot in self.stage_seg.iter_mut() {
+            slot.capture_time = None;
+        }
     }
 
     pub(crate) unsafe fn init(&mut self) {
@@ -149,6 +385,22 @@ impl GraphicsState {
                     self.effect_downsample,
                     cstr(b"texel_size\0"),
                 );
+                self.downsample_mask_image = obs::gs_effect_get_param_by_name(
+                    self.effect_downsample,
+                    cstr(b"mask_image\0"),
+                );
+                self.downsample_mask_threshold = obs::gs_effect_get_param_by_name(
+                    self.effect_downsample,
+                    cstr(b"mask_threshold\0"),
+                );
+                self.downsample_mask_softness = obs::gs_effect_get_param_by_name(
+                    self.effect_downsample,
+                    cstr(b"mask_softness\0"),
+                );
+                self.downsample_mask_invert = obs::gs_effect_get_param_by_name(
+                    self.effect_downsample,
+                    cstr(b"mask_invert\0"),
+                );
             }
         }
 
@@ -159,8 +411,8 @@ impl GraphicsState {
                     obs::gs_effect_get_param_by_name(self.effect_blur, cstr(b"image\0"));
                 self.blur_texel_size =
                     obs::gs_effect_get_param_by_name(self.effect_blur, cstr(b"texel_size\0"));
-                self.blur_radius =
-                    obs::gs_effect_get_param_by_name(self.effect_blur, cstr(b"blur_radius\0"));
+                self.blur_offset =
+                    obs::gs_effect_get_param_by_name(self.effect_blur, cstr(b"blur_offset\0"));
             }
         }
 
@@ -189,6 +441,80 @@ impl GraphicsState {
                     obs::gs_effect_get_param_by_name(self.effect_composite, cstr(b"bg_dim\0"));
                 self.composite_bg_desat =
                     obs::gs_effect_get_param_by_name(self.effect_composite, cstr(b"bg_desat\0"));
+                self.composite_bg_remove =
+                    obs::gs_effect_get_param_by_name(self.effect_composite, cstr(b"bg_remove\0"));
+                self.composite_blur_weighted = obs::gs_effect_get_param_by_name(
+                    self.effect_composite,
+                    cstr(b"blur_weighted\0"),
+                );
+                self.composite_blur_near_image = obs::gs_effect_get_param_by_name(
+                    self.effect_composite,
+                    cstr(b"blur_near_image\0"),
+                );
+                self.composite_depth_image = obs::gs_effect_get_param_by_name(
+                    self.effect_composite,
+                    cstr(b"depth_image\0"),
+                );
+                self.composite_depth_blur =
+                    obs::gs_effect_get_param_by_name(self.effect_composite, cstr(b"depth_blur\0"));
+                self.composite_depth_falloff = obs::gs_effect_get_param_by_name(
+                    self.effect_composite,
+                    cstr(b"depth_falloff\0"),
+                );
+                self.composite_texel_size =
+                    obs::gs_effect_get_param_by_name(self.effect_composite, cstr(b"texel_size\0"));
+                self.composite_decontam_strength = obs::gs_effect_get_param_by_name(
+                    self.effect_composite,
+                    cstr(b"decontam_strength\0"),
+                );
+                self.composite_decontam_radius = obs::gs_effect_get_param_by_name(
+                    self.effect_composite,
+                    cstr(b"decontam_radius\0"),
+                );
+                self.composite_wrap_intensity = obs::gs_effect_get_param_by_name(
+                    self.effect_composite,
+                    cstr(b"wrap_intensity\0"),
+                );
+                self.composite_wrap_radius =
+                    obs::gs_effect_get_param_by_name(self.effect_composite, cstr(b"wrap_radius\0"));
+                self.composite_silhouette_image = obs::gs_effect_get_param_by_name(
+                    self.effect_composite,
+                    cstr(b"silhouette_image\0"),
+                );
+                self.composite_silhouette =
+                    obs::gs_effect_get_param_by_name(self.effect_composite, cstr(b"silhouette\0"));
+                self.composite_silhouette_range = obs::gs_effect_get_param_by_name(
+                    self.effect_composite,
+                    cstr(b"silhouette_range\0"),
+                );
+                self.composite_outline_width = obs::gs_effect_get_param_by_name(
+                    self.effect_composite,
+                    cstr(b"outline_width\0"),
+                );
+                self.composite_outline_softness = obs::gs_effect_get_param_by_name(
+                    self.effect_composite,
+                    cstr(b"outline_softness\0"),
+                );
+                self.composite_outline_color = obs::gs_effect_get_param_by_name(
+                    self.effect_composite,
+                    cstr(b"outline_color\0"),
+                );
+                self.composite_glow_size =
+                    obs::gs_effect_get_param_by_name(self.effect_composite, cstr(b"glow_size\0"));
+                self.composite_glow_color =
+                    obs::gs_effect_get_param_by_name(self.effect_composite, cstr(b"glow_color\0"));
+                self.composite_sil_shadow_offset = obs::gs_effect_get_param_by_name(
+                    self.effect_composite,
+                    cstr(b"sil_shadow_offset\0"),
+                );
+                self.composite_sil_shadow_blur = obs::gs_effect_get_param_by_name(
+                    self.effect_composite,
+                    cstr(b"sil_shadow_blur\0"),
+                );
+                self.composite_sil_shadow_color = obs::gs_effect_get_param_by_name(
+                    self.effect_composite,
+                    cstr(b"sil_shadow_color\0"),
+                );
             }
         }
 
@@ -197,6 +523,28 @@ impl GraphicsState {
             if !self.effect_shape.is_null() {
                 self.shape_image =
                     obs::gs_effect_get_param_by_name(self.effect_shape, cstr(b"image\0"));
+                self.composite_fg_grade =
+                    obs::gs_effect_get_param_by_name(self.effect_composite, cstr(b"fg_grade\0"));
+                self.composite_fg_balance =
+                    obs::gs_effect_get_param_by_name(self.effect_composite, cstr(b"fg_balance\0"));
+                self.composite_bg_grade =
+                    obs::gs_effect_get_param_by_name(self.effect_composite, cstr(b"bg_grade\0"));
+                self.composite_bg_balance =
+                    obs::gs_effect_get_param_by_name(self.effect_composite, cstr(b"bg_balance\0"));
+                self.composite_lut_image =
+                    obs::gs_effect_get_param_by_name(self.effect_composite, cstr(b"lut_image\0"));
+                self.composite_lut_size =
+                    obs::gs_effect_get_param_by_name(self.effect_composite, cstr(b"lut_size\0"));
+                self.composite_lut_domain_min = obs::gs_effect_get_param_by_name(
+                    self.effect_composite,
+                    cstr(b"lut_domain_min\0"),
+                );
+                self.composite_lut_domain_max = obs::gs_effect_get_param_by_name(
+                    self.effect_composite,
+                    cstr(b"lut_domain_max\0"),
+                );
+                self.composite_lut_amount =
+                    obs::gs_effect_get_param_by_name(self.effect_composite, cstr(b"lut_amount\0"));
                 self.shape_size = obs::gs_effect_get_param_by_name(self.effect_shape, cstr(b"size\0"));
                 self.shape_type_param =
                     obs::gs_effect_get_param_by_name(self.effect_shape, cstr(b"shape_type\0"));
@@ -218,6 +566,59 @@ impl GraphicsState {
                     obs::gs_effect_get_param_by_name(self.effect_shape, cstr(b"shadow_blur\0"));
                 self.shape_shadow_color =
                     obs::gs_effect_get_param_by_name(self.effect_shape, cstr(b"shadow_color\0"));
+                self.shape_image_premultiplied = obs::gs_effect_get_param_by_name(
+                    self.effect_shape,
+                    cstr(b"image_premultiplied\0"),
+                );
+                self.shape_exponent =
+                    obs::gs_effect_get_param_by_name(self.effect_shape, cstr(b"shape_exponent\0"));
+                self.shape_sides =
+                    obs::gs_effect_get_param_by_name(self.effect_shape, cstr(b"shape_sides\0"));
+                self.shape_inner_radius = obs::gs_effect_get_param_by_name(
+                    self.effect_shape,
+                    cstr(b"shape_inner_radius\0"),
+                );
+                self.shape_rotation =
+                    obs::gs_effect_get_param_by_name(self.effect_shape, cstr(b"shape_rotation\0"));
+                self.shape_field =
+                    obs::gs_effect_get_param_by_name(self.effect_shape, cstr(b"shape_field\0"));
+                self.shape_field_size = obs::gs_effect_get_param_by_name(
+                    self.effect_shape,
+                    cstr(b"shape_field_size\0"),
+                );
+            }
+        }
+
+        if self.effect_bg_fit.is_null() {
+            self.effect_bg_fit = load_effect(EFFECT_BACKGROUND_FIT);
+            if !self.effect_bg_fit.is_null() {
+                self.bg_fit_image =
+                    obs::gs_effect_get_param_by_name(self.effect_bg_fit, cstr(b"image\0"));
+                self.bg_fit_uv_scale =
+                    obs::gs_effect_get_param_by_name(self.effect_bg_fit, cstr(b"uv_scale\0"));
+                self.bg_fit_uv_offset =
+                    obs::gs_effect_get_param_by_name(self.effect_bg_fit, cstr(b"uv_offset\0"));
+                self.bg_fit_tile =
+                    obs::gs_effect_get_param_by_name(self.effect_bg_fit, cstr(b"tile\0"));
+            }
+        }
+
+        if self.effect_bg_generate.is_null() {
+            self.effect_bg_generate = load_effect(EFFECT_BACKGROUND_GENERATE);
+            if !self.effect_bg_generate.is_null() {
+                let effect = self.effect_bg_generate;
+                self.bg_gen_size = obs::gs_effect_get_param_by_name(effect, cstr(b"size\0"));
+                self.bg_gen_type = obs::gs_effect_get_param_by_name(effect, cstr(b"gen_type\0"));
+                self.bg_gen_colors = [
+                    obs::gs_effect_get_param_by_name(effect, cstr(b"color0\0")),
+                    obs::gs_effect_get_param_by_name(effect, cstr(b"color1\0")),
+                    obs::gs_effect_get_param_by_name(effect, cstr(b"color2\0")),
+                    obs::gs_effect_get_param_by_name(effect, cstr(b"color3\0")),
+                ];
+                self.bg_gen_stop_count =
+                    obs::gs_effect_get_param_by_name(effect, cstr(b"stop_count\0"));
+                self.bg_gen_angle = obs::gs_effect_get_param_by_name(effect, cstr(b"angle\0"));
+                self.bg_gen_time = obs::gs_effect_get_param_by_name(effect, cstr(b"time\0"));
             }
         }
 
@@ -227,31 +628,30 @@ impl GraphicsState {
                 obs::gs_zstencil_format_GS_ZS_NONE,
             );
         }
-        if self.tex_ping.is_null() {
-            self.tex_ping = obs::gs_texrender_create(
+        if self.tex_comp.is_null() {
+            self.tex_comp = obs::gs_texrender_create(
                 obs::gs_color_format_GS_RGBA,
                 obs::gs_zstencil_format_GS_ZS_NONE,
             );
         }
-        if self.tex_pong.is_null() {
-            self.tex_pong = obs::gs_texrender_create(
+        if self.tex_seg.is_null() {
+            self.tex_seg = obs::gs_texrender_create(
                 obs::gs_color_format_GS_RGBA,
                 obs::gs_zstencil_format_GS_ZS_NONE,
             );
         }
-        if self.tex_comp.is_null() {
-            self.tex_comp = obs::gs_texrender_create(
+        if self.tex_bg.is_null() {
+            self.tex_bg = obs::gs_texrender_create(
                 obs::gs_color_format_GS_RGBA,
                 obs::gs_zstencil_format_GS_ZS_NONE,
             );
         }
-        if self.tex_seg.is_null() {
-            self.tex_seg = obs::gs_texrender_create(
+        if self.tex_bg_source.is_null() {
+            self.tex_bg_source = obs::gs_texrender_create(
                 obs::gs_color_format_GS_RGBA,
                 obs::gs_zstencil_format_GS_ZS_NONE,
             );
         }
-
         if self.mask_tex.is_null() {
             let mask_w = 256u32;
             let mask_h = 256u32;
@@ -270,13 +670,134 @@ impl GraphicsState {
             self.mask_h = mask_h;
         }
 
-        if self.stage_seg.is_null() {
-            self.stage_seg = obs::gs_stagesurface_create(256, 256, obs::gs_color_format_GS_RGBA);
+        for slot in self.stage_seg.iter_mut() {
+            if slot.surf.is_null() {
+                slot.surf = obs::gs_stagesurface_create(256, 256, obs::gs_color_format_GS_RGBA);
+                slot.capture_time = None;
+            }
         }
 
         obs::obs_leave_graphics();
     }
 
+    // Grows the blur pyramid pool to `levels` down/up texrenders. The pool never shrinks, so
+    // changing the intensity or quality doesn't reallocate.
+    // Must be called while in graphics context.
+    pub(crate) unsafe fn ensure_blur_levels(&mut self, levels: usize) -> bool {
+        for pool in [&mut self.tex_blur_down, &mut self.tex_blur_up] {
+            while pool.len() < levels {
+                let texrender = obs::gs_texrender_create(
+                    obs::gs_color_format_GS_RGBA,
+                    obs::gs_zstencil_format_GS_ZS_NONE,
+                );
+                if texrender.is_null() {
+                    return false;
+                }
+                pool.push(texrender);
+            }
+        }
+        // Level 0 plus one size per pyramid level.
+        self.blur_level_sizes.reserve((levels + 1).saturating_sub(self.blur_level_sizes.len()));
+        true
+    }
+
+    // Lens blur effect and target, loaded on first use.
+    // Must be called while in graphics context.
+    pub(crate) unsafe fn ensure_bokeh(&mut self) -> bool {
+        if self.effect_bokeh.is_null() {
+            self.effect_bokeh = load_effect(EFFECT_BOKEH_BLUR);
+            if !self.effect_bokeh.is_null() {
+                let effect = self.effect_bokeh;
+                self.bokeh_image = obs::gs_effect_get_param_by_name(effect, cstr(b"image\0"));
+                self.bokeh_texel_size =
+                    obs::gs_effect_get_param_by_name(effect, cstr(b"texel_size\0"));
+                self.bokeh_radius = obs::gs_effect_get_param_by_name(effect, cstr(b"radius\0"));
+                self.bokeh_shape = obs::gs_effect_get_param_by_name(effect, cstr(b"shape\0"));
+                self.bokeh_highlight =
+                    obs::gs_effect_get_param_by_name(effect, cstr(b"highlight\0"));
+            }
+        }
+        if self.tex_bokeh.is_null() {
+            self.tex_bokeh = obs::gs_texrender_create(
+                obs::gs_color_format_GS_RGBA,
+                obs::gs_zstencil_format_GS_ZS_NONE,
+            );
+        }
+        !self.effect_bokeh.is_null() && !self.tex_bokeh.is_null()
+    }
+
+    // Clean plate effect and its ping-pong targets, loaded on first use.
+    // Must be called while in graphics context.
+    pub(crate) unsafe fn ensure_plate(&mut self) -> bool {
+        if self.effect_plate.is_null() {
+            self.effect_plate = load_effect(EFFECT_BACKGROUND_PLATE);
+            if !self.effect_plate.is_null() {
+                let effect = self.effect_plate;
+                self.plate_image = obs::gs_effect_get_param_by_name(effect, cstr(b"image\0"));
+                self.plate_prev_image =
+                    obs::gs_effect_get_param_by_name(effect, cstr(b"plate_image\0"));
+                self.plate_mask_image =
+                    obs::gs_effect_get_param_by_name(effect, cstr(b"mask_image\0"));
+                self.plate_mask_threshold =
+                    obs::gs_effect_get_param_by_name(effect, cstr(b"mask_threshold\0"));
+                self.plate_mask_softness =
+                    obs::gs_effect_get_param_by_name(effect, cstr(b"mask_softness\0"));
+                self.plate_mask_invert =
+                    obs::gs_effect_get_param_by_name(effect, cstr(b"mask_invert\0"));
+                self.plate_blend = obs::gs_effect_get_param_by_name(effect, cstr(b"blend\0"));
+                self.plate_reset = obs::gs_effect_get_param_by_name(effect, cstr(b"plate_reset\0"));
+            }
+        }
+        for texrender in self.tex_plate.iter_mut() {
+            if texrender.is_null() {
+                *texrender = obs::gs_texrender_create(
+                    obs::gs_color_format_GS_RGBA32F,
+                    obs::gs_zstencil_format_GS_ZS_NONE,
+                );
+                self.plate_valid = false;
+            }
+        }
+        !self.effect_plate.is_null() && self.tex_plate.iter().all(|t| !t.is_null())
+    }
+
+    // Privacy background effect, loaded on first use.
+    // Must be called while in graphics context.
+    pub(crate) unsafe fn ensure_privacy(&mut self) -> bool {
+        if self.effect_privacy.is_null() {
+            self.effect_privacy = load_effect(EFFECT_BACKGROUND_PRIVACY);
+            if !self.effect_privacy.is_null() {
+                let effect = self.effect_privacy;
+                self.privacy_image = obs::gs_effect_get_param_by_name(effect, cstr(b"image\0"));
+                self.privacy_block_size =
+                    obs::gs_effect_get_param_by_name(effect, cstr(b"block_size\0"));
+                self.privacy_style = obs::gs_effect_get_param_by_name(effect, cstr(b"style\0"));
+            }
+        }
+        !self.effect_privacy.is_null()
+    }
+
+    // Mask debug effect, loaded on first use.
+    // Must be called while in graphics context.
+    pub(crate) unsafe fn ensure_mask_debug(&mut self) -> bool {
+        if self.effect_mask_debug.is_null() {
+            self.effect_mask_debug = load_effect(EFFECT_MASK_DEBUG);
+            if !self.effect_mask_debug.is_null() {
+                let effect = self.effect_mask_debug;
+                self.mask_debug_image = obs::gs_effect_get_param_by_name(effect, cstr(b"image\0"));
+                self.mask_debug_mask_image =
+                    obs::gs_effect_get_param_by_name(effect, cstr(b"mask_image\0"));
+                self.mask_debug_threshold =
+                    obs::gs_effect_get_param_by_name(effect, cstr(b"mask_threshold\0"));
+                self.mask_debug_softness =
+                    obs::gs_effect_get_param_by_name(effect, cstr(b"mask_softness\0"));
+                self.mask_debug_invert =
+                    obs::gs_effect_get_param_by_name(effect, cstr(b"mask_invert\0"));
+                self.mask_debug_view = obs::gs_effect_get_param_by_name(effect, cstr(b"view\0"));
+            }
+        }
+        !self.effect_mask_debug.is_null()
+    }
+
     pub(crate) unsafe fn destroy(&mut self, frame_history: &mut FrameHistory) {
         obs::obs_enter_graphics();
 
@@ -287,10 +808,24 @@ impl GraphicsState {
         self.mask_w = 0;
         self.mask_h = 0;
 
-        if !self.stage_seg.is_null() {
-            obs::gs_stagesurface_destroy(self.stage_seg);
-            self.stage_seg = std::ptr::null_mut();
+        if !self.depth_tex.is_null() {
+            obs::gs_texture_destroy(self.depth_tex);
+            self.depth_tex = std::ptr::null_mut();
+        }
+        self.depth_size = (0, 0);
+        if !self.silhouette_tex.is_null() {
+            obs::gs_texture_destroy(self.silhouette_tex);
+            self.silhouette_tex = std::ptr::null_mut();
+        }
+        self.silhouette_size = (0, 0);
+
+        for slot in self.stage_seg.iter_mut() {
+            if !slot.surf.is_null() {
+                obs::gs_stagesurface_destroy(slot.surf);
+            }
+            *slot = SegReadbackSlot::default();
         }
+        self.stage_seg_frame = 0;
 
         frame_history.destroy();
 
@@ -298,14 +833,12 @@ impl GraphicsState {
             obs::gs_texrender_destroy(self.tex_down);
             self.tex_down = std::ptr::null_mut();
         }
-        if !self.tex_ping.is_null() {
-            obs::gs_texrender_destroy(self.tex_ping);
-            self.tex_ping = std::ptr::null_mut();
-        }
-        if !self.tex_pong.is_null() {
-            obs::gs_texrender_destroy(self.tex_pong);
-            self.tex_pong = std::ptr::null_mut();
+        for texrender in self.tex_blur_down.drain(..).chain(self.tex_blur_up.drain(..)) {
+            if !texrender.is_null() {
+                obs::gs_texrender_destroy(texrender);
+            }
         }
+        self.blur_level_sizes = Vec::new();
         if !self.tex_comp.is_null() {
             obs::gs_texrender_destroy(self.tex_comp);
             self.tex_comp = std::ptr::null_mut();
@@ -314,19 +847,44 @@ impl GraphicsState {
             obs::gs_texrender_destroy(self.tex_seg);
             self.tex_seg = std::ptr::null_mut();
         }
+        if !self.tex_bg.is_null() {
+            obs::gs_texrender_destroy(self.tex_bg);
+            self.tex_bg = std::ptr::null_mut();
+        }
+        if !self.tex_bg_source.is_null() {
+            obs::gs_texrender_destroy(self.tex_bg_source);
+            self.tex_bg_source = std::ptr::null_mut();
+        }
+        if !self.tex_bokeh.is_null() {
+            obs::gs_texrender_destroy(self.tex_bokeh);
+            self.tex_bokeh = std::ptr::null_mut();
+        }
+        for texrender in self.tex_plate.iter_mut() {
+            if !texrender.is_null() {
+                obs::gs_texrender_destroy(*texrender);
+                *texrender = std::ptr::null_mut();
+            }
+        }
+        self.plate_index = 0;
+        self.plate_valid = false;
+        self.plate_size = (0, 0);
 
         if !self.effect_downsample.is_null() {
             obs::gs_effect_destroy(self.effect_downsample);
             self.effect_downsample = std::ptr::null_mut();
             self.downsample_image = std::ptr::null_mut();
             self.downsample_texel_size = std::ptr::null_mut();
+            self.downsample_mask_image = std::ptr::null_mut();
+            self.downsample_mask_threshold = std::ptr::null_mut();
+            self.downsample_mask_softness = std::ptr::null_mut();
+            self.downsample_mask_invert = std::ptr::null_mut();
         }
         if !self.effect_blur.is_null() {
             obs::gs_effect_destroy(self.effect_blur);
             self.effect_blur = std::ptr::null_mut();
             self.blur_image = std::ptr::null_mut();
             self.blur_texel_size = std::ptr::null_mut();
-            self.blur_radius = std::ptr::null_mut();
+            self.blur_offset = std::ptr::null_mut();
         }
         if !self.effect_composite.is_null() {
             obs::gs_effect_destroy(self.effect_composite);
@@ -339,6 +897,37 @@ impl GraphicsState {
             self.composite_mask_invert = std::ptr::null_mut();
             self.composite_bg_dim = std::ptr::null_mut();
             self.composite_bg_desat = std::ptr::null_mut();
+            self.composite_bg_remove = std::ptr::null_mut();
+            self.composite_blur_weighted = std::ptr::null_mut();
+            self.composite_blur_near_image = std::ptr::null_mut();
+            self.composite_depth_image = std::ptr::null_mut();
+            self.composite_depth_blur = std::ptr::null_mut();
+            self.composite_depth_falloff = std::ptr::null_mut();
+            self.composite_texel_size = std::ptr::null_mut();
+            self.composite_decontam_strength = std::ptr::null_mut();
+            self.composite_decontam_radius = std::ptr::null_mut();
+            self.composite_wrap_intensity = std::ptr::null_mut();
+            self.composite_wrap_radius = std::ptr::null_mut();
+            self.composite_silhouette_image = std::ptr::null_mut();
+            self.composite_silhouette = std::ptr::null_mut();
+            self.composite_silhouette_range = std::ptr::null_mut();
+            self.composite_outline_width = std::ptr::null_mut();
+            self.composite_outline_softness = std::ptr::null_mut();
+            self.composite_outline_color = std::ptr::null_mut();
+            self.composite_glow_size = std::ptr::null_mut();
+            self.composite_glow_color = std::ptr::null_mut();
+            self.composite_sil_shadow_offset = std::ptr::null_mut();
+            self.composite_sil_shadow_blur = std::ptr::null_mut();
+            self.composite_sil_shadow_color = std::ptr::null_mut();
+            self.composite_fg_grade = std::ptr::null_mut();
+            self.composite_fg_balance = std::ptr::null_mut();
+            self.composite_bg_grade = std::ptr::null_mut();
+            self.composite_bg_balance = std::ptr::null_mut();
+            self.composite_lut_image = std::ptr::null_mut();
+            self.composite_lut_size = std::ptr::null_mut();
+            self.composite_lut_domain_min = std::ptr::null_mut();
+            self.composite_lut_domain_max = std::ptr::null_mut();
+            self.composite_lut_amount = std::ptr::null_mut();
         }
         if !self.effect_shape.is_null() {
             obs::gs_effect_destroy(self.effect_shape);
@@ -355,6 +944,69 @@ impl GraphicsState {
             self.shape_shadow_offset = std::ptr::null_mut();
             self.shape_shadow_blur = std::ptr::null_mut();
             self.shape_shadow_color = std::ptr::null_mut();
+            self.shape_image_premultiplied = std::ptr::null_mut();
+            self.shape_exponent = std::ptr::null_mut();
+            self.shape_sides = std::ptr::null_mut();
+            self.shape_inner_radius = std::ptr::null_mut();
+            self.shape_rotation = std::ptr::null_mut();
+            self.shape_field = std::ptr::null_mut();
+            self.shape_field_size = std::ptr::null_mut();
+        }
+        if !self.effect_bg_fit.is_null() {
+            obs::gs_effect_destroy(self.effect_bg_fit);
+            self.effect_bg_fit = std::ptr::null_mut();
+            self.bg_fit_image = std::ptr::null_mut();
+            self.bg_fit_uv_scale = std::ptr::null_mut();
+            self.bg_fit_uv_offset = std::ptr::null_mut();
+            self.bg_fit_tile = std::ptr::null_mut();
+        }
+        if !self.effect_bg_generate.is_null() {
+            obs::gs_effect_destroy(self.effect_bg_generate);
+            self.effect_bg_generate = std::ptr::null_mut();
+            self.bg_gen_size = std::ptr::null_mut();
+            self.bg_gen_type = std::ptr::null_mut();
+            self.bg_gen_colors = [std::ptr::null_mut(); 4];
+            self.bg_gen_stop_count = std::ptr::null_mut();
+            self.bg_gen_angle = std::ptr::null_mut();
+            self.bg_gen_time = std::ptr::null_mut();
+        }
+        if !self.effect_bokeh.is_null() {
+            obs::gs_effect_destroy(self.effect_bokeh);
+            self.effect_bokeh = std::ptr::null_mut();
+            self.bokeh_image = std::ptr::null_mut();
+            self.bokeh_texel_size = std::ptr::null_mut();
+            self.bokeh_radius = std::ptr::null_mut();
+            self.bokeh_shape = std::ptr::null_mut();
+            self.bokeh_highlight = std::ptr::null_mut();
+        }
+        if !self.effect_plate.is_null() {
+            obs::gs_effect_destroy(self.effect_plate);
+            self.effect_plate = std::ptr::null_mut();
+            self.plate_image = std::ptr::null_mut();
+            self.plate_prev_image = std::ptr::null_mut();
+            self.plate_mask_image = std::ptr::null_mut();
+            self.plate_mask_threshold = std::ptr::null_mut();
+            self.plate_mask_softness = std::ptr::null_mut();
+            self.plate_mask_invert = std::ptr::null_mut();
+            self.plate_blend = std::ptr::null_mut();
+            self.plate_reset = std::ptr::null_mut();
+        }
+        if !self.effect_privacy.is_null() {
+            obs::gs_effect_destroy(self.effect_privacy);
+            self.effect_privacy = std::ptr::null_mut();
+            self.privacy_image = std::ptr::null_mut();
+            self.privacy_block_size = std::ptr::null_mut();
+            self.privacy_style = std::ptr::null_mut();
+        }
+        if !self.effect_mask_debug.is_null() {
+            obs::gs_effect_destroy(self.effect_mask_debug);
+            self.effect_mask_debug = std::ptr::null_mut();
+            self.mask_debug_image = std::ptr::null_mut();
+            self.mask_debug_mask_image = std::ptr::null_mut();
+            self.mask_debug_threshold = std::ptr::null_mut();
+            self.mask_debug_softness = std::ptr::null_mut();
+            self.mask_debug_invert = std::ptr::null_mut();
+            self.mask_debug_view = std::ptr::null_mut();
         }
 
         obs::obs_leave_graphics();
@@ -403,6 +1055,18 @@ pub(crate) unsafe fn render_source_to_texrender(
     cx: u32,
     cy: u32,
     target: *mut obs::obs_source_t,
+) -> bool {
+    render_source_scaled_to_texrender(texrender, cx, cy, target, cx, cy)
+}
+
+// Renders `target` (of size source_cx x source_cy) stretched to fill a cx x cy texrender.
+pub(crate) unsafe fn render_source_scaled_to_texrender(
+    texrender: *mut obs::gs_texrender_t,
+    cx: u32,
+    cy: u32,
+    target: *mut obs::obs_source_t,
+    source_cx: u32,
+    source_cy: u32,
 ) -> bool {
     if texrender.is_null() {
         return false;
@@ -417,7 +1081,7 @@ pub(crate) unsafe fn render_source_to_texrender(
 
     let ok = if obs::gs_texrender_begin(texrender, cx, cy) {
         obs::gs_clear(obs::GS_CLEAR_COLOR as u32, &mut clear_color, 0.0, 0);
-        obs::gs_ortho(0.0, cx as f32, 0.0, cy as f32, -100.0, 100.0);
+        obs::gs_ortho(0.0, source_cx as f32, 0.0, source_cy as f32, -100.0, 100.0);
         obs::obs_source_video_render(target);
         obs::gs_texrender_end(texrender);
         true
@@ -470,10 +1134,37 @@ pub(crate) unsafe fn render_effect_to_texrender(
     ok
 }
 
+// Must be called while in graphics context.
+// Fills the texrender with a single color (straight RGBA).
+pub(crate) unsafe fn clear_texrender(
+    texrender: *mut obs::gs_texrender_t,
+    cx: u32,
+    cy: u32,
+    rgba: [f32; 4],
+) -> bool {
+    if texrender.is_null() {
+        return false;
+    }
+
+    obs::gs_texrender_reset(texrender);
+
+    let mut clear_color: obs::vec4 = std::mem::zeroed();
+    std::ptr::write(std::ptr::addr_of_mut!(clear_color).cast::<[f32; 4]>(), rgba);
+
+    if !obs::gs_texrender_begin(texrender, cx, cy) {
+        return false;
+    }
+    obs::gs_clear(obs::GS_CLEAR_COLOR as u32, &mut clear_color, 0.0, 0);
+    obs::gs_texrender_end(texrender);
+    true
+}
+
 pub(crate) unsafe fn draw_shape_to_screen(
     gfx: &GraphicsState,
     settings: &FilterSettings,
+    shape_image: &ShapeImage,
     tex: *mut obs::gs_texture_t,
+    premultiplied: bool,
     cx: u32,
     cy: u32,
 ) {
@@ -532,6 +1223,16 @@ pub(crate) unsafe fn draw_shape_to_screen(
         shadow[3] *= settings.shadow_opacity.clamp(0.0, 1.0);
         set_vec4_param(gfx.shape_shadow_color, shadow);
     }
+    set_float_param(gfx.shape_image_premultiplied, if premultiplied { 1.0 } else { 0.0 });
+    set_float_param(gfx.shape_exponent, settings.shape_exponent);
+    set_float_param(gfx.shape_sides, settings.shape_sides as f32);
+    set_float_param(gfx.shape_inner_radius, settings.shape_inner_radius);
+    set_float_param(gfx.shape_rotation, settings.shape_rotation);
+    if !gfx.shape_field.is_null() {
+        obs::gs_effect_set_texture(gfx.shape_field, shape_image.texture());
+    }
+    let (field_w, field_h) = shape_image.size();
+    set_vec2_param(gfx.shape_field_size, field_w as f32, field_h as f32);
 
     obs::gs_blend_state_push();
     obs::gs_blend_function(
@@ -561,6 +1262,13 @@ pub(crate) unsafe fn set_float_param(param: *mut obs::gs_eparam_t, v: f32) {
     obs::gs_effect_set_float(param, v);
 }
 
+pub(crate) unsafe fn set_vec3_param(param: *mut obs::gs_eparam_t, v: [f32; 3]) {
+    if param.is_null() {
+        return;
+    }
+    obs::gs_effect_set_val(param, v.as_ptr().cast(), std::mem::size_of_val(&v) as obs::size_t);
+}
+
 pub(crate) unsafe fn set_vec4_param(param: *mut obs::gs_eparam_t, v: [f32; 4]) {
     if param.is_null() {
         return;
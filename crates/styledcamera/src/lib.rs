@@ -1,13 +1,19 @@
 #![allow(clippy::missing_safety_doc)]
 
+mod audio_sync;
+mod background;
 mod constants;
+mod file_loader;
 mod filter;
 mod frame_history;
 mod graphics;
+mod lut;
+mod mask_source;
 mod obs_exports;
 mod perf;
 mod segmentation;
 mod settings;
+mod shape_image;
 mod util;
 
 pub use crate::obs_exports::*;
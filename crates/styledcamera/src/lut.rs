@@ -0,0 +1,136 @@
+use std::ffi::{CStr, CString};
+
+use obs_sys as obs;
+use styledcamera_core::lut::parse_cube;
+
+use crate::file_loader::{FileLoader, LoadEvent};
+use crate::util::cstr;
+
+// Parsed .cube file, converted to the texture layout off the graphics thread.
+struct LoadedLut {
+    size: u32,
+    domain_min: [f32; 3],
+    domain_max: [f32; 3],
+    strip: Vec<u16>,
+}
+
+// 3D LUT loaded from a .cube file, uploaded as its blue slices side by side (see
+// CubeLut::strip_rgba_f16) so the composite can sample it as a 2D texture.
+pub(crate) struct LutTexture {
+    texture: *mut obs::gs_texture_t,
+    size: u32,
+    domain_min: [f32; 3],
+    domain_max: [f32; 3],
+    loader: FileLoader<LoadedLut>,
+}
+
+impl Default for LutTexture {
+    fn default() -> Self {
+        Self {
+            texture: std::ptr::null_mut(),
+            size: 0,
+            domain_min: [0.0; 3],
+            domain_max: [1.0; 3],
+            loader: FileLoader::new(load_cube),
+        }
+    }
+}
+
+impl LutTexture {
+    // Starts loading the LUT when the path or the file changed; see poll.
+    pub(crate) fn set_path(&self, path: Option<CString>) {
+        self.loader.set_path(path);
+    }
+
+    pub(crate) fn reload_if_modified(&self) {
+        self.loader.reload_if_modified();
+    }
+
+    // Uploads a finished load. Must be called on the graphics thread.
+    pub(crate) unsafe fn poll(&mut self) {
+        let Some(event) = self.loader.take_event() else {
+            return;
+        };
+
+        obs::obs_enter_graphics();
+        self.destroy_texture();
+        match event {
+            LoadEvent::Loaded(lut) => {
+                let n = lut.size;
+                let mut data_ptrs = [lut.strip.as_ptr().cast::<u8>()];
+                self.texture = obs::gs_texture_create(
+                    n * n,
+                    n,
+                    obs::gs_color_format_GS_RGBA16F,
+                    1,
+                    data_ptrs.as_mut_ptr(),
+                    0,
+                );
+                if !self.texture.is_null() {
+                    self.size = n;
+                    self.domain_min = lut.domain_min;
+                    self.domain_max = lut.domain_max;
+                }
+            }
+            LoadEvent::Failed(err) => {
+                let path = self.loader.path().unwrap_or_default();
+                let msg = CString::new(err).unwrap_or_default();
+                obs::blog(
+                    obs::LOG_WARNING as i32,
+                    cstr(b"StyledCamera: failed to load LUT '%s': %s\n\0"),
+                    path.as_ptr(),
+                    msg.as_ptr(),
+                );
+            }
+            LoadEvent::Cleared => {}
+        }
+        obs::obs_leave_graphics();
+    }
+
+    // A LUT is loaded and can be applied.
+    pub(crate) fn is_loaded(&self) -> bool {
+        !self.texture.is_null()
+    }
+
+    pub(crate) fn texture(&self) -> *mut obs::gs_texture_t {
+        self.texture
+    }
+
+    pub(crate) fn size(&self) -> u32 {
+        self.size
+    }
+
+    pub(crate) fn domain(&self) -> ([f32; 3], [f32; 3]) {
+        (self.domain_min, self.domain_max)
+    }
+
+    pub(crate) unsafe fn free(&mut self) {
+        self.loader.set_path(None);
+        obs::obs_enter_graphics();
+        self.destroy_texture();
+        obs::obs_leave_graphics();
+    }
+
+    // Must be called while in graphics context.
+    unsafe fn destroy_texture(&mut self) {
+        if !self.texture.is_null() {
+            obs::gs_texture_destroy(self.texture);
+        }
+        self.texture = std::ptr::null_mut();
+        self.size = 0;
+        self.domain_min = [0.0; 3];
+        self.domain_max = [1.0; 3];
+    }
+}
+
+fn load_cube(path: &CStr) -> Result<LoadedLut, String> {
+    let path = path.to_str().map_err(|_| "path is not valid UTF-8".to_string())?;
+    let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let lut = parse_cube(&text).map_err(|e| e.to_string())?;
+    Ok(LoadedLut {
+        size: lut.size as u32,
+        domain_min: lut.domain_min,
+        domain_max: lut.domain_max,
+        strip: lut.strip_rgba_f16(),
+    })
+}
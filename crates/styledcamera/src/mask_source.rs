@@ -0,0 +1,167 @@
+use std::ffi::{c_void, CStr, CString};
+use std::os::raw::c_char;
+use std::sync::Mutex;
+
+use obs_sys as obs;
+
+use crate::constants::*;
+use crate::filter::{draw_published_mask, published_mask, registered_filters};
+use crate::util::cstr;
+
+// Companion source that renders a StyledCamera filter's refined mask as a grayscale matte.
+struct StyledCameraMaskSource {
+    // UUID of the chosen filter instance; set from update (UI thread).
+    filter_uuid: Mutex<Option<CString>>,
+}
+
+impl StyledCameraMaskSource {
+    fn size(&self) -> (u32, u32) {
+        let Ok(uuid) = self.filter_uuid.lock() else {
+            return (0, 0);
+        };
+        let Some(mask) = uuid.as_deref().and_then(published_mask) else {
+            return (0, 0);
+        };
+        let Ok(mask) = mask.lock() else {
+            return (0, 0);
+        };
+        (mask.width, mask.height)
+    }
+}
+
+unsafe fn load_filter_uuid(settings: *mut obs::obs_data_t) -> Option<CString> {
+    if settings.is_null() {
+        return None;
+    }
+
+    let uuid = obs::obs_data_get_string(settings, cstr(SETTING_MASK_SOURCE_FILTER));
+    if uuid.is_null() {
+        return None;
+    }
+    let uuid = CStr::from_ptr(uuid);
+    if uuid.is_empty() {
+        None
+    } else {
+        Some(uuid.to_owned())
+    }
+}
+
+pub(crate) unsafe fn register_source() {
+    let mut info: obs::obs_source_info = std::mem::zeroed();
+    info.id = cstr(MASK_SOURCE_ID);
+    info.type_ = obs::obs_source_type_OBS_SOURCE_TYPE_INPUT;
+    info.output_flags = obs::OBS_SOURCE_VIDEO | obs::OBS_SOURCE_CUSTOM_DRAW;
+
+    info.get_name = Some(mask_source_get_name);
+    info.create = Some(mask_source_create);
+    info.destroy = Some(mask_source_destroy);
+    info.update = Some(mask_source_update);
+    info.get_defaults = Some(mask_source_get_defaults);
+    info.get_properties = Some(mask_source_get_properties);
+    info.get_width = Some(mask_source_get_width);
+    info.get_height = Some(mask_source_get_height);
+    info.video_render = Some(mask_source_video_render);
+
+    obs::obs_register_source_s(
+        &info as *const obs::obs_source_info,
+        std::mem::size_of::<obs::obs_source_info>() as obs::size_t,
+    );
+}
+
+unsafe extern "C" fn mask_source_get_name(_type_data: *mut c_void) -> *const c_char {
+    MASK_SOURCE_DISPLAY_NAME.as_ptr().cast()
+}
+
+unsafe extern "C" fn mask_source_create(
+    settings_data: *mut obs::obs_data_t,
+    _source: *mut obs::obs_source_t,
+) -> *mut c_void {
+    let source = Box::new(StyledCameraMaskSource {
+        filter_uuid: Mutex::new(load_filter_uuid(settings_data)),
+    });
+    Box::into_raw(source).cast()
+}
+
+unsafe extern "C" fn mask_source_destroy(data: *mut c_void) {
+    if data.is_null() {
+        return;
+    }
+    drop(Box::from_raw(data.cast::<StyledCameraMaskSource>()));
+}
+
+unsafe extern "C" fn mask_source_update(data: *mut c_void, settings_data: *mut obs::obs_data_t) {
+    if data.is_null() {
+        return;
+    }
+    let source = &*data.cast::<StyledCameraMaskSource>();
+    if let Ok(mut uuid) = source.filter_uuid.lock() {
+        *uuid = load_filter_uuid(settings_data);
+    }
+}
+
+unsafe extern "C" fn mask_source_get_defaults(settings_data: *mut obs::obs_data_t) {
+    if settings_data.is_null() {
+        return;
+    }
+    obs::obs_data_set_default_string(settings_data, cstr(SETTING_MASK_SOURCE_FILTER), cstr(b"\0"));
+}
+
+unsafe extern "C" fn mask_source_get_properties(_data: *mut c_void) -> *mut obs::obs_properties_t {
+    let props = obs::obs_properties_create();
+    if props.is_null() {
+        return props;
+    }
+
+    let list = obs::obs_properties_add_list(
+        props,
+        cstr(SETTING_MASK_SOURCE_FILTER),
+        cstr(PROP_MASK_SOURCE_FILTER),
+        obs::obs_combo_type_OBS_COMBO_TYPE_LIST,
+        obs::obs_combo_format_OBS_COMBO_FORMAT_STRING,
+    );
+    if !list.is_null() {
+        obs::obs_property_list_add_string(list, cstr(PROP_MASK_SOURCE_FILTER_NONE), cstr(b"\0"));
+        for (uuid, label) in registered_filters() {
+            obs::obs_property_list_add_string(list, label.as_ptr(), uuid.as_ptr());
+        }
+    }
+
+    props
+}
+
+unsafe extern "C" fn mask_source_get_width(data: *mut c_void) -> u32 {
+    if data.is_null() {
+        return 0;
+    }
+    (*data.cast::<StyledCameraMaskSource>()).size().0
+}
+
+unsafe extern "C" fn mask_source_get_height(data: *mut c_void) -> u32 {
+    if data.is_null() {
+        return 0;
+    }
+    (*data.cast::<StyledCameraMaskSource>()).size().1
+}
+
+unsafe extern "C" fn mask_source_video_render(data: *mut c_void, _effect: *mut obs::gs_effect_t) {
+    if data.is_null() {
+        return;
+    }
+    let source = &*data.cast::<StyledCameraMaskSource>();
+    let Ok(uuid) = source.filter_uuid.lock() else {
+        return;
+    };
+    let Some(mask) = uuid.as_deref().and_then(published_mask) else {
+        return;
+    };
+    // Held while drawing so the filter cannot destroy the textures underneath us.
+    let Ok(mask) = mask.lock() else {
+        return;
+    };
+
+    if mask.width == 0 || mask.height == 0 {
+        return;
+    }
+    // Nothing is drawn until the filter has produced a mask.
+    draw_published_mask(&mask, mask.width, mask.height);
+}
@@ -51,6 +51,7 @@ pub unsafe extern "C" fn obs_module_load() -> bool {
         version.as_ptr(),
     );
     crate::filter::register_sources();
+    crate::mask_source::register_source();
     true
 }
 
@@ -8,6 +8,10 @@ use std::thread;
 use std::time::{Duration, Instant};
 
 use obs_sys as obs;
+use styledcamera_core::distance::{
+    distance_to_person, encode_distance_u8, encode_signed_distance_u8, person_pixels,
+    signed_distance_to_person,
+};
 
 use crate::constants::MODEL_FILE;
 use crate::perf::SegPerf;
@@ -19,13 +23,123 @@ pub(crate) struct SegInput {
     pub height: u32,
     pub temporal_smoothing: f32,
     pub capture_time: Instant,
+    // Id of the frame-history entry this input was downsampled from.
+    pub frame_id: u64,
+    pub depth: Option<DistanceFieldRequest>,
+    pub silhouette: Option<DistanceFieldRequest>,
+}
+
+// Asks the worker for a distance field next to the mask (depth-graded blur, silhouette outline).
+#[derive(Clone, Copy)]
+pub(crate) struct DistanceFieldRequest {
+    pub threshold: f32,
+    pub invert: bool,
+    // Frame width / height; the mask is square but stretched over the frame.
+    pub aspect: f32,
+}
+
+// Distances are encoded in frame heights; anything this far or farther is fully "far".
+const DEPTH_FIELD_RANGE: f32 = 1.0;
+
+fn depth_field_u8(mask: &[u8], width: u32, height: u32, request: DistanceFieldRequest) -> Vec<u8> {
+    let (w, h) = (width as usize, height as usize);
+    let person = person_pixels(mask, request.threshold, request.invert);
+    let pixel_size = [request.aspect / w as f32, 1.0 / h as f32];
+    encode_distance_u8(&distance_to_person(&person, w, h, pixel_size), DEPTH_FIELD_RANGE)
+}
+
+// Signed distances (frame heights) are encoded over +-this range; it bounds how far outline,
+// glow and silhouette shadow can reach (about 135 px on a 1080p frame).
+pub(crate) const SILHOUETTE_FIELD_RANGE: f32 = 0.125;
+
+fn silhouette_field_u8(
+    mask: &[u8],
+    width: u32,
+    height: u32,
+    request: DistanceFieldRequest,
+) -> Vec<u8> {
+    let (w, h) = (width as usize, height as usize);
+    let person = person_pixels(mask, request.threshold, request.invert);
+    let pixel_size = [request.aspect / w as f32, 1.0 / h as f32];
+    encode_signed_distance_u8(
+        &signed_distance_to_person(&person, w, h, pixel_size),
+        SILHOUETTE_FIELD_RANGE,
+    )
 }
 
 pub(crate) struct SegOutput {
     pub mask: Vec<u8>,
+    // Distance from the person (0..255 over DEPTH_FIELD_RANGE), when requested.
+    pub distance: Option<Vec<u8>>,
+    // Signed distance to the silhouette (127.5 on it, +-SILHOUETTE_FIELD_RANGE), when requested.
+    pub silhouette: Option<Vec<u8>>,
     pub width: u32,
     pub height: u32,
     pub capture_time: Instant,
+    pub frame_id: u64,
+    // Worker time spent on this frame (preprocess + inference + postprocess).
+    pub infer_ms: f32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum WorkerState {
+    Stopped,
+    Starting,
+    Running,
+    Failed,
+}
+
+impl WorkerState {
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            WorkerState::Stopped => "stopped",
+            WorkerState::Starting => "starting",
+            WorkerState::Running => "running",
+            WorkerState::Failed => "failed",
+        }
+    }
+}
+
+// Measurements owned by the render thread, published each frame for the properties UI.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct RenderStats {
+    // None while the worker is stopped or before its first mask.
+    pub mask_fps: Option<f32>,
+    pub mask_latency_ms: f32,
+    pub history_frames: usize,
+    pub history_vram_bytes: u64,
+    // Auto mask rate: target FPS and the worker's time per frame.
+    pub mask_rate: Option<(f32, f32)>,
+}
+
+// Shared between the render thread, the worker thread and the properties UI.
+#[derive(Clone)]
+pub(crate) struct SegStatus {
+    pub worker: WorkerState,
+    pub model_path: Option<PathBuf>,
+    pub ort_version: Option<String>,
+    pub input_is_nchw: Option<bool>,
+    pub last_error: Option<String>,
+    pub render: RenderStats,
+}
+
+impl Default for SegStatus {
+    fn default() -> Self {
+        Self {
+            worker: WorkerState::Stopped,
+            model_path: None,
+            ort_version: None,
+            input_is_nchw: None,
+            last_error: None,
+            render: RenderStats::default(),
+        }
+    }
+}
+
+fn update_status(status: &Mutex<SegStatus>, f: impl FnOnce(&mut SegStatus)) {
+    if let Ok(mut guard) = status.lock() {
+        f(&mut guard);
+    }
 }
 
 pub(crate) struct SegInbox {
@@ -87,6 +201,7 @@ pub(crate) struct SegmentationState {
     pub inbox: Option<Arc<SegInbox>>,
     pub rx: Option<Receiver<SegOutput>>,
     pub thread: Option<thread::JoinHandle<()>>,
+    pub status: Arc<Mutex<SegStatus>>,
 }
 
 impl Default for SegmentationState {
@@ -95,6 +210,7 @@ impl Default for SegmentationState {
             inbox: None,
             rx: None,
             thread: None,
+            status: Arc::new(Mutex::new(SegStatus::default())),
         }
     }
 }
@@ -112,6 +228,10 @@ impl SegmentationState {
                     obs::LOG_WARNING as i32,
                     cstr(b"StyledCamera: ONNX Runtime dylib not found; segmentation disabled\n\0"),
                 );
+                update_status(&self.status, |s| {
+                    s.worker = WorkerState::Failed;
+                    s.last_error = Some("ONNX Runtime dylib not found".to_owned());
+                });
                 return;
             }
         };
@@ -123,16 +243,33 @@ impl SegmentationState {
                     obs::LOG_WARNING as i32,
                     cstr(b"StyledCamera: segmentation model not found; segmentation disabled\n\0"),
                 );
+                update_status(&self.status, |s| {
+                    s.worker = WorkerState::Failed;
+                    s.model_path = None;
+                    s.last_error = Some("segmentation model not found".to_owned());
+                });
                 return;
             }
         };
 
+        update_status(&self.status, |s| {
+            s.worker = WorkerState::Starting;
+            s.model_path = Some(model_path.clone());
+        });
+
         let (out_tx, out_rx) = mpsc::sync_channel::<SegOutput>(1);
 
         let inbox = Arc::new(SegInbox::new());
         let inbox_for_thread = inbox.clone();
+        let status_for_thread = self.status.clone();
         let handle = thread::spawn(move || {
-            segmentation_thread_main(inbox_for_thread, out_tx, dylib_path, model_path);
+            segmentation_thread_main(
+                inbox_for_thread,
+                out_tx,
+                status_for_thread,
+                dylib_path,
+                model_path,
+            );
         });
 
         self.inbox = Some(inbox);
@@ -148,12 +285,27 @@ impl SegmentationState {
         if let Some(handle) = self.thread.take() {
             let _ = handle.join();
         }
+        // Keep a failure visible in the status readout; otherwise report a clean stop.
+        update_status(&self.status, |s| {
+            if s.worker != WorkerState::Failed {
+                s.worker = WorkerState::Stopped;
+            }
+        });
+    }
+
+    pub(crate) fn publish_render_stats(&self, stats: RenderStats) {
+        update_status(&self.status, |s| s.render = stats);
+    }
+
+    pub(crate) fn status_snapshot(&self) -> SegStatus {
+        self.status.lock().map(|s| s.clone()).unwrap_or_default()
     }
 }
 
 fn segmentation_thread_main(
     inbox: Arc<SegInbox>,
     tx: SyncSender<SegOutput>,
+    status: Arc<Mutex<SegStatus>>,
     dylib_path: PathBuf,
     model_path: PathBuf,
 ) {
@@ -186,9 +338,16 @@ fn segmentation_thread_main(
                 cstr(b"StyledCamera: failed to init ONNX Runtime; segmentation disabled\n\0"),
             );
         }
+        update_status(&status, |s| {
+            s.worker = WorkerState::Failed;
+            s.last_error = Some("failed to init ONNX Runtime".to_owned());
+        });
         return;
     }
 
+    let ort_version = styledcamera_core::segmentation::ort_version_from_build_info(ort::info())
+        .map(str::to_owned);
+
     let mut session =
         match ort::session::Session::builder().and_then(|b| b.commit_from_file(model_path)) {
             Ok(s) => s,
@@ -199,16 +358,31 @@ fn segmentation_thread_main(
                         cstr(b"StyledCamera: failed to load segmentation model into ORT session\n\0"),
                     );
                 }
+                update_status(&status, |s| {
+                    s.worker = WorkerState::Failed;
+                    s.ort_version = ort_version;
+                    s.last_error = Some("failed to load segmentation model into ORT session".to_owned());
+                });
                 return;
             }
         };
 
+    update_status(&status, |s| {
+        s.worker = WorkerState::Running;
+        s.ort_version = ort_version;
+        s.input_is_nchw = None;
+    });
+
     let mut prev_mask: Vec<f32> = Vec::new();
     let mut input_is_nchw: Option<bool> = None;
     let mut last_infer_error_log: Option<Instant> = None;
+    // Whether the last inference failed; separate from the log rate limit so recovery is
+    // reported however soon it follows the failure.
+    let mut in_error = false;
 
     while let Some(input) = inbox.pop_latest_blocking() {
         let t_total = perf.start();
+        let t_work = Instant::now();
 
         let w = input.width as usize;
         let h = input.height as usize;
@@ -312,6 +486,7 @@ fn segmentation_thread_main(
                     perf.record_infer(t_infer);
                     if nchw.is_some() {
                         input_is_nchw = Some(true);
+                        update_status(&status, |s| s.input_is_nchw = Some(true));
                     }
                     nchw
                 }
@@ -319,6 +494,12 @@ fn segmentation_thread_main(
         };
 
         let Some((out_shape, out_data)) = picked else {
+            if !in_error {
+                in_error = true;
+                update_status(&status, |s| {
+                    s.last_error = Some("segmentation inference failed (model/input mismatch?)".to_owned());
+                });
+            }
             let now = Instant::now();
             let due = last_infer_error_log
                 .map(|t| now.duration_since(t) >= Duration::from_secs(2))
@@ -334,6 +515,10 @@ fn segmentation_thread_main(
             }
             continue;
         };
+        if in_error {
+            in_error = false;
+            update_status(&status, |s| s.last_error = None);
+        }
 
         let t_post = perf.start();
         let Some(mask_u8) = styledcamera_core::segmentation::postprocess_mask_u8(
@@ -345,13 +530,23 @@ fn segmentation_thread_main(
         ) else {
             continue;
         };
+        let distance = input
+            .depth
+            .map(|request| depth_field_u8(&mask_u8, input.width, input.height, request));
+        let silhouette = input
+            .silhouette
+            .map(|request| silhouette_field_u8(&mask_u8, input.width, input.height, request));
         perf.record_postprocess(t_post);
 
         let _ = tx.try_send(SegOutput {
             mask: mask_u8,
+            distance,
+            silhouette,
             width: input.width,
             height: input.height,
             capture_time: input.capture_time,
+            frame_id: input.frame_id,
+            infer_ms: t_work.elapsed().as_secs_f32() * 1000.0,
         });
 
         perf.record_total(t_total);
@@ -1,5 +1,13 @@
+use std::ffi::{c_void, CStr, CString};
+
 use obs_sys as obs;
 
+use styledcamera_core::background::BackgroundFit;
+use styledcamera_core::blur::{BlurQuality, BokehShape};
+use styledcamera_core::color::Grade;
+use styledcamera_core::rate::MaskRateTarget;
+
+use crate::audio_sync::SyncOffsetState;
 use crate::constants::*;
 use crate::util::cstr;
 
@@ -25,27 +33,421 @@ unsafe extern "C" fn on_shape_type_modified(
     if !p_r.is_null() { obs::obs_property_set_visible(p_r, show_frame); }
     if !p_b.is_null() { obs::obs_property_set_visible(p_b, show_frame); }
 
+    // Squircle, polygon, hexagon, star and heart (5..9) can be turned.
+    set_property_visible(props, SETTING_SHAPE_ROTATION, (5..=9).contains(&shape_type));
+    set_property_visible(props, SETTING_SHAPE_EXPONENT, shape_type == 5);
+    set_property_visible(props, SETTING_SHAPE_SIDES, shape_type == 6 || shape_type == 8);
+    set_property_visible(props, SETTING_SHAPE_INNER_RADIUS, shape_type == 8);
+    set_property_visible(props, SETTING_SHAPE_IMAGE_PATH, shape_type == 10);
+    set_property_visible(props, SETTING_SHAPE_IMAGE_STATUS, shape_type == 10);
+    let sides = obs::obs_properties_get(props, cstr(SETTING_SHAPE_SIDES));
+    if !sides.is_null() {
+        let label = if shape_type == 8 { PROP_SHAPE_POINTS } else { PROP_SHAPE_SIDES };
+        obs::obs_property_set_description(sides, cstr(label));
+    }
+
+    // Visibility changes require a refresh.
+    true
+}
+
+unsafe extern "C" fn on_mask_rate_mode_modified(
+    props: *mut obs::obs_properties_t,
+    _property: *mut obs::obs_property_t,
+    settings: *mut obs::obs_data_t,
+) -> bool {
+    if props.is_null() || settings.is_null() {
+        return false;
+    }
+
+    let mode = MaskRateMode::from_i64(obs::obs_data_get_int(settings, cstr(SETTING_MASK_RATE_MODE)));
+
+    let p_budget = obs::obs_properties_get(props, cstr(SETTING_MASK_CPU_BUDGET));
+    let p_ceiling = obs::obs_properties_get(props, cstr(SETTING_MASK_LATENCY_CEILING_MS));
+    if !p_budget.is_null() {
+        obs::obs_property_set_visible(p_budget, mode == MaskRateMode::AutoCpuBudget);
+    }
+    if !p_ceiling.is_null() {
+        obs::obs_property_set_visible(p_ceiling, mode == MaskRateMode::AutoLatencyCeiling);
+    }
+
     // Visibility changes require a refresh.
     true
 }
 
+unsafe fn set_property_visible(props: *mut obs::obs_properties_t, key: &'static [u8], visible: bool) {
+    let p = obs::obs_properties_get(props, cstr(key));
+    if !p.is_null() {
+        obs::obs_property_set_visible(p, visible);
+    }
+}
+
+// Also registered on the color stop count, blur style and depth toggle, which decide which
+// controls are shown.
+unsafe extern "C" fn on_bg_type_modified(
+    props: *mut obs::obs_properties_t,
+    _property: *mut obs::obs_property_t,
+    settings: *mut obs::obs_data_t,
+) -> bool {
+    if props.is_null() || settings.is_null() {
+        return false;
+    }
+
+    let bg_type = BackgroundType::from_i64(obs::obs_data_get_int(settings, cstr(SETTING_BG_TYPE)));
+    // A removed background has nothing to blur, dim, desaturate or wrap light from.
+    let show_bg_controls = bg_type != BackgroundType::Transparent;
+
+    set_property_visible(props, GROUP_BG_GRADE, show_bg_controls);
+    for key in [
+        SETTING_BLUR_INTENSITY,
+        SETTING_BLUR_QUALITY,
+        SETTING_BG_DIM,
+        SETTING_BG_DESAT,
+        SETTING_LIGHT_WRAP,
+        SETTING_LIGHT_WRAP_WIDTH,
+    ] {
+        set_property_visible(props, key, show_bg_controls);
+    }
+    let lens = BlurStyle::from_i64(obs::obs_data_get_int(settings, cstr(SETTING_BLUR_STYLE)))
+        == BlurStyle::Lens;
+    set_property_visible(props, SETTING_BLUR_STYLE, show_bg_controls);
+    for key in [SETTING_BOKEH_SHAPE, SETTING_BOKEH_HIGHLIGHTS] {
+        set_property_visible(props, key, show_bg_controls && lens);
+    }
+    // With depth grading the intensity is the strength far from the person.
+    let depth = obs::obs_data_get_bool(settings, cstr(SETTING_DEPTH_BLUR));
+    set_property_visible(props, SETTING_DEPTH_BLUR, show_bg_controls);
+    set_property_visible(props, SETTING_DEPTH_FALLOFF, show_bg_controls && depth);
+    let intensity = obs::obs_properties_get(props, cstr(SETTING_BLUR_INTENSITY));
+    if !intensity.is_null() {
+        let label = if depth { PROP_BLUR_MAX } else { PROP_BLUR_INTENSITY };
+        obs::obs_property_set_description(intensity, cstr(label));
+    }
+    // Only the camera frame has a person in it to keep out of the blur.
+    set_property_visible(props, SETTING_BLUR_MASK_AWARE, bg_type == BackgroundType::Blur);
+    for key in [SETTING_BG_IMAGE_PATH, SETTING_BG_IMAGE_FIT] {
+        set_property_visible(props, key, bg_type == BackgroundType::Image);
+    }
+    set_property_visible(props, SETTING_BG_SOURCE, bg_type == BackgroundType::Source);
+
+    // Solid uses one color; the other generators use the selected number of stops.
+    let generated = bg_type.generator().is_some();
+    let uses_stops = generated && bg_type != BackgroundType::Solid;
+    let stops = if uses_stops {
+        obs::obs_data_get_int(settings, cstr(SETTING_BG_GRADIENT_STOPS)).clamp(2, 4) as usize
+    } else {
+        1
+    };
+    let color_keys = [SETTING_BG_COLOR_1, SETTING_BG_COLOR_2, SETTING_BG_COLOR_3, SETTING_BG_COLOR_4];
+    for (i, key) in color_keys.into_iter().enumerate() {
+        set_property_visible(props, key, generated && i < stops);
+    }
+    set_property_visible(props, SETTING_BG_GRADIENT_STOPS, uses_stops);
+    set_property_visible(
+        props,
+        SETTING_BG_GRADIENT_ANGLE,
+        bg_type == BackgroundType::LinearGradient || bg_type == BackgroundType::ConicGradient,
+    );
+    set_property_visible(props, SETTING_BG_ANIM_SPEED, bg_type.is_animated());
+    set_property_visible(props, SETTING_BG_PLATE_FREEZE, bg_type == BackgroundType::CleanPlate);
+    for key in [SETTING_BG_PRIVACY_STYLE, SETTING_BG_PRIVACY_BLOCK] {
+        set_property_visible(props, key, bg_type == BackgroundType::Privacy);
+    }
+
+    // Visibility changes require a refresh.
+    true
+}
+
+unsafe extern "C" fn on_audio_compensate_modified(
+    props: *mut obs::obs_properties_t,
+    _property: *mut obs::obs_property_t,
+    settings: *mut obs::obs_data_t,
+) -> bool {
+    if props.is_null() || settings.is_null() {
+        return false;
+    }
+
+    let enabled = obs::obs_data_get_bool(settings, cstr(SETTING_AUDIO_COMPENSATE));
+    let p = obs::obs_properties_get(props, cstr(SETTING_AUDIO_SYNC_SOURCE));
+    if !p.is_null() {
+        obs::obs_property_set_visible(p, enabled);
+    }
+
+    // Visibility changes require a refresh.
+    true
+}
+
+unsafe extern "C" fn on_debug_show_mask_modified(
+    props: *mut obs::obs_properties_t,
+    _property: *mut obs::obs_property_t,
+    settings: *mut obs::obs_data_t,
+) -> bool {
+    if props.is_null() || settings.is_null() {
+        return false;
+    }
+
+    let enabled = obs::obs_data_get_bool(settings, cstr(SETTING_DEBUG_SHOW_MASK));
+    set_property_visible(props, SETTING_DEBUG_MASK_VIEW, enabled);
+
+    // Visibility changes require a refresh.
+    true
+}
+
+struct SourceListParam {
+    list: *mut obs::obs_property_t,
+    // The filtered source itself; rendering it as its own background would recurse.
+    exclude: *mut obs::obs_source_t,
+}
+
+unsafe extern "C" fn add_video_source_to_list(param: *mut c_void, source: *mut obs::obs_source_t) -> bool {
+    let param = &*param.cast::<SourceListParam>();
+    if source == param.exclude || obs::obs_source_get_output_flags(source) & obs::OBS_SOURCE_VIDEO == 0 {
+        return true;
+    }
+    let name = obs::obs_source_get_name(source);
+    if !name.is_null() {
+        obs::obs_property_list_add_string(param.list, name, name);
+    }
+    true
+}
+
+unsafe extern "C" fn add_audio_source_to_list(param: *mut c_void, source: *mut obs::obs_source_t) -> bool {
+    let list = param.cast::<obs::obs_property_t>();
+    if obs::obs_source_get_output_flags(source) & obs::OBS_SOURCE_AUDIO != 0 {
+        let name = obs::obs_source_get_name(source);
+        if !name.is_null() {
+            obs::obs_property_list_add_string(list, name, name);
+        }
+    }
+    true
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MaskRateMode {
+    Fixed,
+    AutoCpuBudget,
+    AutoLatencyCeiling,
+}
+
+impl MaskRateMode {
+    fn from_i64(v: i64) -> Self {
+        match v {
+            1 => MaskRateMode::AutoCpuBudget,
+            2 => MaskRateMode::AutoLatencyCeiling,
+            _ => MaskRateMode::Fixed,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BackgroundType {
+    Blur,
+    Transparent,
+    Image,
+    Source,
+    Solid,
+    LinearGradient,
+    RadialGradient,
+    ConicGradient,
+    Noise,
+    Aurora,
+    // Learned from frames where the background was visible.
+    CleanPlate,
+    // The camera's own background, pixelated beyond reading. Fails closed: when a frame cannot
+    // be styled nothing is drawn, never the unfiltered camera (see skip_video_filter).
+    Privacy,
+}
+
+impl BackgroundType {
+    fn from_i64(v: i64) -> Self {
+        match v {
+            1 => BackgroundType::Transparent,
+            2 => BackgroundType::Image,
+            3 => BackgroundType::Source,
+            4 => BackgroundType::Solid,
+            5 => BackgroundType::LinearGradient,
+            6 => BackgroundType::RadialGradient,
+            7 => BackgroundType::ConicGradient,
+            8 => BackgroundType::Noise,
+            9 => BackgroundType::Aurora,
+            10 => BackgroundType::CleanPlate,
+            11 => BackgroundType::Privacy,
+            _ => BackgroundType::Blur,
+        }
+    }
+
+    // `gen_type` in background_generate.effect, for generated backgrounds.
+    pub(crate) fn generator(self) -> Option<f32> {
+        match self {
+            BackgroundType::Solid => Some(0.0),
+            BackgroundType::LinearGradient => Some(1.0),
+            BackgroundType::RadialGradient => Some(2.0),
+            BackgroundType::ConicGradient => Some(3.0),
+            BackgroundType::Noise => Some(4.0),
+            BackgroundType::Aurora => Some(5.0),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn is_animated(self) -> bool {
+        self == BackgroundType::Noise || self == BackgroundType::Aurora
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum BlurStyle {
+    // Smooth dual-filter pyramid.
+    Gaussian,
+    // Aperture-shaped bokeh with blooming highlights.
+    Lens,
+}
+
+impl BlurStyle {
+    fn from_i64(v: i64) -> Self {
+        match v {
+            1 => BlurStyle::Lens,
+            _ => BlurStyle::Gaussian,
+        }
+    }
+}
+
+// What the mask debug output shows instead of the styled frame.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DebugMaskView {
+    // The mask as the model returned it, never inverted (also the left half of SideBySide).
+    Raw,
+    Overlay,
+    Binary,
+    EdgeBand,
+    SideBySide,
+    // The downscaled frame handed to the model.
+    SegInput,
+}
+
+impl DebugMaskView {
+    fn from_i64(v: i64) -> Self {
+        match v {
+            1 => DebugMaskView::Overlay,
+            2 => DebugMaskView::Binary,
+            3 => DebugMaskView::EdgeBand,
+            4 => DebugMaskView::SideBySide,
+            5 => DebugMaskView::SegInput,
+            _ => DebugMaskView::Raw,
+        }
+    }
+
+    // `view` in mask_debug.effect; the other views are drawn with the default effect.
+    pub(crate) fn shader_view(self) -> Option<f32> {
+        match self {
+            DebugMaskView::Overlay => Some(1.0),
+            DebugMaskView::Binary => Some(2.0),
+            DebugMaskView::EdgeBand => Some(3.0),
+            DebugMaskView::SideBySide => Some(4.0),
+            DebugMaskView::Raw | DebugMaskView::SegInput => None,
+        }
+    }
+}
+
+// How the privacy background hides what is behind the person.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum PrivacyStyle {
+    Mosaic,
+    // Mosaic quantized to a few levels with noise.
+    Scramble,
+}
+
+impl PrivacyStyle {
+    fn from_i64(v: i64) -> Self {
+        match v {
+            1 => PrivacyStyle::Scramble,
+            _ => PrivacyStyle::Mosaic,
+        }
+    }
+
+    // `style` in background_privacy.effect.
+    pub(crate) fn shader_value(self) -> f32 {
+        match self {
+            PrivacyStyle::Mosaic => 0.0,
+            PrivacyStyle::Scramble => 1.0,
+        }
+    }
+}
+
+// Which layers the .cube LUT is applied to.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LutTarget {
+    Foreground,
+    Background,
+    Both,
+}
+
+impl LutTarget {
+    fn from_i64(v: i64) -> Self {
+        match v {
+            0 => LutTarget::Foreground,
+            1 => LutTarget::Background,
+            _ => LutTarget::Both,
+        }
+    }
+
+    // Blend amount per layer: (foreground, background).
+    pub(crate) fn amounts(self, amount: f32) -> (f32, f32) {
+        match self {
+            LutTarget::Foreground => (amount, 0.0),
+            LutTarget::Background => (0.0, amount),
+            LutTarget::Both => (amount, amount),
+        }
+    }
+}
+
+// Deep blue, violet, teal, amber (0xAABBGGRR).
+const BG_COLOR_DEFAULTS: [u32; 4] = [0xFF3A1F14, 0xFF8A3B5E, 0xFF8A8A1E, 0xFF30A8F0];
+
 #[derive(Clone, Copy)]
 pub(crate) struct FilterSettings {
     pub blur_intensity: f32,
+    pub blur_quality: BlurQuality,
+    pub blur_style: BlurStyle,
+    pub bokeh_shape: BokehShape,
+    pub bokeh_highlights: f32,
+    pub depth_blur: bool,
+    pub depth_falloff: f32,
+    pub blur_mask_aware: bool,
     pub debug_show_mask: bool,
+    pub debug_mask_view: DebugMaskView,
 
     pub mask_fps: u32,
+    pub mask_rate_mode: MaskRateMode,
+    pub mask_cpu_budget: f32,
+    pub mask_latency_ceiling_ms: f32,
     pub mask_temporal_smoothing: f32,
     pub mask_threshold: f32,
     pub mask_softness: f32,
     pub mask_invert: bool,
     pub sync_video_extra_delay_ms: f32,
+    pub history_max_frames: u32,
+    pub audio_compensate: bool,
+    pub bg_type: BackgroundType,
+    pub bg_image_fit: BackgroundFit,
+    pub bg_colors_abgr: [u32; 4],
+    pub bg_gradient_stops: u32,
+    pub bg_gradient_angle: f32,
+    pub bg_anim_speed: f32,
+    pub bg_plate_freeze: bool,
+    pub bg_privacy_style: PrivacyStyle,
+    pub bg_privacy_block: f32,
     pub bg_dim: f32,
     pub bg_desat: f32,
+    pub edge_decontam: f32,
+    pub light_wrap: f32,
+    pub light_wrap_width: f32,
 
     pub shape_type: i32,
     pub corner_radius: f32,
     pub feather: f32,
+    pub shape_exponent: f32,
+    pub shape_sides: u32,
+    pub shape_inner_radius: f32,
+    pub shape_rotation: f32,
     pub border_thickness: f32,
     pub border_color_argb: u32,
     pub padding: f32,
@@ -59,26 +461,73 @@ pub(crate) struct FilterSettings {
     pub shadow_offset_x: f32,
     pub shadow_offset_y: f32,
     pub shadow_color_argb: u32,
+
+    pub sil_outline_width: f32,
+    pub sil_outline_color_argb: u32,
+    pub sil_softness: f32,
+    pub sil_glow_size: f32,
+    pub sil_glow_color_argb: u32,
+    pub sil_shadow_opacity: f32,
+    pub sil_shadow_blur: f32,
+    pub sil_shadow_offset_x: f32,
+    pub sil_shadow_offset_y: f32,
+    pub sil_shadow_color_argb: u32,
+
+    pub fg_grade: Grade,
+    pub bg_grade: Grade,
+    // A LUT is loaded; set by the filter from its LutTexture, not from the settings data.
+    pub lut_enabled: bool,
+    pub lut_target: LutTarget,
+    pub lut_amount: f32,
 }
 
 impl Default for FilterSettings {
     fn default() -> Self {
         Self {
             blur_intensity: 0.0,
+            blur_quality: BlurQuality::Balanced,
+            blur_style: BlurStyle::Gaussian,
+            bokeh_shape: BokehShape::Disk,
+            bokeh_highlights: 0.5,
+            depth_blur: false,
+            depth_falloff: 0.35,
+            blur_mask_aware: true,
             debug_show_mask: false,
+            debug_mask_view: DebugMaskView::Raw,
 
             mask_fps: 15,
+            mask_rate_mode: MaskRateMode::Fixed,
+            mask_cpu_budget: 25.0,
+            mask_latency_ceiling_ms: 120.0,
             mask_temporal_smoothing: 0.4,
             mask_threshold: 0.5,
             mask_softness: 0.1,
             mask_invert: false,
             sync_video_extra_delay_ms: 0.0,
+            history_max_frames: 32,
+            audio_compensate: false,
+            bg_type: BackgroundType::Blur,
+            bg_image_fit: BackgroundFit::Cover,
+            bg_colors_abgr: BG_COLOR_DEFAULTS,
+            bg_gradient_stops: 2,
+            bg_gradient_angle: 90.0,
+            bg_anim_speed: 1.0,
+            bg_plate_freeze: false,
+            bg_privacy_style: PrivacyStyle::Mosaic,
+            bg_privacy_block: 32.0,
             bg_dim: 0.0,
             bg_desat: 0.0,
+            edge_decontam: 0.0,
+            light_wrap: 0.0,
+            light_wrap_width: 16.0,
 
             shape_type: 0,
             corner_radius: 24.0,
             feather: 1.5,
+            shape_exponent: 4.0,
+            shape_sides: 5,
+            shape_inner_radius: 0.5,
+            shape_rotation: 0.0,
             border_thickness: 0.0,
             border_color_argb: 0xFFFFFFFF,
             padding: 0.0,
@@ -92,6 +541,23 @@ impl Default for FilterSettings {
             shadow_offset_x: 0.0,
             shadow_offset_y: -4.0,
             shadow_color_argb: 0xFF000000,
+
+            sil_outline_width: 0.0,
+            sil_outline_color_argb: 0xFFFFFFFF,
+            sil_softness: 1.5,
+            sil_glow_size: 0.0,
+            sil_glow_color_argb: 0xFFFFFFFF,
+            sil_shadow_opacity: 0.0,
+            sil_shadow_blur: 8.0,
+            sil_shadow_offset_x: 8.0,
+            sil_shadow_offset_y: 8.0,
+            sil_shadow_color_argb: 0xFF000000,
+
+            fg_grade: Grade::default(),
+            bg_grade: Grade::default(),
+            lut_enabled: false,
+            lut_target: LutTarget::Both,
+            lut_amount: 1.0,
         }
     }
 }
@@ -99,13 +565,44 @@ impl Default for FilterSettings {
 impl FilterSettings {
     pub(crate) fn needs_segmentation(&self) -> bool {
         self.debug_show_mask
+            || self.bg_type != BackgroundType::Blur
             || self.blur_intensity > 0.0001
             || self.bg_dim > 0.0001
             || self.bg_desat > 0.0001
+            || self.silhouette_active()
+            || self.grading_active()
+    }
+
+    pub(crate) fn grading_active(&self) -> bool {
+        !self.fg_grade.is_neutral()
+            || !self.bg_grade.is_neutral()
+            || (self.lut_enabled && self.lut_amount > 0.0001)
+    }
+
+    // Any of outline, glow or silhouette shadow is visible.
+    pub(crate) fn silhouette_active(&self) -> bool {
+        self.sil_outline_width > 0.0001
+            || self.sil_glow_size > 0.0001
+            || self.sil_shadow_opacity > 0.0001
+    }
+
+    pub(crate) fn mask_rate_target(&self) -> Option<MaskRateTarget> {
+        match self.mask_rate_mode {
+            MaskRateMode::Fixed => None,
+            MaskRateMode::AutoCpuBudget => {
+                Some(MaskRateTarget::CpuBudget(self.mask_cpu_budget / 100.0))
+            }
+            MaskRateMode::AutoLatencyCeiling => {
+                Some(MaskRateTarget::LatencyCeilingMs(self.mask_latency_ceiling_ms))
+            }
+        }
     }
 
     pub(crate) fn needs_background_composite(&self) -> bool {
-        self.blur_intensity > 0.0001 || self.bg_dim > 0.0001 || self.bg_desat > 0.0001
+        self.bg_type != BackgroundType::Blur
+            || self.blur_intensity > 0.0001 || self.bg_dim > 0.0001 || self.bg_desat > 0.0001
+            || self.silhouette_active()
+            || self.grading_active()
     }
 
     pub(crate) unsafe fn load(settings: *mut obs::obs_data_t) -> Self {
@@ -116,9 +613,26 @@ impl FilterSettings {
         let mut s = Self::default();
 
         s.blur_intensity = obs::obs_data_get_double(settings, cstr(SETTING_BLUR_INTENSITY)) as f32;
+        s.blur_quality =
+            BlurQuality::from_i64(obs::obs_data_get_int(settings, cstr(SETTING_BLUR_QUALITY)));
+        s.blur_style = BlurStyle::from_i64(obs::obs_data_get_int(settings, cstr(SETTING_BLUR_STYLE)));
+        s.bokeh_shape =
+            BokehShape::from_i64(obs::obs_data_get_int(settings, cstr(SETTING_BOKEH_SHAPE)));
+        s.bokeh_highlights =
+            obs::obs_data_get_double(settings, cstr(SETTING_BOKEH_HIGHLIGHTS)) as f32;
+        s.depth_blur = obs::obs_data_get_bool(settings, cstr(SETTING_DEPTH_BLUR));
+        s.depth_falloff = obs::obs_data_get_double(settings, cstr(SETTING_DEPTH_FALLOFF)) as f32;
+        s.blur_mask_aware = obs::obs_data_get_bool(settings, cstr(SETTING_BLUR_MASK_AWARE));
         s.debug_show_mask = obs::obs_data_get_bool(settings, cstr(SETTING_DEBUG_SHOW_MASK));
+        s.debug_mask_view =
+            DebugMaskView::from_i64(obs::obs_data_get_int(settings, cstr(SETTING_DEBUG_MASK_VIEW)));
 
         s.mask_fps = obs::obs_data_get_int(settings, cstr(SETTING_MASK_FPS)).max(1) as u32;
+        s.mask_rate_mode =
+            MaskRateMode::from_i64(obs::obs_data_get_int(settings, cstr(SETTING_MASK_RATE_MODE)));
+        s.mask_cpu_budget = obs::obs_data_get_int(settings, cstr(SETTING_MASK_CPU_BUDGET)) as f32;
+        s.mask_latency_ceiling_ms =
+            obs::obs_data_get_int(settings, cstr(SETTING_MASK_LATENCY_CEILING_MS)) as f32;
         s.mask_temporal_smoothing =
             obs::obs_data_get_double(settings, cstr(SETTING_MASK_TEMPORAL)) as f32;
         s.mask_threshold = obs::obs_data_get_double(settings, cstr(SETTING_MASK_THRESHOLD)) as f32;
@@ -126,12 +640,45 @@ impl FilterSettings {
         s.mask_invert = obs::obs_data_get_bool(settings, cstr(SETTING_MASK_INVERT));
         s.sync_video_extra_delay_ms =
             obs::obs_data_get_int(settings, cstr(SETTING_SYNC_VIDEO_EXTRA_DELAY_MS)) as f32;
+        s.history_max_frames =
+            obs::obs_data_get_int(settings, cstr(SETTING_HISTORY_MAX_FRAMES)).max(2) as u32;
+        s.audio_compensate = obs::obs_data_get_bool(settings, cstr(SETTING_AUDIO_COMPENSATE));
+        s.bg_type = BackgroundType::from_i64(obs::obs_data_get_int(settings, cstr(SETTING_BG_TYPE)));
+        s.bg_image_fit =
+            BackgroundFit::from_i64(obs::obs_data_get_int(settings, cstr(SETTING_BG_IMAGE_FIT)));
+        s.bg_colors_abgr = [
+            obs::obs_data_get_int(settings, cstr(SETTING_BG_COLOR_1)) as u32,
+            obs::obs_data_get_int(settings, cstr(SETTING_BG_COLOR_2)) as u32,
+            obs::obs_data_get_int(settings, cstr(SETTING_BG_COLOR_3)) as u32,
+            obs::obs_data_get_int(settings, cstr(SETTING_BG_COLOR_4)) as u32,
+        ];
+        s.bg_gradient_stops =
+            obs::obs_data_get_int(settings, cstr(SETTING_BG_GRADIENT_STOPS)).clamp(2, 4) as u32;
+        s.bg_gradient_angle =
+            obs::obs_data_get_double(settings, cstr(SETTING_BG_GRADIENT_ANGLE)) as f32;
+        s.bg_anim_speed = obs::obs_data_get_double(settings, cstr(SETTING_BG_ANIM_SPEED)) as f32;
+        s.bg_plate_freeze = obs::obs_data_get_bool(settings, cstr(SETTING_BG_PLATE_FREEZE));
+        s.bg_privacy_style =
+            PrivacyStyle::from_i64(obs::obs_data_get_int(settings, cstr(SETTING_BG_PRIVACY_STYLE)));
+        s.bg_privacy_block = obs::obs_data_get_double(settings, cstr(SETTING_BG_PRIVACY_BLOCK))
+            .clamp(4.0, 128.0) as f32;
         s.bg_dim = obs::obs_data_get_double(settings, cstr(SETTING_BG_DIM)) as f32;
         s.bg_desat = obs::obs_data_get_double(settings, cstr(SETTING_BG_DESAT)) as f32;
+        s.edge_decontam = obs::obs_data_get_double(settings, cstr(SETTING_EDGE_DECONTAM)) as f32;
+        s.light_wrap = obs::obs_data_get_double(settings, cstr(SETTING_LIGHT_WRAP)) as f32;
+        s.light_wrap_width =
+            obs::obs_data_get_double(settings, cstr(SETTING_LIGHT_WRAP_WIDTH)) as f32;
 
         s.shape_type = obs::obs_data_get_int(settings, cstr(SETTING_SHAPE_TYPE)) as i32;
         s.corner_radius = obs::obs_data_get_double(settings, cstr(SETTING_CORNER_RADIUS)) as f32;
         s.feather = obs::obs_data_get_double(settings, cstr(SETTING_FEATHER)) as f32;
+        s.shape_exponent = obs::obs_data_get_double(settings, cstr(SETTING_SHAPE_EXPONENT))
+            .clamp(2.0, 12.0) as f32;
+        s.shape_sides =
+            obs::obs_data_get_int(settings, cstr(SETTING_SHAPE_SIDES)).clamp(3, 12) as u32;
+        s.shape_inner_radius = obs::obs_data_get_double(settings, cstr(SETTING_SHAPE_INNER_RADIUS))
+            .clamp(0.1, 0.95) as f32;
+        s.shape_rotation = obs::obs_data_get_double(settings, cstr(SETTING_SHAPE_ROTATION)) as f32;
         s.border_thickness =
             obs::obs_data_get_double(settings, cstr(SETTING_BORDER_THICKNESS)) as f32;
         s.border_color_argb = obs::obs_data_get_int(settings, cstr(SETTING_BORDER_COLOR)) as u32;
@@ -149,30 +696,300 @@ impl FilterSettings {
             obs::obs_data_get_double(settings, cstr(SETTING_SHADOW_OFFSET_Y)) as f32;
         s.shadow_color_argb = obs::obs_data_get_int(settings, cstr(SETTING_SHADOW_COLOR)) as u32;
 
+        s.sil_outline_width =
+            obs::obs_data_get_double(settings, cstr(SETTING_SIL_OUTLINE_WIDTH)) as f32;
+        s.sil_outline_color_argb =
+            obs::obs_data_get_int(settings, cstr(SETTING_SIL_OUTLINE_COLOR)) as u32;
+        s.sil_softness = obs::obs_data_get_double(settings, cstr(SETTING_SIL_SOFTNESS)) as f32;
+        s.sil_glow_size = obs::obs_data_get_double(settings, cstr(SETTING_SIL_GLOW_SIZE)) as f32;
+        s.sil_glow_color_argb =
+            obs::obs_data_get_int(settings, cstr(SETTING_SIL_GLOW_COLOR)) as u32;
+        s.sil_shadow_opacity =
+            obs::obs_data_get_double(settings, cstr(SETTING_SIL_SHADOW_OPACITY)) as f32;
+        s.sil_shadow_blur =
+            obs::obs_data_get_double(settings, cstr(SETTING_SIL_SHADOW_BLUR)) as f32;
+        s.sil_shadow_offset_x =
+            obs::obs_data_get_double(settings, cstr(SETTING_SIL_SHADOW_OFFSET_X)) as f32;
+        s.sil_shadow_offset_y =
+            obs::obs_data_get_double(settings, cstr(SETTING_SIL_SHADOW_OFFSET_Y)) as f32;
+        s.sil_shadow_color_argb =
+            obs::obs_data_get_int(settings, cstr(SETTING_SIL_SHADOW_COLOR)) as u32;
+
+        s.fg_grade = load_grade(settings, &FG_GRADE_KEYS);
+        s.bg_grade = load_grade(settings, &BG_GRADE_KEYS);
+        s.lut_target =
+            LutTarget::from_i64(obs::obs_data_get_int(settings, cstr(SETTING_LUT_TARGET)));
+        s.lut_amount = obs::obs_data_get_double(settings, cstr(SETTING_LUT_AMOUNT)) as f32;
+
         s
     }
 }
 
+// Setting keys of one layer's grade (foreground or background).
+struct GradeKeys {
+    exposure: &'static [u8],
+    contrast: &'static [u8],
+    saturation: &'static [u8],
+    temperature: &'static [u8],
+    tint: &'static [u8],
+    vignette: &'static [u8],
+}
+
+impl GradeKeys {
+    fn all(&self) -> [&'static [u8]; 6] {
+        [self.exposure, self.contrast, self.saturation, self.temperature, self.tint, self.vignette]
+    }
+}
+
+const FG_GRADE_KEYS: GradeKeys = GradeKeys {
+    exposure: SETTING_FG_EXPOSURE,
+    contrast: SETTING_FG_CONTRAST,
+    saturation: SETTING_FG_SATURATION,
+    temperature: SETTING_FG_TEMPERATURE,
+    tint: SETTING_FG_TINT,
+    vignette: SETTING_FG_VIGNETTE,
+};
+
+const BG_GRADE_KEYS: GradeKeys = GradeKeys {
+    exposure: SETTING_BG_EXPOSURE,
+    contrast: SETTING_BG_CONTRAST,
+    saturation: SETTING_BG_SATURATION,
+    temperature: SETTING_BG_TEMPERATURE,
+    tint: SETTING_BG_TINT,
+    vignette: SETTING_BG_VIGNETTE,
+};
+
+unsafe fn load_grade(settings: *mut obs::obs_data_t, keys: &GradeKeys) -> Grade {
+    let get = |key: &'static [u8]| obs::obs_data_get_double(settings, cstr(key)) as f32;
+    Grade {
+        exposure: get(keys.exposure),
+        contrast: get(keys.contrast),
+        saturation: get(keys.saturation),
+        temperature: get(keys.temperature),
+        tint: get(keys.tint),
+        vignette: get(keys.vignette),
+    }
+}
+
+unsafe fn add_grade_group(
+    props: *mut obs::obs_properties_t,
+    group: &'static [u8],
+    label: &'static [u8],
+    keys: &GradeKeys,
+) {
+    let grade_props = obs::obs_properties_create();
+    if grade_props.is_null() {
+        return;
+    }
+
+    let sliders = [
+        (keys.exposure, PROP_GRADE_EXPOSURE, -3.0, 3.0),
+        (keys.contrast, PROP_GRADE_CONTRAST, -1.0, 1.0),
+        (keys.saturation, PROP_GRADE_SATURATION, -1.0, 1.0),
+        (keys.temperature, PROP_GRADE_TEMPERATURE, -1.0, 1.0),
+        (keys.tint, PROP_GRADE_TINT, -1.0, 1.0),
+        (keys.vignette, PROP_GRADE_VIGNETTE, 0.0, 1.0),
+    ];
+    for (key, prop, min, max) in sliders {
+        obs::obs_properties_add_float_slider(grade_props, cstr(key), cstr(prop), min, max, 0.01);
+    }
+
+    obs::obs_properties_add_group(
+        props,
+        cstr(group),
+        cstr(label),
+        obs::obs_group_type_OBS_GROUP_NORMAL,
+        grade_props,
+    );
+}
+
+// Name of the source whose audio offset is compensated; None means the filtered source.
+pub(crate) unsafe fn load_audio_sync_state(
+    settings: *mut obs::obs_data_t,
+) -> Option<SyncOffsetState> {
+    let saved = !settings.is_null()
+        && obs::obs_data_has_user_value(settings, cstr(SETTING_AUDIO_SYNC_BASE_OFFSET))
+        && obs::obs_data_has_user_value(settings, cstr(SETTING_AUDIO_SYNC_WRITTEN_OFFSET));
+    saved.then(|| SyncOffsetState {
+        base_offset_ns: obs::obs_data_get_int(settings, cstr(SETTING_AUDIO_SYNC_BASE_OFFSET)),
+        written_offset_ns: obs::obs_data_get_int(settings, cstr(SETTING_AUDIO_SYNC_WRITTEN_OFFSET)),
+    })
+}
+
+pub(crate) unsafe fn save_audio_sync_state(
+    settings: *mut obs::obs_data_t,
+    state: Option<SyncOffsetState>,
+) {
+    if settings.is_null() {
+        return;
+    }
+    match state {
+        Some(state) => {
+            let base = cstr(SETTING_AUDIO_SYNC_BASE_OFFSET);
+            obs::obs_data_set_int(settings, base, state.base_offset_ns);
+            let written = cstr(SETTING_AUDIO_SYNC_WRITTEN_OFFSET);
+            obs::obs_data_set_int(settings, written, state.written_offset_ns);
+        }
+        None => {
+            obs::obs_data_erase(settings, cstr(SETTING_AUDIO_SYNC_BASE_OFFSET));
+            obs::obs_data_erase(settings, cstr(SETTING_AUDIO_SYNC_WRITTEN_OFFSET));
+        }
+    }
+}
+
+pub(crate) unsafe fn load_audio_sync_source(settings: *mut obs::obs_data_t) -> Option<CString> {
+    if settings.is_null() {
+        return None;
+    }
+
+    let name = obs::obs_data_get_string(settings, cstr(SETTING_AUDIO_SYNC_SOURCE));
+    if name.is_null() {
+        return None;
+    }
+    let name = CStr::from_ptr(name);
+    if name.is_empty() {
+        None
+    } else {
+        Some(name.to_owned())
+    }
+}
+
+// Custom shape image file; None unless the Custom image shape is selected.
+pub(crate) unsafe fn load_shape_image_path(settings: *mut obs::obs_data_t) -> Option<CString> {
+    // Shape type 10 is Custom image.
+    if settings.is_null() || obs::obs_data_get_int(settings, cstr(SETTING_SHAPE_TYPE)) != 10 {
+        return None;
+    }
+
+    let path = obs::obs_data_get_string(settings, cstr(SETTING_SHAPE_IMAGE_PATH));
+    if path.is_null() {
+        return None;
+    }
+    let path = CStr::from_ptr(path);
+    if path.is_empty() {
+        None
+    } else {
+        Some(path.to_owned())
+    }
+}
+
+// Background image file; None unless the Image background type is selected.
+pub(crate) unsafe fn load_bg_image_path(settings: *mut obs::obs_data_t) -> Option<CString> {
+    if settings.is_null() {
+        return None;
+    }
+    let bg_type = BackgroundType::from_i64(obs::obs_data_get_int(settings, cstr(SETTING_BG_TYPE)));
+    if bg_type != BackgroundType::Image {
+        return None;
+    }
+
+    let path = obs::obs_data_get_string(settings, cstr(SETTING_BG_IMAGE_PATH));
+    if path.is_null() {
+        return None;
+    }
+    let path = CStr::from_ptr(path);
+    if path.is_empty() {
+        None
+    } else {
+        Some(path.to_owned())
+    }
+}
+
+// .cube LUT file; None when no file is selected.
+pub(crate) unsafe fn load_lut_path(settings: *mut obs::obs_data_t) -> Option<CString> {
+    if settings.is_null() {
+        return None;
+    }
+    let path = obs::obs_data_get_string(settings, cstr(SETTING_LUT_PATH));
+    if path.is_null() {
+        return None;
+    }
+    let path = CStr::from_ptr(path);
+    if path.is_empty() {
+        None
+    } else {
+        Some(path.to_owned())
+    }
+}
+
+// Background source name; None unless the Source background type is selected.
+pub(crate) unsafe fn load_bg_source_name(settings: *mut obs::obs_data_t) -> Option<CString> {
+    if settings.is_null() {
+        return None;
+    }
+    let bg_type = BackgroundType::from_i64(obs::obs_data_get_int(settings, cstr(SETTING_BG_TYPE)));
+    if bg_type != BackgroundType::Source {
+        return None;
+    }
+
+    let name = obs::obs_data_get_string(settings, cstr(SETTING_BG_SOURCE));
+    if name.is_null() {
+        return None;
+    }
+    let name = CStr::from_ptr(name);
+    if name.is_empty() {
+        None
+    } else {
+        Some(name.to_owned())
+    }
+}
+
 pub(crate) unsafe fn set_defaults(settings: *mut obs::obs_data_t) {
     if settings.is_null() {
         return;
     }
 
     obs::obs_data_set_default_double(settings, cstr(SETTING_BLUR_INTENSITY), 0.0);
+    obs::obs_data_set_default_int(settings, cstr(SETTING_BLUR_QUALITY), 1);
+    obs::obs_data_set_default_int(settings, cstr(SETTING_BLUR_STYLE), 0);
+    obs::obs_data_set_default_int(settings, cstr(SETTING_BOKEH_SHAPE), 0);
+    obs::obs_data_set_default_double(settings, cstr(SETTING_BOKEH_HIGHLIGHTS), 0.5);
+    obs::obs_data_set_default_bool(settings, cstr(SETTING_DEPTH_BLUR), false);
+    obs::obs_data_set_default_double(settings, cstr(SETTING_DEPTH_FALLOFF), 0.35);
+    obs::obs_data_set_default_bool(settings, cstr(SETTING_BLUR_MASK_AWARE), true);
     obs::obs_data_set_default_bool(settings, cstr(SETTING_DEBUG_SHOW_MASK), false);
+    obs::obs_data_set_default_int(settings, cstr(SETTING_DEBUG_MASK_VIEW), 0);
 
     obs::obs_data_set_default_int(settings, cstr(SETTING_MASK_FPS), 15);
+    obs::obs_data_set_default_int(settings, cstr(SETTING_MASK_RATE_MODE), 0);
+    obs::obs_data_set_default_int(settings, cstr(SETTING_MASK_CPU_BUDGET), 25);
+    obs::obs_data_set_default_int(settings, cstr(SETTING_MASK_LATENCY_CEILING_MS), 120);
     obs::obs_data_set_default_double(settings, cstr(SETTING_MASK_TEMPORAL), 0.4);
     obs::obs_data_set_default_double(settings, cstr(SETTING_MASK_THRESHOLD), 0.5);
     obs::obs_data_set_default_double(settings, cstr(SETTING_MASK_SOFTNESS), 0.1);
     obs::obs_data_set_default_bool(settings, cstr(SETTING_MASK_INVERT), false);
     obs::obs_data_set_default_int(settings, cstr(SETTING_SYNC_VIDEO_EXTRA_DELAY_MS), 0);
+    obs::obs_data_set_default_int(settings, cstr(SETTING_HISTORY_MAX_FRAMES), 32);
+    obs::obs_data_set_default_bool(settings, cstr(SETTING_AUDIO_COMPENSATE), false);
+    obs::obs_data_set_default_string(settings, cstr(SETTING_AUDIO_SYNC_SOURCE), cstr(b"\0"));
+    obs::obs_data_set_default_int(settings, cstr(SETTING_BG_TYPE), 0);
+    obs::obs_data_set_default_string(settings, cstr(SETTING_BG_IMAGE_PATH), cstr(b"\0"));
+    obs::obs_data_set_default_int(settings, cstr(SETTING_BG_IMAGE_FIT), 0);
+    obs::obs_data_set_default_string(settings, cstr(SETTING_BG_SOURCE), cstr(b"\0"));
+    let color_keys = [SETTING_BG_COLOR_1, SETTING_BG_COLOR_2, SETTING_BG_COLOR_3, SETTING_BG_COLOR_4];
+    for (key, color) in color_keys.into_iter().zip(BG_COLOR_DEFAULTS) {
+        obs::obs_data_set_default_int(settings, cstr(key), color as i64);
+    }
+    obs::obs_data_set_default_int(settings, cstr(SETTING_BG_GRADIENT_STOPS), 2);
+    obs::obs_data_set_default_double(settings, cstr(SETTING_BG_GRADIENT_ANGLE), 90.0);
+    obs::obs_data_set_default_double(settings, cstr(SETTING_BG_ANIM_SPEED), 1.0);
+    obs::obs_data_set_default_bool(settings, cstr(SETTING_BG_PLATE_FREEZE), false);
+    obs::obs_data_set_default_int(settings, cstr(SETTING_BG_PRIVACY_STYLE), 0);
+    obs::obs_data_set_default_double(settings, cstr(SETTING_BG_PRIVACY_BLOCK), 32.0);
     obs::obs_data_set_default_double(settings, cstr(SETTING_BG_DIM), 0.0);
     obs::obs_data_set_default_double(settings, cstr(SETTING_BG_DESAT), 0.0);
+    obs::obs_data_set_default_double(settings, cstr(SETTING_EDGE_DECONTAM), 0.0);
+    obs::obs_data_set_default_double(settings, cstr(SETTING_LIGHT_WRAP), 0.0);
+    obs::obs_data_set_default_double(settings, cstr(SETTING_LIGHT_WRAP_WIDTH), 16.0);
 
     obs::obs_data_set_default_int(settings, cstr(SETTING_SHAPE_TYPE), 0);
     obs::obs_data_set_default_double(settings, cstr(SETTING_CORNER_RADIUS), 24.0);
     obs::obs_data_set_default_double(settings, cstr(SETTING_FEATHER), 1.5);
+    obs::obs_data_set_default_double(settings, cstr(SETTING_SHAPE_EXPONENT), 4.0);
+    obs::obs_data_set_default_int(settings, cstr(SETTING_SHAPE_SIDES), 5);
+    obs::obs_data_set_default_double(settings, cstr(SETTING_SHAPE_INNER_RADIUS), 0.5);
+    obs::obs_data_set_default_double(settings, cstr(SETTING_SHAPE_ROTATION), 0.0);
+    obs::obs_data_set_default_string(settings, cstr(SETTING_SHAPE_IMAGE_PATH), cstr(b"\0"));
     obs::obs_data_set_default_double(settings, cstr(SETTING_BORDER_THICKNESS), 0.0);
     obs::obs_data_set_default_int(settings, cstr(SETTING_BORDER_COLOR), 0xFFFFFFFFu32 as i64);
     obs::obs_data_set_default_double(settings, cstr(SETTING_PADDING), 0.0);
@@ -186,18 +1003,83 @@ pub(crate) unsafe fn set_defaults(settings: *mut obs::obs_data_t) {
     obs::obs_data_set_default_double(settings, cstr(SETTING_SHADOW_OFFSET_X), 0.0);
     obs::obs_data_set_default_double(settings, cstr(SETTING_SHADOW_OFFSET_Y), -4.0);
     obs::obs_data_set_default_int(settings, cstr(SETTING_SHADOW_COLOR), 0xFF000000u32 as i64);
+
+    obs::obs_data_set_default_double(settings, cstr(SETTING_SIL_OUTLINE_WIDTH), 0.0);
+    obs::obs_data_set_default_int(settings, cstr(SETTING_SIL_OUTLINE_COLOR), 0xFFFFFFFFu32 as i64);
+    obs::obs_data_set_default_double(settings, cstr(SETTING_SIL_SOFTNESS), 1.5);
+    obs::obs_data_set_default_double(settings, cstr(SETTING_SIL_GLOW_SIZE), 0.0);
+    obs::obs_data_set_default_int(settings, cstr(SETTING_SIL_GLOW_COLOR), 0xFFFFFFFFu32 as i64);
+    obs::obs_data_set_default_double(settings, cstr(SETTING_SIL_SHADOW_OPACITY), 0.0);
+    obs::obs_data_set_default_double(settings, cstr(SETTING_SIL_SHADOW_BLUR), 8.0);
+    obs::obs_data_set_default_double(settings, cstr(SETTING_SIL_SHADOW_OFFSET_X), 8.0);
+    obs::obs_data_set_default_double(settings, cstr(SETTING_SIL_SHADOW_OFFSET_Y), 8.0);
+    obs::obs_data_set_default_int(settings, cstr(SETTING_SIL_SHADOW_COLOR), 0xFF000000u32 as i64);
+
+    obs::obs_data_set_default_string(settings, cstr(SETTING_LUT_PATH), cstr(b"\0"));
+    obs::obs_data_set_default_int(settings, cstr(SETTING_LUT_TARGET), 2);
+    obs::obs_data_set_default_double(settings, cstr(SETTING_LUT_AMOUNT), 1.0);
+    for keys in [&FG_GRADE_KEYS, &BG_GRADE_KEYS] {
+        for key in keys.all() {
+            obs::obs_data_set_default_double(settings, cstr(key), 0.0);
+        }
+    }
 }
 
-pub(crate) unsafe fn get_properties() -> *mut obs::obs_properties_t {
+pub(crate) unsafe fn get_properties(
+    status_text: &CStr,
+    shape_image_status: &CStr,
+    on_status_refresh: obs::obs_property_clicked_t,
+    filter_parent: *mut obs::obs_source_t,
+) -> *mut obs::obs_properties_t {
     let props = obs::obs_properties_create();
     if props.is_null() {
         return props;
     }
 
+    // Status (read-only; refreshed when properties are reopened or via the button)
+    let status_props = obs::obs_properties_create();
+    if !status_props.is_null() {
+        // An info text with an empty value renders its description as the full-width text.
+        obs::obs_properties_add_text(
+            status_props,
+            cstr(SETTING_SEG_STATUS),
+            status_text.as_ptr(),
+            obs::obs_text_type_OBS_TEXT_INFO,
+        );
+        obs::obs_properties_add_button(
+            status_props,
+            cstr(SETTING_SEG_STATUS_REFRESH),
+            cstr(PROP_SEG_STATUS_REFRESH),
+            on_status_refresh,
+        );
+
+        obs::obs_properties_add_group(
+            props,
+            cstr(GROUP_STATUS),
+            cstr(GROUP_LABEL_STATUS),
+            obs::obs_group_type_OBS_GROUP_NORMAL,
+            status_props,
+        );
+    }
+
     // Segmentation
     let seg_props = obs::obs_properties_create();
     if !seg_props.is_null() {
-        obs::obs_properties_add_int_slider(
+        let rate_list = obs::obs_properties_add_list(
+            seg_props,
+            cstr(SETTING_MASK_RATE_MODE),
+            cstr(PROP_MASK_RATE_MODE),
+            obs::obs_combo_type_OBS_COMBO_TYPE_LIST,
+            obs::obs_combo_format_OBS_COMBO_FORMAT_INT,
+        );
+        if !rate_list.is_null() {
+            obs::obs_property_list_add_int(rate_list, cstr(b"Fixed\0"), 0);
+            obs::obs_property_list_add_int(rate_list, cstr(b"Auto (CPU budget)\0"), 1);
+            obs::obs_property_list_add_int(rate_list, cstr(b"Auto (latency ceiling)\0"), 2);
+        }
+        obs::obs_property_set_modified_callback(rate_list, Some(on_mask_rate_mode_modified));
+
+        let p = obs::obs_properties_add_int_slider(
             seg_props,
             cstr(SETTING_MASK_FPS),
             cstr(PROP_MASK_FPS),
@@ -205,6 +1087,25 @@ pub(crate) unsafe fn get_properties() -> *mut obs::obs_properties_t {
             60,
             1,
         );
+        obs::obs_property_set_long_description(p, cstr(PROP_MASK_FPS_LONG));
+        let p = obs::obs_properties_add_int_slider(
+            seg_props,
+            cstr(SETTING_MASK_CPU_BUDGET),
+            cstr(PROP_MASK_CPU_BUDGET),
+            5,
+            100,
+            1,
+        );
+        obs::obs_property_set_visible(p, false);
+        let p = obs::obs_properties_add_int_slider(
+            seg_props,
+            cstr(SETTING_MASK_LATENCY_CEILING_MS),
+            cstr(PROP_MASK_LATENCY_CEILING_MS),
+            20,
+            500,
+            5,
+        );
+        obs::obs_property_set_visible(p, false);
         obs::obs_properties_add_float_slider(
             seg_props,
             cstr(SETTING_MASK_TEMPORAL),
@@ -240,9 +1141,169 @@ pub(crate) unsafe fn get_properties() -> *mut obs::obs_properties_t {
         );
     }
 
-    // Background (blur, dim, desat)
+    // Audio sync
+    let audio_props = obs::obs_properties_create();
+    if !audio_props.is_null() {
+        let p = obs::obs_properties_add_bool(
+            audio_props,
+            cstr(SETTING_AUDIO_COMPENSATE),
+            cstr(PROP_AUDIO_COMPENSATE),
+        );
+        obs::obs_property_set_long_description(p, cstr(PROP_AUDIO_COMPENSATE_LONG));
+        obs::obs_property_set_modified_callback(p, Some(on_audio_compensate_modified));
+
+        let source_list = obs::obs_properties_add_list(
+            audio_props,
+            cstr(SETTING_AUDIO_SYNC_SOURCE),
+            cstr(PROP_AUDIO_SYNC_SOURCE),
+            obs::obs_combo_type_OBS_COMBO_TYPE_LIST,
+            obs::obs_combo_format_OBS_COMBO_FORMAT_STRING,
+        );
+        if !source_list.is_null() {
+            obs::obs_property_list_add_string(source_list, cstr(PROP_AUDIO_SYNC_SOURCE_PARENT), cstr(b"\0"));
+            obs::obs_enum_sources(Some(add_audio_source_to_list), source_list.cast());
+            obs::obs_property_set_visible(source_list, false);
+        }
+
+        obs::obs_properties_add_group(
+            props,
+            cstr(GROUP_AUDIO_SYNC),
+            cstr(GROUP_LABEL_AUDIO_SYNC),
+            obs::obs_group_type_OBS_GROUP_NORMAL,
+            audio_props,
+        );
+    }
+
+    // Background (type, blur, dim, desat)
     let bg_props = obs::obs_properties_create();
     if !bg_props.is_null() {
+        let bg_list = obs::obs_properties_add_list(
+            bg_props,
+            cstr(SETTING_BG_TYPE),
+            cstr(PROP_BG_TYPE),
+            obs::obs_combo_type_OBS_COMBO_TYPE_LIST,
+            obs::obs_combo_format_OBS_COMBO_FORMAT_INT,
+        );
+        if !bg_list.is_null() {
+            obs::obs_property_list_add_int(bg_list, cstr(b"Blur\0"), 0);
+            obs::obs_property_list_add_int(bg_list, cstr(b"Remove (transparent)\0"), 1);
+            obs::obs_property_list_add_int(bg_list, cstr(b"Image\0"), 2);
+            obs::obs_property_list_add_int(bg_list, cstr(b"Source\0"), 3);
+            obs::obs_property_list_add_int(bg_list, cstr(b"Solid color\0"), 4);
+            obs::obs_property_list_add_int(bg_list, cstr(b"Linear gradient\0"), 5);
+            obs::obs_property_list_add_int(bg_list, cstr(b"Radial gradient\0"), 6);
+            obs::obs_property_list_add_int(bg_list, cstr(b"Conic gradient\0"), 7);
+            obs::obs_property_list_add_int(bg_list, cstr(b"Animated noise\0"), 8);
+            obs::obs_property_list_add_int(bg_list, cstr(b"Aurora\0"), 9);
+            obs::obs_property_list_add_int(bg_list, cstr(b"Clean plate (learned background)\0"), 10);
+            obs::obs_property_list_add_int(bg_list, cstr(b"Privacy (pixelate)\0"), 11);
+        }
+        obs::obs_property_set_modified_callback(bg_list, Some(on_bg_type_modified));
+
+        let p = obs::obs_properties_add_path(
+            bg_props,
+            cstr(SETTING_BG_IMAGE_PATH),
+            cstr(PROP_BG_IMAGE_PATH),
+            obs::obs_path_type_OBS_PATH_FILE,
+            cstr(PROP_BG_IMAGE_PATH_FILTER),
+            std::ptr::null(),
+        );
+        obs::obs_property_set_visible(p, false);
+        let fit_list = obs::obs_properties_add_list(
+            bg_props,
+            cstr(SETTING_BG_IMAGE_FIT),
+            cstr(PROP_BG_IMAGE_FIT),
+            obs::obs_combo_type_OBS_COMBO_TYPE_LIST,
+            obs::obs_combo_format_OBS_COMBO_FORMAT_INT,
+        );
+        if !fit_list.is_null() {
+            obs::obs_property_list_add_int(fit_list, cstr(b"Cover\0"), 0);
+            obs::obs_property_list_add_int(fit_list, cstr(b"Contain\0"), 1);
+            obs::obs_property_list_add_int(fit_list, cstr(b"Stretch\0"), 2);
+            obs::obs_property_list_add_int(fit_list, cstr(b"Tile\0"), 3);
+        }
+        obs::obs_property_set_visible(fit_list, false);
+
+        let source_list = obs::obs_properties_add_list(
+            bg_props,
+            cstr(SETTING_BG_SOURCE),
+            cstr(PROP_BG_SOURCE),
+            obs::obs_combo_type_OBS_COMBO_TYPE_LIST,
+            obs::obs_combo_format_OBS_COMBO_FORMAT_STRING,
+        );
+        if !source_list.is_null() {
+            let mut param = SourceListParam { list: source_list, exclude: filter_parent };
+            let param_ptr: *mut SourceListParam = &mut param;
+            obs::obs_enum_scenes(Some(add_video_source_to_list), param_ptr.cast());
+            obs::obs_enum_sources(Some(add_video_source_to_list), param_ptr.cast());
+            obs::obs_property_set_visible(source_list, false);
+        }
+
+        let p = obs::obs_properties_add_int_slider(
+            bg_props,
+            cstr(SETTING_BG_GRADIENT_STOPS),
+            cstr(PROP_BG_GRADIENT_STOPS),
+            2,
+            4,
+            1,
+        );
+        obs::obs_property_set_modified_callback(p, Some(on_bg_type_modified));
+        obs::obs_property_set_visible(p, false);
+        for (key, label) in [
+            (SETTING_BG_COLOR_1, PROP_BG_COLOR_1),
+            (SETTING_BG_COLOR_2, PROP_BG_COLOR_2),
+            (SETTING_BG_COLOR_3, PROP_BG_COLOR_3),
+            (SETTING_BG_COLOR_4, PROP_BG_COLOR_4),
+        ] {
+            let p = obs::obs_properties_add_color(bg_props, cstr(key), cstr(label));
+            obs::obs_property_set_visible(p, false);
+        }
+        let p = obs::obs_properties_add_float_slider(
+            bg_props,
+            cstr(SETTING_BG_GRADIENT_ANGLE),
+            cstr(PROP_BG_GRADIENT_ANGLE),
+            0.0,
+            360.0,
+            1.0,
+        );
+        obs::obs_property_set_visible(p, false);
+        let p = obs::obs_properties_add_float_slider(
+            bg_props,
+            cstr(SETTING_BG_ANIM_SPEED),
+            cstr(PROP_BG_ANIM_SPEED),
+            0.0,
+            5.0,
+            0.05,
+        );
+        obs::obs_property_set_visible(p, false);
+        let p = obs::obs_properties_add_bool(
+            bg_props,
+            cstr(SETTING_BG_PLATE_FREEZE),
+            cstr(PROP_BG_PLATE_FREEZE),
+        );
+        obs::obs_property_set_visible(p, false);
+        let privacy_list = obs::obs_properties_add_list(
+            bg_props,
+            cstr(SETTING_BG_PRIVACY_STYLE),
+            cstr(PROP_BG_PRIVACY_STYLE),
+            obs::obs_combo_type_OBS_COMBO_TYPE_LIST,
+            obs::obs_combo_format_OBS_COMBO_FORMAT_INT,
+        );
+        if !privacy_list.is_null() {
+            obs::obs_property_list_add_int(privacy_list, cstr(b"Mosaic\0"), 0);
+            obs::obs_property_list_add_int(privacy_list, cstr(b"Scramble (quantize + noise)\0"), 1);
+        }
+        obs::obs_property_set_visible(privacy_list, false);
+        let p = obs::obs_properties_add_float_slider(
+            bg_props,
+            cstr(SETTING_BG_PRIVACY_BLOCK),
+            cstr(PROP_BG_PRIVACY_BLOCK),
+            4.0,
+            128.0,
+            1.0,
+        );
+        obs::obs_property_set_visible(p, false);
+
         obs::obs_properties_add_float_slider(
             bg_props,
             cstr(SETTING_BLUR_INTENSITY),
@@ -251,6 +1312,68 @@ pub(crate) unsafe fn get_properties() -> *mut obs::obs_properties_t {
             1.0,
             0.005,
         );
+        let style_list = obs::obs_properties_add_list(
+            bg_props,
+            cstr(SETTING_BLUR_STYLE),
+            cstr(PROP_BLUR_STYLE),
+            obs::obs_combo_type_OBS_COMBO_TYPE_LIST,
+            obs::obs_combo_format_OBS_COMBO_FORMAT_INT,
+        );
+        if !style_list.is_null() {
+            obs::obs_property_list_add_int(style_list, cstr(b"Gaussian\0"), 0);
+            obs::obs_property_list_add_int(style_list, cstr(b"Lens (bokeh)\0"), 1);
+        }
+        obs::obs_property_set_modified_callback(style_list, Some(on_bg_type_modified));
+        let shape_list = obs::obs_properties_add_list(
+            bg_props,
+            cstr(SETTING_BOKEH_SHAPE),
+            cstr(PROP_BOKEH_SHAPE),
+            obs::obs_combo_type_OBS_COMBO_TYPE_LIST,
+            obs::obs_combo_format_OBS_COMBO_FORMAT_INT,
+        );
+        if !shape_list.is_null() {
+            obs::obs_property_list_add_int(shape_list, cstr(b"Disk\0"), 0);
+            obs::obs_property_list_add_int(shape_list, cstr(b"Hexagon\0"), 1);
+        }
+        obs::obs_property_set_visible(shape_list, false);
+        let p = obs::obs_properties_add_float_slider(
+            bg_props,
+            cstr(SETTING_BOKEH_HIGHLIGHTS),
+            cstr(PROP_BOKEH_HIGHLIGHTS),
+            0.0,
+            1.0,
+            0.01,
+        );
+        obs::obs_property_set_visible(p, false);
+        let quality_list = obs::obs_properties_add_list(
+            bg_props,
+            cstr(SETTING_BLUR_QUALITY),
+            cstr(PROP_BLUR_QUALITY),
+            obs::obs_combo_type_OBS_COMBO_TYPE_LIST,
+            obs::obs_combo_format_OBS_COMBO_FORMAT_INT,
+        );
+        if !quality_list.is_null() {
+            obs::obs_property_list_add_int(quality_list, cstr(b"Fast\0"), 0);
+            obs::obs_property_list_add_int(quality_list, cstr(b"Balanced\0"), 1);
+            obs::obs_property_list_add_int(quality_list, cstr(b"High\0"), 2);
+        }
+        let p =
+            obs::obs_properties_add_bool(bg_props, cstr(SETTING_DEPTH_BLUR), cstr(PROP_DEPTH_BLUR));
+        obs::obs_property_set_modified_callback(p, Some(on_bg_type_modified));
+        let p = obs::obs_properties_add_float_slider(
+            bg_props,
+            cstr(SETTING_DEPTH_FALLOFF),
+            cstr(PROP_DEPTH_FALLOFF),
+            0.02,
+            1.0,
+            0.01,
+        );
+        obs::obs_property_set_visible(p, false);
+        obs::obs_properties_add_bool(
+            bg_props,
+            cstr(SETTING_BLUR_MASK_AWARE),
+            cstr(PROP_BLUR_MASK_AWARE),
+        );
         obs::obs_properties_add_float_slider(
             bg_props,
             cstr(SETTING_BG_DIM),
@@ -267,6 +1390,30 @@ pub(crate) unsafe fn get_properties() -> *mut obs::obs_properties_t {
             1.0,
             0.005,
         );
+        obs::obs_properties_add_float_slider(
+            bg_props,
+            cstr(SETTING_EDGE_DECONTAM),
+            cstr(PROP_EDGE_DECONTAM),
+            0.0,
+            1.0,
+            0.01,
+        );
+        obs::obs_properties_add_float_slider(
+            bg_props,
+            cstr(SETTING_LIGHT_WRAP),
+            cstr(PROP_LIGHT_WRAP),
+            0.0,
+            1.0,
+            0.01,
+        );
+        obs::obs_properties_add_float_slider(
+            bg_props,
+            cstr(SETTING_LIGHT_WRAP_WIDTH),
+            cstr(PROP_LIGHT_WRAP_WIDTH),
+            2.0,
+            64.0,
+            1.0,
+        );
 
         obs::obs_properties_add_group(
             props,
@@ -277,6 +1424,134 @@ pub(crate) unsafe fn get_properties() -> *mut obs::obs_properties_t {
         );
     }
 
+    // Silhouette outline / glow / shadow (follows the person, not the card shape)
+    let sil_props = obs::obs_properties_create();
+    if !sil_props.is_null() {
+        obs::obs_properties_add_float_slider(
+            sil_props,
+            cstr(SETTING_SIL_OUTLINE_WIDTH),
+            cstr(PROP_SIL_OUTLINE_WIDTH),
+            0.0,
+            40.0,
+            0.5,
+        );
+        obs::obs_properties_add_color_alpha(
+            sil_props,
+            cstr(SETTING_SIL_OUTLINE_COLOR),
+            cstr(PROP_SIL_OUTLINE_COLOR),
+        );
+        obs::obs_properties_add_float_slider(
+            sil_props,
+            cstr(SETTING_SIL_SOFTNESS),
+            cstr(PROP_SIL_SOFTNESS),
+            0.0,
+            20.0,
+            0.5,
+        );
+        obs::obs_properties_add_float_slider(
+            sil_props,
+            cstr(SETTING_SIL_GLOW_SIZE),
+            cstr(PROP_SIL_GLOW_SIZE),
+            0.0,
+            80.0,
+            1.0,
+        );
+        obs::obs_properties_add_color_alpha(
+            sil_props,
+            cstr(SETTING_SIL_GLOW_COLOR),
+            cstr(PROP_SIL_GLOW_COLOR),
+        );
+        obs::obs_properties_add_float_slider(
+            sil_props,
+            cstr(SETTING_SIL_SHADOW_OPACITY),
+            cstr(PROP_SIL_SHADOW_OPACITY),
+            0.0,
+            1.0,
+            0.005,
+        );
+        obs::obs_properties_add_float_slider(
+            sil_props,
+            cstr(SETTING_SIL_SHADOW_BLUR),
+            cstr(PROP_SIL_SHADOW_BLUR),
+            0.0,
+            40.0,
+            0.5,
+        );
+        obs::obs_properties_add_float_slider(
+            sil_props,
+            cstr(SETTING_SIL_SHADOW_OFFSET_X),
+            cstr(PROP_SIL_SHADOW_OFFSET_X),
+            -60.0,
+            60.0,
+            1.0,
+        );
+        obs::obs_properties_add_float_slider(
+            sil_props,
+            cstr(SETTING_SIL_SHADOW_OFFSET_Y),
+            cstr(PROP_SIL_SHADOW_OFFSET_Y),
+            -60.0,
+            60.0,
+            1.0,
+        );
+        obs::obs_properties_add_color_alpha(
+            sil_props,
+            cstr(SETTING_SIL_SHADOW_COLOR),
+            cstr(PROP_SIL_SHADOW_COLOR),
+        );
+
+        obs::obs_properties_add_group(
+            props,
+            cstr(GROUP_SILHOUETTE),
+            cstr(GROUP_LABEL_SILHOUETTE),
+            obs::obs_group_type_OBS_GROUP_NORMAL,
+            sil_props,
+        );
+    }
+
+    add_grade_group(props, GROUP_FG_GRADE, GROUP_LABEL_FG_GRADE, &FG_GRADE_KEYS);
+    add_grade_group(props, GROUP_BG_GRADE, GROUP_LABEL_BG_GRADE, &BG_GRADE_KEYS);
+
+    // LUT (.cube file applied through the mask)
+    let lut_props = obs::obs_properties_create();
+    if !lut_props.is_null() {
+        obs::obs_properties_add_path(
+            lut_props,
+            cstr(SETTING_LUT_PATH),
+            cstr(PROP_LUT_PATH),
+            obs::obs_path_type_OBS_PATH_FILE,
+            cstr(PROP_LUT_PATH_FILTER),
+            std::ptr::null(),
+        );
+        let target_list = obs::obs_properties_add_list(
+            lut_props,
+            cstr(SETTING_LUT_TARGET),
+            cstr(PROP_LUT_TARGET),
+            obs::obs_combo_type_OBS_COMBO_TYPE_LIST,
+            obs::obs_combo_format_OBS_COMBO_FORMAT_INT,
+        );
+        if !target_list.is_null() {
+            obs::obs_property_list_add_int(target_list, cstr(b"Foreground\0"), 0);
+            obs::obs_property_list_add_int(target_list, cstr(b"Background\0"), 1);
+            obs::obs_property_list_add_int(target_list, cstr(b"Both\0"), 2);
+        }
+        obs::obs_properties_add_float_slider(
+            lut_props,
+            cstr(SETTING_LUT_AMOUNT),
+            cstr(PROP_LUT_AMOUNT),
+            0.0,
+            1.0,
+            0.01,
+        );
+
+        obs::obs_properties_add_group(
+            props,
+            cstr(GROUP_LUT),
+            cstr(GROUP_LABEL_LUT),
+            obs::obs_group_type_OBS_GROUP_NORMAL,
+            lut_props,
+        );
+    }
+
     // Shape (type + edge + padding)
     let shape_props = obs::obs_properties_create();
     if !shape_props.is_null() {
@@ -293,9 +1568,33 @@ pub(crate) unsafe fn get_properties() -> *mut obs::obs_properties_t {
             obs::obs_property_list_add_int(shape_list, cstr(b"Rounded rectangle\0"), 2);
             obs::obs_property_list_add_int(shape_list, cstr(b"Square\0"), 3);
             obs::obs_property_list_add_int(shape_list, cstr(b"Vertical rectangle\0"), 4);
+            obs::obs_property_list_add_int(shape_list, cstr(b"Squircle\0"), 5);
+            obs::obs_property_list_add_int(shape_list, cstr(b"Polygon\0"), 6);
+            obs::obs_property_list_add_int(shape_list, cstr(b"Hexagon\0"), 7);
+            obs::obs_property_list_add_int(shape_list, cstr(b"Star\0"), 8);
+            obs::obs_property_list_add_int(shape_list, cstr(b"Heart\0"), 9);
+            obs::obs_property_list_add_int(shape_list, cstr(b"Custom image\0"), 10);
         }
         obs::obs_property_set_modified_callback(shape_list, Some(on_shape_type_modified));
 
+        let p = obs::obs_properties_add_path(
+            shape_props,
+            cstr(SETTING_SHAPE_IMAGE_PATH),
+            cstr(PROP_SHAPE_IMAGE_PATH),
+            obs::obs_path_type_OBS_PATH_FILE,
+            cstr(PROP_SHAPE_IMAGE_PATH_FILTER),
+            std::ptr::null(),
+        );
+        obs::obs_property_set_visible(p, false);
+        // Load failures only reach the log otherwise; refreshed with the status group.
+        let p = obs::obs_properties_add_text(
+            shape_props,
+            cstr(SETTING_SHAPE_IMAGE_STATUS),
+            shape_image_status.as_ptr(),
+            obs::obs_text_type_OBS_TEXT_INFO,
+        );
+        obs::obs_property_set_visible(p, false);
+
         obs::obs_properties_add_float_slider(
             shape_props,
             cstr(SETTING_CORNER_RADIUS),
@@ -304,6 +1603,42 @@ pub(crate) unsafe fn get_properties() -> *mut obs::obs_properties_t {
             2000.0,
             1.0,
         );
+        let p = obs::obs_properties_add_float_slider(
+            shape_props,
+            cstr(SETTING_SHAPE_EXPONENT),
+            cstr(PROP_SHAPE_EXPONENT),
+            2.0,
+            12.0,
+            0.1,
+        );
+        obs::obs_property_set_visible(p, false);
+        let p = obs::obs_properties_add_int_slider(
+            shape_props,
+            cstr(SETTING_SHAPE_SIDES),
+            cstr(PROP_SHAPE_SIDES),
+            3,
+            12,
+            1,
+        );
+        obs::obs_property_set_visible(p, false);
+        let p = obs::obs_properties_add_float_slider(
+            shape_props,
+            cstr(SETTING_SHAPE_INNER_RADIUS),
+            cstr(PROP_SHAPE_INNER_RADIUS),
+            0.1,
+            0.95,
+            0.01,
+        );
+        obs::obs_property_set_visible(p, false);
+        let p = obs::obs_properties_add_float_slider(
+            shape_props,
+            cstr(SETTING_SHAPE_ROTATION),
+            cstr(PROP_SHAPE_ROTATION),
+            -180.0,
+            180.0,
+            1.0,
+        );
+        obs::obs_property_set_visible(p, false);
 
         let p = obs::obs_properties_add_float_slider(
             shape_props,
@@ -447,11 +1782,37 @@ pub(crate) unsafe fn get_properties() -> *mut obs::obs_properties_t {
             250,
             1,
         );
-        obs::obs_properties_add_bool(
+        let p = obs::obs_properties_add_int(
+            debug_props,
+            cstr(SETTING_HISTORY_MAX_FRAMES),
+            cstr(PROP_HISTORY_MAX_FRAMES),
+            2,
+            120,
+            1,
+        );
+        obs::obs_property_set_long_description(p, cstr(PROP_HISTORY_MAX_FRAMES_LONG));
+        let p = obs::obs_properties_add_bool(
             debug_props,
             cstr(SETTING_DEBUG_SHOW_MASK),
             cstr(PROP_DEBUG_SHOW_MASK),
         );
+        obs::obs_property_set_modified_callback(p, Some(on_debug_show_mask_modified));
+        let view_list = obs::obs_properties_add_list(
+            debug_props,
+            cstr(SETTING_DEBUG_MASK_VIEW),
+            cstr(PROP_DEBUG_MASK_VIEW),
+            obs::obs_combo_type_OBS_COMBO_TYPE_LIST,
+            obs::obs_combo_format_OBS_COMBO_FORMAT_INT,
+        );
+        if !view_list.is_null() {
+            obs::obs_property_list_add_int(view_list, cstr(b"Raw mask\0"), 0);
+            obs::obs_property_list_add_int(view_list, cstr(b"Color overlay\0"), 1);
+            obs::obs_property_list_add_int(view_list, cstr(b"Thresholded (binary)\0"), 2);
+            obs::obs_property_list_add_int(view_list, cstr(b"Edge band heatmap\0"), 3);
+            obs::obs_property_list_add_int(view_list, cstr(b"Side by side (raw / refined)\0"), 4);
+            obs::obs_property_list_add_int(view_list, cstr(b"Segmentation input\0"), 5);
+        }
+        obs::obs_property_set_visible(view_list, false);
 
         obs::obs_properties_add_group(
             props,
@@ -0,0 +1,178 @@
+use std::ffi::{CStr, CString};
+
+use obs_sys as obs;
+use styledcamera_core::half::f16_bits_to_f32;
+use styledcamera_core::shape::ImageShapeField;
+
+use crate::file_loader::{FileLoader, LoadEvent};
+use crate::util::cstr;
+
+// Custom card shape: signed distance field of an image's alpha (see ImageShapeField), uploaded
+// as a float texture for shape_style.effect.
+pub(crate) struct ShapeImage {
+    texture: *mut obs::gs_texture_t,
+    size: (u32, u32),
+    loader: FileLoader<ImageShapeField>,
+}
+
+impl Default for ShapeImage {
+    fn default() -> Self {
+        Self {
+            texture: std::ptr::null_mut(),
+            size: (0, 0),
+            loader: FileLoader::new(load_field),
+        }
+    }
+}
+
+impl ShapeImage {
+    // Starts building the shape when the path or the file changed; decoding and the distance
+    // field run on the loader's thread, see poll.
+    pub(crate) fn set_path(&self, path: Option<CString>) {
+        self.loader.set_path(path);
+    }
+
+    pub(crate) fn reload_if_modified(&self) {
+        self.loader.reload_if_modified();
+    }
+
+    // Uploads a finished shape. Must be called on the graphics thread.
+    pub(crate) unsafe fn poll(&mut self) {
+        let Some(event) = self.loader.take_event() else {
+            return;
+        };
+
+        obs::obs_enter_graphics();
+        self.destroy_texture();
+        match event {
+            LoadEvent::Loaded(field) => {
+                let (w, h) = (field.width as u32, field.height as u32);
+                let mut data_ptrs = [field.data.as_ptr().cast::<u8>()];
+                self.texture = obs::gs_texture_create(
+                    w,
+                    h,
+                    obs::gs_color_format_GS_R32F,
+                    1,
+                    data_ptrs.as_mut_ptr(),
+                    0,
+                );
+                if !self.texture.is_null() {
+                    self.size = (w, h);
+                }
+            }
+            LoadEvent::Failed(err) => {
+                let path = self.loader.path().unwrap_or_default();
+                let msg = CString::new(err).unwrap_or_default();
+                obs::blog(
+                    obs::LOG_WARNING as i32,
+                    cstr(b"StyledCamera: failed to load shape image '%s': %s\n\0"),
+                    path.as_ptr(),
+                    msg.as_ptr(),
+                );
+            }
+            LoadEvent::Cleared => {}
+        }
+        obs::obs_leave_graphics();
+    }
+
+    // One line for the properties UI.
+    pub(crate) fn status(&self) -> String {
+        if let Some(err) = self.loader.error() {
+            return format!("Could not load the shape image: {err}");
+        }
+        if !self.texture.is_null() {
+            "Shape image loaded".to_owned()
+        } else if self.loader.path().is_some() {
+            "Loading the shape image...".to_owned()
+        } else {
+            "No shape image selected".to_owned()
+        }
+    }
+
+    pub(crate) fn texture(&self) -> *mut obs::gs_texture_t {
+        self.texture
+    }
+
+    // Field size in texels; (0, 0) while no shape is loaded.
+    pub(crate) fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    pub(crate) unsafe fn free(&mut self) {
+        self.loader.set_path(None);
+        obs::obs_enter_graphics();
+        self.destroy_texture();
+        obs::obs_leave_graphics();
+    }
+
+    // Must be called while in graphics context.
+    unsafe fn destroy_texture(&mut self) {
+        if !self.texture.is_null() {
+            obs::gs_texture_destroy(self.texture);
+        }
+        self.texture = std::ptr::null_mut();
+        self.size = (0, 0);
+    }
+}
+
+fn load_field(path: &CStr) -> Result<ImageShapeField, String> {
+    // gs_image_file_t is plain data; all-zero is its "not loaded" state.
+    let mut file: obs::gs_image_file_t = unsafe { std::mem::zeroed() };
+    let alpha = unsafe {
+        obs::gs_image_file_init(&mut file, path.as_ptr());
+        let alpha = image_alpha(&file);
+        obs::obs_enter_graphics();
+        obs::gs_image_file_free(&mut file);
+        obs::obs_leave_graphics();
+        alpha
+    };
+
+    let (alpha, w, h) = alpha?;
+    ImageShapeField::from_alpha(&alpha, w, h)
+        .ok_or_else(|| "the image has no opaque pixels".to_string())
+}
+
+// Alpha channel of the decoded (not yet uploaded) pixels, as 8 bits per pixel.
+// gs_image_file_t carries no buffer length: libobs decodes into cx * cy tightly packed pixels in
+// native byte order, so the length is derived from the format and checked for overflow.
+unsafe fn image_alpha(file: &obs::gs_image_file_t) -> Result<(Vec<u8>, usize, usize), String> {
+    if !file.loaded || file.texture_data.is_null() {
+        return Err("could not decode the image".to_string());
+    }
+    let (w, h) = (file.cx as usize, file.cy as usize);
+    let (bytes_per_pixel, alpha_of): (usize, fn(&[u8]) -> u8) = match file.format {
+        obs::gs_color_format_GS_RGBA
+        | obs::gs_color_format_GS_BGRA
+        | obs::gs_color_format_GS_RGBA_UNORM
+        | obs::gs_color_format_GS_BGRA_UNORM => (4, |px| px[3]),
+        // No alpha: the whole image is the shape.
+        obs::gs_color_format_GS_BGRX | obs::gs_color_format_GS_BGRX_UNORM => (4, |_| 255),
+        // Single channel: a grayscale mask, white is the shape.
+        obs::gs_color_format_GS_A8 | obs::gs_color_format_GS_R8 => (1, |px| px[0]),
+        obs::gs_color_format_GS_R16 => (2, |px| (u16::from_ne_bytes([px[0], px[1]]) >> 8) as u8),
+        // 16-bit PNGs and the like.
+        obs::gs_color_format_GS_RGBA16 => (8, |px| (u16::from_ne_bytes([px[6], px[7]]) >> 8) as u8),
+        obs::gs_color_format_GS_R10G10B10A2 => (4, |px| {
+            (u32::from_ne_bytes([px[0], px[1], px[2], px[3]]) >> 30) as u8 * 85
+        }),
+        obs::gs_color_format_GS_RGBA16F => (8, |px| {
+            unit_to_u8(f16_bits_to_f32(u16::from_ne_bytes([px[6], px[7]])))
+        }),
+        obs::gs_color_format_GS_RGBA32F => (16, |px| {
+            unit_to_u8(f32::from_ne_bytes([px[12], px[13], px[14], px[15]]))
+        }),
+        format => return Err(format!("unsupported pixel format ({format})")),
+    };
+    let len = w
+        .checked_mul(h)
+        .and_then(|n| n.checked_mul(bytes_per_pixel))
+        .filter(|&n| n > 0 && n <= isize::MAX as usize)
+        .ok_or_else(|| format!("invalid image size ({w}x{h})"))?;
+    let data = std::slice::from_raw_parts(file.texture_data, len);
+    let alpha = data.chunks_exact(bytes_per_pixel).map(alpha_of).collect();
+    Ok((alpha, w, h))
+}
+
+fn unit_to_u8(v: f32) -> u8 {
+    (v.clamp(0.0, 1.0) * 255.0).round() as u8
+}